@@ -7,6 +7,7 @@ def wave_point(py_img: numpy.ndarray, ctr_x: float, ctr_y: float, u: float) -> n
     :param ctr_x: pixel centre position
     :param ctr_y: pixel centre position
     :param u:
+    :raises ValueError: if the image is empty or any float argument is not finite
     """
     ...
 
@@ -26,8 +27,29 @@ def star_pattern(py_img: numpy.ndarray, ctr_x: float, ctr_y: float, point_count:
     :param py_img: image as array, expects shape() = (x,y,3)
     :param ctr_x: pixel centre position
     :param ctr_y: pixel centre position
-    :param point_count: number of points in the pattern
+    :param point_count: number of points in the pattern, at least 1
     :param u:
+    :raises ValueError: if the image is empty or any float argument is not finite
+    """
+    ...
+
+
+def set_num_threads(n: int) -> None:
+    """
+    set the number of worker threads used by every py_lens function, the pool is shared by the
+    lens and window functions. use 1 inside multiprocessing workers so each process uses one thread
+
+    :param n: number of threads, 0 goes back to the default: the `PY_LENS_NUM_THREADS`
+        environment variable if it is set, otherwise the number of available cpus
+    :raises ValueError: if n is 0 and `PY_LENS_NUM_THREADS` is not a positive integer
+    """
+    ...
+
+
+def get_num_threads() -> int:
+    """
+    :return: number of worker threads used by py_lens functions
+    :raises ValueError: if `PY_LENS_NUM_THREADS` is not a positive integer
     """
     ...
 
@@ -46,67 +68,2206 @@ def line_to_image(py_img: numpy.ndarray, shape0: int, shape1: int) -> numpy.ndar
     :param shape0:  shape of output image (should be the same as what was put into image_to_line)
     :param py_img: 2d numpy array
     :return: 3d numpy array,
+    :raises ValueError: if py_img has fewer rows than image_to_line creates for an image of shape (shape0, shape1)
     """
     ...
 
 
-def windowed_rms_single(py_img: numpy.ndarray, window_size: int, ddof1: bool) -> numpy.ndarray:
+def windowed_rms_single(py_img: numpy.ndarray, window_size: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
     """
     rms function over a windowed array, based on image processing, ignores the 3rd layer
 
-    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
     :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
-    :param ddof1: if true set ddof value to 1, else ddof is 0
-    :return: numpy array with dimensions of the input[x,y,z] - [w-1, w-1, 0]
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype,
+        e.g. 2.0 reproduces the rms of py_lens 0.2 and earlier
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
     """
 
 
-def windowed_rms_double(py_img: numpy.ndarray, window_size: tuple[int, int], ddof1: bool) -> numpy.ndarray:
+def windowed_rms_double(py_img: numpy.ndarray, window_size: tuple[int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
     """
     rms function over a windowed array, based on image processing, ignores the 3rd layer
 
-    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
     :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
-    :param ddof1: if true set ddof value to 1, else ddof is 0
-    :return: numpy array with dimensions of the input[x,y,z] - [w0-1, w1-1, 0]
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype,
+        e.g. 2.0 reproduces the rms of py_lens 0.2 and earlier
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
     """
 
 
-def windowed_rms_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], ddof1: bool) -> numpy.ndarray:
+def windowed_rms_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
     """
     rms function over a windowed array,
 
-    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
     :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
-    :param ddof1: if true set ddof value to 1, else ddof is 0
-    :return: numpy array with dimensions of the input[x,y,z] - [w0-1, w1-1, w2-1]
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype,
+        e.g. 2.0 reproduces the rms of py_lens 0.2 and earlier
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
     """
 
-def windowed_stdev_single(py_img: numpy.ndarray, window_size: int) -> numpy.ndarray:
+def windowed_stdev_single(py_img: numpy.ndarray, window_size: int, ddof1: bool, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
     """
     standard deviation over a windowed array, based on image processing, ignores the 3rd layer
 
-    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
     :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
-    :return: numpy array with dimensions of the input[x,y,z] - [w-1, w-1, 0]
+    :param ddof1: if true set ddof value to 1, else ddof is 0
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
     """
 
 
-def windowed_stdev_double(py_img: numpy.ndarray, window_size: tuple[int, int]) -> numpy.ndarray:
+def windowed_stdev_double(py_img: numpy.ndarray, window_size: tuple[int, int], ddof1: bool, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
     """
     standard deviation over a windowed array, based on image processing, ignores the 3rd layer
 
-    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
     :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
-    :return: numpy array with dimensions of the input[x,y,z] - [w0-1, w1-1, 0]
+    :param ddof1: if true set ddof value to 1, else ddof is 0
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
     """
 
 
-def windowed_stdev_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int]) -> numpy.ndarray:
+def windowed_stdev_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], ddof1: bool, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
     """
     standard deviation over a windowed array,
 
-    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param ddof1: if true set ddof value to 1, else ddof is 0
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_mean_single(py_img: numpy.ndarray, window_size: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    mean over a windowed array, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_mean_double(py_img: numpy.ndarray, window_size: tuple[int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    mean over a windowed array, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_mean_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    mean over a windowed array
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_variance_single(py_img: numpy.ndarray, window_size: int, ddof1: bool, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    variance over a windowed array, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param ddof1: if true set ddof value to 1, else ddof is 0
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_variance_double(py_img: numpy.ndarray, window_size: tuple[int, int], ddof1: bool, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    variance over a windowed array, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param ddof1: if true set ddof value to 1, else ddof is 0
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_variance_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], ddof1: bool, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    variance over a windowed array
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param ddof1: if true set ddof value to 1, else ddof is 0
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_min_single(py_img: numpy.ndarray, window_size: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    smallest value in each window, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_min_double(py_img: numpy.ndarray, window_size: tuple[int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    smallest value in each window, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_min_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    smallest value in each window
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_max_single(py_img: numpy.ndarray, window_size: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    largest value in each window, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_max_double(py_img: numpy.ndarray, window_size: tuple[int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    largest value in each window, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_max_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    largest value in each window
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_range_single(py_img: numpy.ndarray, window_size: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    largest minus smallest value in each window, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_range_double(py_img: numpy.ndarray, window_size: tuple[int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    largest minus smallest value in each window, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_range_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    largest minus smallest value in each window
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_median_single(py_img: numpy.ndarray, window_size: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    median of each window, the mean of the two middle values for windows with an even size, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_median_double(py_img: numpy.ndarray, window_size: tuple[int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    median of each window, the mean of the two middle values for windows with an even size, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_median_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    median of each window, the mean of the two middle values for windows with an even size
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_percentile_single(py_img: numpy.ndarray, window_size: int, q: float, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    q-th percentile of each window, interpolated linearly like the default of `numpy.percentile`, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param q: percentile between 0 and 100, 50 is the median
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        scale is not finite, or q is not between 0 and 100
+    """
+
+
+def windowed_percentile_double(py_img: numpy.ndarray, window_size: tuple[int, int], q: float, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    q-th percentile of each window, interpolated linearly like the default of `numpy.percentile`, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param q: percentile between 0 and 100, 50 is the median
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        scale is not finite, or q is not between 0 and 100
+    """
+
+
+def windowed_percentile_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], q: float, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    q-th percentile of each window, interpolated linearly like the default of `numpy.percentile`
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
     :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
-    :return: numpy array with dimensions of the input[x,y,z] - [w0-1, w1-1, w2-1]
+    :param q: percentile between 0 and 100, 50 is the median
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        scale is not finite, or q is not between 0 and 100
+    """
+
+
+def windowed_rank_single(py_img: numpy.ndarray, window_size: int, rank: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    rank-th smallest value of each window counting from 0, 0 is the minimum and the window size - 1
+    the maximum, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param rank: position in the sorted window, below the number of values it uses
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        scale is not finite, or rank is not below the number of values in a window
+    """
+
+
+def windowed_rank_double(py_img: numpy.ndarray, window_size: tuple[int, int], rank: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    rank-th smallest value of each window counting from 0, 0 is the minimum and the window size - 1
+    the maximum, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param rank: position in the sorted window, below the number of values it uses
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        scale is not finite, or rank is not below the number of values in a window
+    """
+
+
+def windowed_rank_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], rank: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    rank-th smallest value of each window counting from 0, 0 is the minimum and the window size - 1
+    the maximum
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param rank: position in the sorted window, below the number of values it uses
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        scale is not finite, or rank is not below the number of values in a window
+    """
+
+
+def windowed_mode_single(py_img: numpy.ndarray, window_size: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    most common value in each window, the smallest of them on a tie, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_mode_double(py_img: numpy.ndarray, window_size: tuple[int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    most common value in each window, the smallest of them on a tie, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_mode_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    most common value in each window, the smallest of them on a tie
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_skewness_single(py_img: numpy.ndarray, window_size: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    skewness of each window, biased like `scipy.stats.skew`, with an integer output
+    flat windows give 0 and unsigned dtypes clip negative values to 0, use output_dtype='float64'
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None or 'float64' return float64, skewness isn't in the units of the
+        input so it is never rounded to an integer dtype, 'float32' returns float32
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_skewness_double(py_img: numpy.ndarray, window_size: tuple[int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    skewness of each window, biased like `scipy.stats.skew`, with an integer output
+    flat windows give 0 and unsigned dtypes clip negative values to 0, use output_dtype='float64'
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None or 'float64' return float64, skewness isn't in the units of the
+        input so it is never rounded to an integer dtype, 'float32' returns float32
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_skewness_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    skewness of each window, biased like `scipy.stats.skew`, with an integer output
+    flat windows give 0 and unsigned dtypes clip negative values to 0, use output_dtype='float64'
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None or 'float64' return float64, skewness isn't in the units of the
+        input so it is never rounded to an integer dtype, 'float32' returns float32
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_kurtosis_single(py_img: numpy.ndarray, window_size: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    excess kurtosis of each window, biased like `scipy.stats.kurtosis`, with an integer
+    output flat windows give 0 and unsigned dtypes clip negative values to 0, use output_dtype='float64'
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None or 'float64' return float64, kurtosis isn't in the units of the
+        input so it is never rounded to an integer dtype, 'float32' returns float32
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_kurtosis_double(py_img: numpy.ndarray, window_size: tuple[int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    excess kurtosis of each window, biased like `scipy.stats.kurtosis`, with an integer
+    output flat windows give 0 and unsigned dtypes clip negative values to 0, use output_dtype='float64'
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None or 'float64' return float64, kurtosis isn't in the units of the
+        input so it is never rounded to an integer dtype, 'float32' returns float32
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_kurtosis_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    excess kurtosis of each window, biased like `scipy.stats.kurtosis`, with an integer
+    output flat windows give 0 and unsigned dtypes clip negative values to 0, use output_dtype='float64'
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None or 'float64' return float64, kurtosis isn't in the units of the
+        input so it is never rounded to an integer dtype, 'float32' returns float32
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_mean_abs_dev_single(py_img: numpy.ndarray, window_size: int, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    mean absolute deviation from the mean of each window, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_mean_abs_dev_double(py_img: numpy.ndarray, window_size: tuple[int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    mean absolute deviation from the mean of each window, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_mean_abs_dev_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    mean absolute deviation from the mean of each window
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_entropy_single(py_img: numpy.ndarray, window_size: int, /, bins: int = 256, value_range: tuple[float, float] | None = None, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    shannon entropy in bits of the histogram of each window, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param bins: number of equal width histogram bins, up to 65536, windows without a dilation or
+        footprint slide one histogram along each row so the bin count doesn't change the speed
+    :param value_range: (lo, hi) split into the bins, values outside go to the first or last bin,
+        None uses the smallest and largest finite value of the image, which gives every value of
+        a uint8 image its own bin, use bins=65536, value_range=(0, 65536) for the same with uint16
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None or 'float64' return float64, entropy isn't in the units of the
+        input so it is never rounded to an integer dtype, 'float32' returns float32
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if bins is not between 1 and 65536, value_range is not increasing, any
+        window size, stride or dilation is 0, the window covers more than the matching image
+        dimension with mode='valid', mode / padding / output_dtype is not one of the listed names,
+        the footprint doesn't have the shape of the window or is all False, or a float argument is
+        not finite
+    """
+
+
+def windowed_entropy_double(py_img: numpy.ndarray, window_size: tuple[int, int], /, bins: int = 256, value_range: tuple[float, float] | None = None, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    shannon entropy in bits of the histogram of each window, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param bins: number of equal width histogram bins, up to 65536, windows without a dilation or
+        footprint slide one histogram along each row so the bin count doesn't change the speed
+    :param value_range: (lo, hi) split into the bins, values outside go to the first or last bin,
+        None uses the smallest and largest finite value of the image, which gives every value of
+        a uint8 image its own bin, use bins=65536, value_range=(0, 65536) for the same with uint16
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None or 'float64' return float64, entropy isn't in the units of the
+        input so it is never rounded to an integer dtype, 'float32' returns float32
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if bins is not between 1 and 65536, value_range is not increasing, any
+        window size, stride or dilation is 0, the window covers more than the matching image
+        dimension with mode='valid', mode / padding / output_dtype is not one of the listed names,
+        the footprint doesn't have the shape of the window or is all False, or a float argument is
+        not finite
+    """
+
+
+def windowed_entropy_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], /, bins: int = 256, value_range: tuple[float, float] | None = None, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    shannon entropy in bits of the histogram of each window
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param bins: number of equal width histogram bins, up to 65536, windows without a dilation or
+        footprint slide one histogram along each row so the bin count doesn't change the speed
+    :param value_range: (lo, hi) split into the bins, values outside go to the first or last bin,
+        None uses the smallest and largest finite value of the image, which gives every value of
+        a uint8 image its own bin, use bins=65536, value_range=(0, 65536) for the same with uint16
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None or 'float64' return float64, entropy isn't in the units of the
+        input so it is never rounded to an integer dtype, 'float32' returns float32
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if bins is not between 1 and 65536, value_range is not increasing, any
+        window size, stride or dilation is 0, the window covers more than the matching image
+        dimension with mode='valid', mode / padding / output_dtype is not one of the listed names,
+        the footprint doesn't have the shape of the window or is all False, or a float argument is
+        not finite
+    """
+
+
+def windowed_fraction_above_single(py_img: numpy.ndarray, window_size: int, threshold: float, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    fraction of each window strictly above threshold, between 0 and 1, use output_dtype='float32'
+    or a scale such as 100.0 with integer output, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param threshold: values above this count, NaN never does
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None or 'float64' return float64, a fraction isn't in the units of the
+        input so it is never rounded to an integer dtype, 'float32' returns float32
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w-1, w-1, 0] for 'valid',
+        the input shape for 'same', input + [w-1, w-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        scale or threshold is not finite
+    """
+
+
+def windowed_fraction_above_double(py_img: numpy.ndarray, window_size: tuple[int, int], threshold: float, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    fraction of each window strictly above threshold, between 0 and 1, use output_dtype='float32'
+    or a scale such as 100.0 with integer output, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param threshold: values above this count, NaN never does
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None or 'float64' return float64, a fraction isn't in the units of the
+        input so it is never rounded to an integer dtype, 'float32' returns float32
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, 0] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, 0] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        scale or threshold is not finite
+    """
+
+
+def windowed_fraction_above_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], threshold: float, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    fraction of each window strictly above threshold, between 0 and 1, use output_dtype='float32'
+    or a scale such as 100.0 with integer output
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param threshold: values above this count, NaN never does
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None or 'float64' return float64, a fraction isn't in the units of the
+        input so it is never rounded to an integer dtype, 'float32' returns float32
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array, see window_output_shape for its shape with a stride or dilation,
+        without them the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid',
+        the input shape for 'same', input + [w0-1, w1-1, w2-1] for 'full'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', mode / padding / output_dtype is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False,
+        scale or threshold is not finite
+    """
+
+
+def windowed_stats_single(py_img: numpy.ndarray, window_size: int, stats: list[str], /, ddof1: bool = False, as_dict: bool = False, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int = 1, dilation: int = 1, output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray | dict[str, numpy.ndarray]:
+    """
+    several statistics of each window in one pass, each window is read once and statistics that
+    share work, such as mean / stdev / rms or median / percentiles, compute it once, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w,w,1] to traverse the array
+    :param stats: names of the statistics, any of 'rms', 'stdev', 'mean', 'variance', 'min', 'max',
+        'range', 'median', 'mode', 'skewness', 'kurtosis', 'mean_abs_dev', or 'p<q>' for the q-th
+        percentile such as 'p90', each gives the same values as its windowed_<stat> function
+    :param ddof1: use a ddof of 1 for 'stdev' and 'variance', 'rms' always uses 0
+    :param as_dict: return a dict from each name in stats to its own array instead of one array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w,w], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array of shape (x', y', z', len(stats)) with the statistics along the last axis in
+        the order of stats, (x', y', z') as for the other windowed functions, see
+        window_output_shape, without a stride or dilation the input[x,y,z] - [w-1, w-1, 0] for 'valid'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', stats is empty or has an unknown name, a
+        percentile is outside 0..100, mode / padding / output_dtype is not one of the listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_stats_double(py_img: numpy.ndarray, window_size: tuple[int, int], stats: list[str], /, ddof1: bool = False, as_dict: bool = False, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int] = (1, 1), dilation: tuple[int, int] = (1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray | dict[str, numpy.ndarray]:
+    """
+    several statistics of each window in one pass, each window is read once and statistics that
+    share work, such as mean / stdev / rms or median / percentiles, compute it once, ignores the 3rd layer
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,1] to traverse the array
+    :param stats: names of the statistics, any of 'rms', 'stdev', 'mean', 'variance', 'min', 'max',
+        'range', 'median', 'mode', 'skewness', 'kurtosis', 'mean_abs_dev', or 'p<q>' for the q-th
+        percentile such as 'p90', each gives the same values as its windowed_<stat> function
+    :param ddof1: use a ddof of 1 for 'stdev' and 'variance', 'rms' always uses 0
+    :param as_dict: return a dict from each name in stats to its own array instead of one array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on the first two axes, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on the first two axes, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1], only the elements of each window
+        where it is True are used, see footprint_disk, footprint_annulus, footprint_diamond and
+        footprint_cross
+    :return: numpy array of shape (x', y', z', len(stats)) with the statistics along the last axis in
+        the order of stats, (x', y', z') as for the other windowed functions, see
+        window_output_shape, without a stride or dilation the input[x,y,z] - [w0-1, w1-1, 0] for 'valid'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', stats is empty or has an unknown name, a
+        percentile is outside 0..100, mode / padding / output_dtype is not one of the listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_stats_triple(py_img: numpy.ndarray, window_size: tuple[int, int, int], stats: list[str], /, ddof1: bool = False, as_dict: bool = False, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: tuple[int, int, int] = (1, 1, 1), dilation: tuple[int, int, int] = (1, 1, 1), output_dtype: str | None = None, scale: float = 1.0, footprint: numpy.ndarray | None = None) -> numpy.ndarray | dict[str, numpy.ndarray]:
+    """
+    several statistics of each window in one pass, each window is read once and statistics that
+    share work, such as mean / stdev / rms or median / percentiles, compute it once
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, this will create a moving window of shape [w0,w1,w2] to traverse the array
+    :param stats: names of the statistics, any of 'rms', 'stdev', 'mean', 'variance', 'min', 'max',
+        'range', 'median', 'mode', 'skewness', 'kurtosis', 'mean_abs_dev', or 'p<q>' for the q-th
+        percentile such as 'p90', each gives the same values as its windowed_<stat> function
+    :param ddof1: use a ddof of 1 for 'stdev' and 'variance', 'rms' always uses 0
+    :param as_dict: return a dict from each name in stats to its own array instead of one array
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the window on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows on every axis, the output keeps every stride-th window
+    :param dilation: spacing between the elements of a window on every axis, a window then
+        covers (w-1)*dilation+1 elements
+    :param output_dtype: None returns the input dtype, integer dtypes round the statistic to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact statistic
+    :param scale: every statistic is multiplied by scale before the conversion to output_dtype
+    :param footprint: boolean numpy.ndarray of shape [w0,w1,w2], or [w0,w1] for w2 == 1, only the
+        elements of each window where it is True are used, see footprint_disk, footprint_annulus,
+        footprint_diamond and footprint_cross
+    :return: numpy array of shape (x', y', z', len(stats)) with the statistics along the last axis in
+        the order of stats, (x', y', z') as for the other windowed functions, see
+        window_output_shape, without a stride or dilation the input[x,y,z] - [w0-1, w1-1, w2-1] for 'valid'
+    :raises ValueError: if any window size, stride or dilation is 0, the window covers more than the
+        matching image dimension with mode='valid', stats is empty or has an unknown name, a
+        percentile is outside 0..100, mode / padding / output_dtype is not one of the listed names, the footprint doesn't have the shape of the window or is all False,
+        or scale is not finite
+    """
+
+
+def windowed_gaussian(py_img: numpy.ndarray, sigma: float | tuple[float, float] | tuple[float, float, float], stat: str, /, truncate: float = 4.0, ddof1: bool = False, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int | tuple[int, int] | tuple[int, int, int] = 1, output_dtype: str | None = None, scale: float = 1.0) -> numpy.ndarray:
+    """
+    gaussian weighted statistic of each window, smooth where the box windows of windowed_stdev_*
+    give blocky edges, the mean runs one axis at a time
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param sigma: standard deviation of the gaussian in pixels, a float applies to the first two
+        axes and leaves the 3rd at 0, a sigma of 0 gives that axis a window of 1
+    :param stat: 'mean', 'variance', 'stdev' or 'rms', the weighted versions of the windowed
+        functions of the same name, rms is the weighted stdev with a ddof of 0
+    :param ddof1: use the reliability weight correction sum(w*d^2) / (V1 - V2/V1) for 'variance'
+        and 'stdev', with V1 = sum(w) and V2 = sum(w^2), equal weights give a ddof of 1
+    :param truncate: the window reaches int(truncate * sigma + 0.5) pixels either side of the centre,
+        like `scipy.ndimage.gaussian_filter`
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the kernel on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows, an int applies to the first two axes
+    :param output_dtype: None returns the input dtype, integer dtypes round the result to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact result
+    :param scale: every result is multiplied by scale before the conversion to output_dtype
+    :return: numpy array, see window_output_shape for its shape with the window size of the kernel
+    :raises ValueError: if a sigma is negative, truncate is not above 0, stat / mode / padding /
+        output_dtype is not one of the listed names, a stride is 0, the window covers more than the
+        matching image dimension with mode='valid', or a float argument is not finite
+    """
+
+
+def windowed_kernel(py_img: numpy.ndarray, kernel: numpy.ndarray, stat: str, /, ddof1: bool = False, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int | tuple[int, int] | tuple[int, int, int] = 1, dilation: int | tuple[int, int] | tuple[int, int, int] = 1, output_dtype: str | None = None, scale: float = 1.0) -> numpy.ndarray:
+    """
+    statistic of each window weighted by a kernel, the weights don't need to sum to 1
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param kernel: float64 numpy.ndarray with 2 dimensions [k0,k1] or 3 dimensions [k0,k1,k2], the
+        window has the shape of the kernel
+    :param stat: 'mean', 'variance', 'stdev' or 'rms', the weighted versions of the windowed
+        functions of the same name, rms is the weighted stdev with a ddof of 0
+    :param ddof1: use the reliability weight correction sum(w*d^2) / (V1 - V2/V1) for 'variance'
+        and 'stdev', with V1 = sum(w) and V2 = sum(w^2), equal weights give a ddof of 1
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the kernel on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows, an int applies to the first two axes
+    :param dilation: spacing between the elements of a window, an int applies to the first two axes
+    :param output_dtype: None returns the input dtype, integer dtypes round the result to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact result
+    :param scale: every result is multiplied by scale before the conversion to output_dtype
+    :return: numpy array, see window_output_shape for its shape with the window size of the kernel
+    :raises ValueError: if the kernel is empty, has a negative or non finite weight or sums to 0,
+        stat / mode / padding / output_dtype is not one of the listed names, a stride or dilation
+        is 0, or the window covers more than the matching image dimension with mode='valid'
+    """
+
+
+def correlate(py_img: numpy.ndarray, kernel: numpy.ndarray, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int | tuple[int, int] | tuple[int, int, int] = 1, dilation: int | tuple[int, int] | tuple[int, int, int] = 1, output_dtype: str | None = None, scale: float = 1.0) -> numpy.ndarray:
+    """
+    sum of each window times the kernel, element by element, like `scipy.ndimage.correlate`,
+    kernels that are the product of one weight per axis run one axis at a time
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param kernel: float64 numpy.ndarray with 2 dimensions [k0,k1] or 3 dimensions [k0,k1,k2], the
+        window has the shape of the kernel
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the kernel on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows, an int applies to the first two axes
+    :param dilation: spacing between the elements of a window, an int applies to the first two axes
+    :param output_dtype: None returns the input dtype, integer dtypes round the result to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact result
+    :param scale: every result is multiplied by scale before the conversion to output_dtype
+    :return: numpy array, see window_output_shape for its shape with the window size of the kernel
+    :raises ValueError: if the kernel is empty or has a non finite weight, mode / padding /
+        output_dtype is not one of the listed names, a stride or dilation is 0, or the window covers
+        more than the matching image dimension with mode='valid'
+    """
+
+
+def convolve(py_img: numpy.ndarray, kernel: numpy.ndarray, /, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int | tuple[int, int] | tuple[int, int, int] = 1, dilation: int | tuple[int, int] | tuple[int, int, int] = 1, output_dtype: str | None = None, scale: float = 1.0) -> numpy.ndarray:
+    """
+    convolution of the image with the kernel, correlate with the kernel reversed on every axis
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param kernel: float64 numpy.ndarray with 2 dimensions [k0,k1] or 3 dimensions [k0,k1,k2], the
+        window has the shape of the kernel
+    :param mode: 'valid' only uses windows inside the image, 'same' centres the kernel on every pixel
+        and returns the shape of the input, 'full' uses every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows, an int applies to the first two axes
+    :param dilation: spacing between the elements of a window, an int applies to the first two axes
+    :param output_dtype: None returns the input dtype, integer dtypes round the result to the nearest
+        value and clip it to their range, 'float32' or 'float64' return the exact result
+    :param scale: every result is multiplied by scale before the conversion to output_dtype
+    :return: numpy array, see window_output_shape for its shape with the window size of the kernel
+    :raises ValueError: if the kernel is empty or has a non finite weight, mode / padding /
+        output_dtype is not one of the listed names, a stride or dilation is 0, or the window covers
+        more than the matching image dimension with mode='valid'
+    """
+
+
+def generic_window(py_img: numpy.ndarray, window_size: int | tuple[int, int] | tuple[int, int, int], func, /, batch_size: int | None = None, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int | tuple[int, int] | tuple[int, int, int] = 1, dilation: int | tuple[int, int] | tuple[int, int, int] = 1, footprint: numpy.ndarray | None = None) -> numpy.ndarray:
+    """
+    python function applied over a moving window, like scipy.ndimage.generic_filter, the windows
+    are gathered on the shared thread pool but func holds the GIL while it runs
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size, an int or tuple as given to the windowed_*_single, _double
+        or _triple functions
+    :param func: with batch_size=None called with one window of shape [w0,w1,w2] and the dtype of
+        the image, returns a float. with batch_size=n called with up to n windows stacked as
+        [k,w0,w1,w2], returns a sequence of k floats in the same order
+    :param batch_size: number of windows per call to func, None calls it once per window
+    :param mode: 'valid', 'same' or 'full', see windowed_rms_single
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows, an int applies to the first two axes like window_size
+    :param dilation: spacing between the elements of a window, an int applies to the first two axes
+    :param footprint: boolean numpy.ndarray with the shape of the window, func then gets the n
+        elements where it is True as a window of shape [1,n,1], or [k,1,n,1] with batch_size, see
+        footprint_disk, footprint_annulus, footprint_diamond and footprint_cross
+    :return: float64 numpy array, see window_output_shape for its shape
+    :raises ValueError: if any window size, stride, dilation or batch_size is 0, the window covers
+        more than the matching image dimension with mode='valid', mode / padding is not one of the
+        listed names, the footprint doesn't have the shape of the window or is all False, or func
+        returns the wrong number of values for a batch
+    :raises: any exception raised by func, the remaining windows are skipped
+    """
+
+
+def footprint_disk(radius: int, /) -> numpy.ndarray:
+    """
+    :param radius: every element within radius of the centre is True
+    :return: boolean array of shape [2*radius+1, 2*radius+1], a footprint for the windowed functions
+    """
+
+
+def footprint_annulus(inner: int, outer: int, /) -> numpy.ndarray:
+    """
+    :param inner: radius of the hole, footprint_disk(inner - 1) is removed from the ring
+    :param outer: radius of the ring, footprint_annulus(0, r) is footprint_disk(r)
+    :return: boolean array of shape [2*outer+1, 2*outer+1], a footprint for the windowed functions
+    :raises ValueError: if inner is larger than outer
+    """
+
+
+def footprint_diamond(radius: int, /) -> numpy.ndarray:
+    """
+    :param radius: every element within radius steps along rows and columns of the centre is True
+    :return: boolean array of shape [2*radius+1, 2*radius+1], a footprint for the windowed functions
+    """
+
+
+def footprint_cross(radius: int, /) -> numpy.ndarray:
+    """
+    :param radius: the centre row and column are True
+    :return: boolean array of shape [2*radius+1, 2*radius+1], a footprint for the windowed functions
+    """
+
+
+def threshold_niblack(py_img: numpy.ndarray, window_size: int | tuple[int, int] | tuple[int, int, int], /, k: float = -0.2, mode: str = 'same', padding: str = 'reflect', cval: float = 0.0) -> numpy.ndarray:
+    """
+    niblack threshold m + k * s of the mean m and population stdev s of each window
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window centred on each pixel, an int applies to the first two axes and a
+        tuple of 2 ints leaves the 3rd axis at 1, so every channel is thresholded on its own
+    :param k: weight of the stdev, a negative k puts the threshold below the window mean
+    :param mode: 'same' returns a mask with the shape of the image, 'valid' only the pixels whose
+        window is inside the image, 'full' every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :return: boolean numpy array, True where the pixel is above its threshold, invert it for dark
+        text on a light page
+    :raises ValueError: if mode / padding is not one of the listed names, a window size is 0,
+        the window covers more than the matching image dimension with mode='valid', or a float
+        argument is not finite
+    """
+
+
+def threshold_sauvola(py_img: numpy.ndarray, window_size: int | tuple[int, int] | tuple[int, int, int], /, k: float = 0.2, r: float | None = None, mode: str = 'same', padding: str = 'reflect', cval: float = 0.0) -> numpy.ndarray:
+    """
+    sauvola threshold m * (1 + k * (s / r - 1)) of the mean m and population stdev s of each
+    window, less sensitive to the background level than niblack
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window centred on each pixel, an int applies to the first two axes and a
+        tuple of 2 ints leaves the 3rd axis at 1, so every channel is thresholded on its own
+    :param k: weight of the stdev, typically between 0.2 and 0.5
+    :param r: dynamic range of the stdev, None uses half the value range (max - min) of the image,
+        use 128 to match the usual value for uint8 pages
+    :param mode: 'same' returns a mask with the shape of the image, 'valid' only the pixels whose
+        window is inside the image, 'full' every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :return: boolean numpy array, True where the pixel is above its threshold, invert it for dark
+        text on a light page
+    :raises ValueError: if r is not above 0, mode / padding is not one of the listed names, a window size is 0,
+        the window covers more than the matching image dimension with mode='valid', or a float
+        argument is not finite
+    """
+
+
+def threshold_wolf(py_img: numpy.ndarray, window_size: int | tuple[int, int] | tuple[int, int, int], /, k: float = 0.5, mode: str = 'same', padding: str = 'reflect', cval: float = 0.0) -> numpy.ndarray:
+    """
+    wolf-jolion threshold (1 - k) * m + k * lo + k * s / r * (m - lo), with the mean m and
+    population stdev s of each window, lo the image minimum and r the largest window stdev, handles
+    low contrast pages better than sauvola
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window centred on each pixel, an int applies to the first two axes and a
+        tuple of 2 ints leaves the 3rd axis at 1, so every channel is thresholded on its own
+    :param k: weight of the image minimum and the stdev
+    :param mode: 'same' returns a mask with the shape of the image, 'valid' only the pixels whose
+        window is inside the image, 'full' every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :return: boolean numpy array, True where the pixel is above its threshold, invert it for dark
+        text on a light page
+    :raises ValueError: if mode / padding is not one of the listed names, a window size is 0,
+        the window covers more than the matching image dimension with mode='valid', or a float
+        argument is not finite
+    """
+
+
+def threshold_bernsen(py_img: numpy.ndarray, window_size: int | tuple[int, int] | tuple[int, int, int], /, contrast_threshold: float = 15.0, mode: str = 'same', padding: str = 'reflect', cval: float = 0.0) -> numpy.ndarray:
+    """
+    bernsen threshold (min + max) / 2 of each window, a window whose max - min is below
+    contrast_threshold is taken as all background or all foreground, foreground when its mid value
+    is above the mid value of the whole image
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window centred on each pixel, an int applies to the first two axes and a
+        tuple of 2 ints leaves the 3rd axis at 1, so every channel is thresholded on its own
+    :param contrast_threshold: smallest max - min of a window that is split at its mid value
+    :param mode: 'same' returns a mask with the shape of the image, 'valid' only the pixels whose
+        window is inside the image, 'full' every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :return: boolean numpy array, True where the pixel is above its threshold, invert it for dark
+        text on a light page
+    :raises ValueError: if mode / padding is not one of the listed names, a window size is 0,
+        the window covers more than the matching image dimension with mode='valid', or a float
+        argument is not finite
+    """
+
+
+def local_contrast_normalise(py_img: numpy.ndarray, window_size: int | tuple[int, int] | tuple[int, int, int], /, eps: float = 1e-3, luminance: bool = False, mode: str = 'same', padding: str = 'reflect', cval: float = 0.0, output_dtype: str = 'float64') -> numpy.ndarray:
+    """
+    local contrast normalisation (x - m) / (s + eps), with the mean m and population stdev s of the
+    window centred on each pixel, the output lines up with the input
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window centred on each pixel, an int applies to the first two axes and a
+        tuple of 2 ints leaves the 3rd axis at 1, so every channel is normalised on its own
+    :param eps: added to the stdev, raise it to stop the noise of flat regions from being stretched,
+        e.g. 10 for uint8 images, a flat window gives 0 even with an eps of 0
+    :param luminance: normalise the luminance instead of every channel and return one channel, rec.
+        601 weights for 3 or 4 channels (a 4th channel is alpha and ignored), the mean of the
+        channels for any other count, the window must then have a depth of 1
+    :param mode: 'same' returns the shape of the image, 'valid' only the pixels whose window is
+        inside the image, 'full' every window that overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param output_dtype: 'float32' or 'float64'
+    :return: float numpy array, see window_output_shape for its shape
+    :raises ValueError: if eps is negative, mode / padding / output_dtype is not one of the listed
+        names, a window size is 0, the window covers more than the matching image dimension with
+        mode='valid', or a float argument is not finite
+    """
+
+
+def local_binary_pattern(py_img: numpy.ndarray, /, points: int | None = None, radius: float = 1.0, method: str = 'default', mode: str = 'same', padding: str = 'reflect', cval: float = 0.0) -> numpy.ndarray:
+    """
+    local binary pattern code of every pixel, each channel on its own
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param points: number of neighbours, evenly spaced on a circle of radius around each pixel and
+        bilinearly interpolated, counter clockwise from the pixel to the right like
+        `skimage.feature.local_binary_pattern`, None uses the original 3x3 pattern of the 8
+        neighbours of each pixel
+    :param radius: radius of the circle, only used with points
+    :param method: 'default' sets bit p when neighbour p is at least the centre, 'ror' takes the
+        smallest code of every rotation of the bits, 'uniform' gives the number of set bits for
+        patterns with at most 2 changes between 0 and 1 around the circle and points + 1 for the
+        others
+    :param mode: 'same' returns the shape of the image, 'valid' only the pixels whose neighbours are
+        all inside the image, 'full' every pixel whose window overlaps the image
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :return: uint32 numpy array, codes are below 2**points for 'default' and 'ror' and below
+        points + 2 for 'uniform'
+    :raises ValueError: if points is not between 1 and 32, radius is not above 0, method / mode /
+        padding is not one of the listed names, or a float argument is not finite
+    """
+
+
+def windowed_lbp_histogram(py_img: numpy.ndarray, window_size: int | tuple[int, int] | tuple[int, int, int], /, points: int | None = None, radius: float = 1.0, method: str = 'uniform', mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int | tuple[int, int] | tuple[int, int, int] = 1) -> numpy.ndarray:
+    """
+    histogram of the local binary pattern codes in each window, see local_binary_pattern, the codes
+    are computed for every pixel with mode='same' first
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size as given to the windowed_*_single, _double or _triple function
+    :param points: number of neighbours, evenly spaced on a circle of radius around each pixel and
+        bilinearly interpolated, counter clockwise from the pixel to the right like
+        `skimage.feature.local_binary_pattern`, None uses the original 3x3 pattern of the 8
+        neighbours of each pixel
+    :param radius: radius of the circle, only used with points
+    :param method: 'default' sets bit p when neighbour p is at least the centre, 'ror' takes the
+        smallest code of every rotation of the bits, 'uniform' gives the number of set bits for
+        patterns with at most 2 changes between 0 and 1 around the circle and points + 1 for the
+        others
+    :param mode: 'valid', 'same' or 'full' for the histogram windows, see windowed_rms_single
+    :param padding: values outside the image, for the codes and for the histogram windows with
+        'same' and 'full', one of 'reflect', 'edge', 'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows, an int applies to the first two axes
+    :return: float64 numpy array [h, w, c, bins], the fraction of the window with every code, bins
+        is 2**points for 'default' and 'ror' and points + 2 for 'uniform'
+    :raises ValueError: if points is not between 1 and 32, or above 16 for 'default' and 'ror',
+        radius is not above 0, method / mode / padding is not one of the listed names, a window
+        size or stride is 0, the window covers more than the matching image dimension with
+        mode='valid', or a float argument is not finite
+    """
+
+
+def windowed_glcm(py_img: numpy.ndarray, window_size: int | tuple[int, int] | tuple[int, int, int], /, offsets: list[tuple[int, int]] = [(0, 1)], levels: int = 8, features: list[str] | None = None, symmetric: bool = True, average: bool = True, value_range: tuple[float, float] | None = None, as_dict: bool = False, mode: str = 'valid', padding: str = 'reflect', cval: float = 0.0, stride: int | tuple[int, int] | tuple[int, int, int] = 1) -> numpy.ndarray | dict[str, numpy.ndarray]:
+    """
+    haralick texture features of the grey level co-occurrence matrix (GLCM) of each window, the
+    matrix counts how often level a has level b at an offset from it inside the window
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param window_size: window size as given to the windowed_*_single, _double or _triple function,
+        pairs never cross between channels
+    :param offsets: (rows, columns) from the first pixel of a pair to the second, e.g. [(0, 1),
+        (-1, 1), (-1, 0), (-1, -1)] for distance 1 at 0, 45, 90 and 135 degrees
+    :param levels: number of grey levels, between 2 and 256, value_range is split into levels equal
+        bins
+    :param features: any of 'contrast', 'homogeneity', 'energy', 'correlation' and 'entropy', in
+        the order of the output, None for all of them. energy is sqrt(sum p^2), entropy uses log2
+        and correlation is 1 for a window of a single level
+    :param symmetric: count every pair both ways, like `skimage.feature.graycomatrix`
+    :param average: return the mean of every feature over the offsets, otherwise one value per
+        offset and feature, offset major
+    :param value_range: (lo, hi) mapped to the grey levels, values outside go to the first or last
+        level, None uses the smallest and largest finite value of the image
+    :param as_dict: return a dict from feature name to array, with a last axis of one value per
+        offset when average is False
+    :param mode: 'valid', 'same' or 'full', see windowed_rms_single
+    :param padding: values outside the image for 'same' and 'full', one of 'reflect', 'edge',
+        'constant' or 'wrap', same as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param stride: step between windows, an int applies to the first two axes
+    :return: float64 numpy array [h, w, c, n] with n the number of features, times the number of
+        offsets when average is False, see window_output_shape for the first three axes
+    :raises ValueError: if offsets or features is empty, an offset leaves no pair inside the window,
+        levels is not between 2 and 256, value_range is not increasing, a feature / mode / padding
+        is not one of the listed names, a window size or stride is 0, the window covers more than
+        the matching image dimension with mode='valid', or a float argument is not finite
+    """
+
+
+def morphology(py_img: numpy.ndarray, operation: str, /, size: int | tuple[int, int] | tuple[int, int, int] = 3, footprint: numpy.ndarray | None = None, padding: str = 'reflect', cval: float = 0.0, output_dtype: str | None = None) -> numpy.ndarray:
+    """
+    grayscale morphology with a box or footprint structuring element centred on every pixel
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param operation: 'erosion' the minimum under the element, 'dilation' the maximum under the
+        reflected element, 'opening' the dilation of the erosion, 'closing' the erosion of the
+        dilation, 'tophat' the image minus its opening, 'blackhat' the closing minus the image,
+        'gradient' the dilation minus the erosion
+    :param size: odd size of a box element, an int is a [w,w,1] box, boxes take about 3 comparisons
+        per pixel whatever their size
+    :param footprint: boolean numpy.ndarray with odd sizes used as the element instead of the box,
+        see footprint_disk, footprint_diamond and footprint_cross
+    :param padding: values outside the image, one of 'reflect', 'edge', 'constant' or 'wrap', same
+        as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param output_dtype: None returns the input dtype, integer dtypes clip the result to their
+        range, 'float32' or 'float64' return it unclipped
+    :return: numpy array with the shape of the input
+    :raises ValueError: if operation / padding / output_dtype is not one of the listed names, a
+        size of the element is 0 or even, the footprint is all False, or cval is not finite
+    """
+
+
+def gradients(py_img: numpy.ndarray, /, operator: str = 'sobel', padding: str = 'reflect', cval: float = 0.0, as_dict: bool = False) -> numpy.ndarray | dict[str, numpy.ndarray]:
+    """
+    derivatives of every pixel from a 3x3 operator with their magnitude and orientation, each
+    channel on its own, the derivatives are not normalised like `scipy.ndimage.sobel`
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param operator: 'sobel', 'scharr' or 'prewitt', a central difference smoothed across it with
+        [1, 2, 1], [3, 10, 3] or [1, 1, 1]
+    :param padding: values outside the image, one of 'reflect', 'edge', 'constant' or 'wrap', same
+        as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param as_dict: return a dict from each name to its own array instead of one stacked array
+    :return: float64 numpy array of shape input[x,y,z] + [4], the derivative down the rows 'dy',
+        along the columns 'dx', the 'magnitude' hypot(dy, dx) and the 'orientation' atan2(dy, dx)
+        in radians on the last axis
+    :raises ValueError: if operator / padding is not one of the listed names or cval is not finite
+    """
+
+
+def laplacian(py_img: numpy.ndarray, /, padding: str = 'reflect', cval: float = 0.0) -> numpy.ndarray:
+    """
+    4 neighbour laplacian [[0, 1, 0], [1, -4, 1], [0, 1, 0]] of every pixel, each channel on its own
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param padding: values outside the image, one of 'reflect', 'edge', 'constant' or 'wrap', same
+        as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :return: float64 numpy array with the shape of the input
+    :raises ValueError: if padding is not one of the listed names or cval is not finite
+    """
+
+
+def canny(py_img: numpy.ndarray, /, sigma: float = 1.0, low_threshold: float | None = None, high_threshold: float | None = None, padding: str = 'reflect', cval: float = 0.0) -> numpy.ndarray:
+    """
+    canny edges of every channel, gaussian smoothing, sobel gradients, thinning to one pixel wide
+    maxima along the gradient and hysteresis between the two thresholds
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param sigma: standard deviation of the gaussian smoothing, 0 doesn't smooth
+    :param low_threshold: gradient magnitudes above it are edges when they connect to a strong
+        edge, None is half of high_threshold
+    :param high_threshold: gradient magnitudes above it are strong edges, None is a fifth of the
+        largest magnitude of the image
+    :param padding: values outside the image, one of 'reflect', 'edge', 'constant' or 'wrap', same
+        as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :return: boolean numpy array with the shape of the input, True on the edges
+    :raises ValueError: if sigma is negative, low_threshold is negative or above high_threshold,
+        padding is not one of the listed names, or a float argument is not finite
+    """
+
+
+def structure_tensor(py_img: numpy.ndarray, /, sigma: float = 1.0, k: float = 0.04, operator: str = 'sobel', padding: str = 'reflect', cval: float = 0.0, as_dict: bool = False) -> numpy.ndarray | dict[str, numpy.ndarray]:
+    """
+    structure tensor of every pixel, the products of the derivatives weighted by a gaussian, with
+    the maps derived from its eigenvalues, each channel on its own
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param sigma: standard deviation of the gaussian weighting, 0 doesn't weight
+    :param k: harris sensitivity, usually between 0.04 and 0.06
+    :param operator: 'sobel', 'scharr' or 'prewitt', the derivatives as in `gradients`
+    :param padding: values outside the image, one of 'reflect', 'edge', 'constant' or 'wrap', same
+        as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param as_dict: return a dict from each name to its own array instead of one stacked array
+    :return: float64 numpy array of shape input[x,y,z] + [7], 'jyy', 'jxy', 'jxx', the
+        'coherence' ((l1 - l2) / (l1 + l2))^2 of the eigenvalues, the dominant 'orientation' in
+        radians, the 'harris' response det - k * trace^2 and the 'shi_tomasi' smaller eigenvalue
+        on the last axis
+    :raises ValueError: if sigma is negative, operator / padding is not one of the listed names
+        or a float argument is not finite
+    """
+
+
+def corner_peaks(py_img: numpy.ndarray, /, method: str = 'harris', sigma: float = 1.0, k: float = 0.04, min_distance: int = 1, threshold: float | None = None, operator: str = 'sobel', padding: str = 'reflect', cval: float = 0.0) -> numpy.ndarray:
+    """
+    local maxima of a corner response of `structure_tensor`, each channel on its own
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param method: 'harris' or 'shi_tomasi'
+    :param sigma: standard deviation of the gaussian weighting, 0 doesn't weight
+    :param k: harris sensitivity, unused by 'shi_tomasi'
+    :param min_distance: a peak is the largest response within this many rows and columns
+    :param threshold: responses must be above it, None is 1% of the largest response
+    :param operator: 'sobel', 'scharr' or 'prewitt', the derivatives as in `gradients`
+    :param padding: values outside the image, one of 'reflect', 'edge', 'constant' or 'wrap', same
+        as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :return: integer numpy array of shape [n, 3], the row, column and channel of every peak,
+        strongest first
+    :raises ValueError: if method / operator / padding is not one of the listed names,
+        min_distance is 0, sigma is negative or a float argument is not finite
+    """
+
+
+def bilateral(py_img: numpy.ndarray, range_sigma: float, /, size: int | tuple[int, int] | tuple[int, int, int] = 5, spatial_sigma: float = 1.0, joint: bool = False, padding: str = 'reflect', cval: float = 0.0, output_dtype: str | None = None) -> numpy.ndarray:
+    """
+    edge preserving bilateral filter, the mean of the window around every pixel weighted by a
+    gaussian of the distance to the centre and a gaussian of the difference to the centre value
+
+    :param py_img: input image, must be a numpy.ndarray with 3 dimensions `len(array.shape)==3`, of
+        dtype uint8, uint16, uint32, uint64, int8, int16, int32, int64, float32 or float64
+    :param range_sigma: standard deviation of the value differences, in the units of the image,
+        differences well above it are not averaged
+    :param size: odd size of the window, an int is a [w,w,1] window
+    :param spatial_sigma: standard deviation of the distance to the centre in pixels
+    :param joint: weight every pixel by the euclidean distance between its colour and the colour of
+        the centre, so an edge in any channel is kept in all of them, the depth of size must be 1
+    :param padding: values outside the image, one of 'reflect', 'edge', 'constant' or 'wrap', same
+        as `numpy.pad`
+    :param cval: value used outside the image when padding is 'constant'
+    :param output_dtype: None returns the input dtype, integer dtypes clip the result to their
+        range, 'float32' or 'float64' return it unclipped
+    :return: numpy array with the shape of the input
+    :raises ValueError: if padding / output_dtype is not one of the listed names, a size is 0 or
+        even, a joint window is deeper than 1, a sigma is not positive or a float argument is not
+        finite
+    """
+
+
+def window_output_shape(shape: tuple[int, int, int], window_size: int | tuple[int, int] | tuple[int, int, int], /, stride: int | tuple[int, int] | tuple[int, int, int] = 1, dilation: int | tuple[int, int] | tuple[int, int, int] = 1, mode: str = 'valid') -> tuple[int, int, int]:
+    """
+    shape returned by the windowed functions for an input of `shape`, without running them
+
+    :param shape: shape of the input image
+    :param window_size: window size as given to the windowed_*_single, _double or _triple function
+    :param stride: step between windows, an int applies to the first two axes like window_size
+    :param dilation: spacing between the elements of a window, an int applies to the first two axes
+    :param mode: 'valid', 'same' or 'full', see windowed_rms_single
+    :return: output shape, for each axis (n_padded - ((w-1)*dilation+1)) // stride + 1
+    :raises ValueError: if any window size, stride or dilation is 0 or the window covers more than
+        the matching dimension with mode='valid'
     """
@@ -3,12 +3,19 @@ use ndarray::Array3;
 use ndarray::ArrayView2;
 use ndarray::ArrayView3;
 
+use crate::error::PyLensError;
+
 const ORDER: u8 = 64;
 
+/// number of rows [`image_to_line`] creates for an image of shape `(shape0, shape1, _)`
+fn line_length(shape0: usize, shape1: usize) -> usize {
+    shape0.max(shape1).next_power_of_two().pow(2)
+}
+
 pub fn image_to_line<'a>(image: &'a ArrayView3<'a, u8>) -> Array2<u8> {
     let im_shape = image.shape();
-    let long_side = im_shape[0].max(im_shape[1]).next_power_of_two();
-    let mut flat_img: Array2<u8> = Array2::zeros((long_side.pow(2), im_shape[2]));
+    let line_len = line_length(im_shape[0], im_shape[1]);
+    let mut flat_img: Array2<u8> = Array2::zeros((line_len, im_shape[2]));
 
     image.indexed_iter().for_each(|((x, y, z), &c)| {
         let t = fast_hilbert::xy2h(x as u32, y as u32, ORDER) as usize;
@@ -18,23 +25,43 @@ pub fn image_to_line<'a>(image: &'a ArrayView3<'a, u8>) -> Array2<u8> {
     flat_img
 }
 
+/// inverse of [`image_to_line`]
+///
+/// # Errors
+/// [`PyLensError::InvalidArgument`] if `image` has fewer rows than [`image_to_line`] would
+/// have created for an image of shape `(imshape1, imshape2, _)`
 pub fn line_to_image<'a>(
     image: &'a ArrayView2<'a, u8>,
     imshape1: usize,
     imshape2: usize,
-) -> Array3<u8> {
+) -> Result<Array3<u8>, PyLensError> {
+    let required = line_length(imshape1, imshape2);
+    if image.shape()[0] < required {
+        return Err(PyLensError::InvalidArgument {
+            name: "py_img",
+            reason: format!(
+                "line has {} rows but an image of shape ({}, {}) needs at least {}, \
+                 check shape0 and shape1 match the image passed to image_to_line",
+                image.shape()[0],
+                imshape1,
+                imshape2,
+                required
+            ),
+        });
+    }
     let mut out_img: Array3<u8> = Array3::zeros((imshape1, imshape2, image.shape()[1]));
 
     out_img.indexed_iter_mut().for_each(|((x, y, z), c)| {
         let t = fast_hilbert::xy2h(x as u32, y as u32, ORDER) as usize;
         *c = image[[t, z]];
     });
-    out_img
+    Ok(out_img)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::array_reshape;
+    use crate::error::PyLensError;
     use ndarray::Array3;
 
     static US_U8_MAX: usize = u8::MAX as usize;
@@ -51,7 +78,7 @@ mod tests {
         let (sh1, sh2, sh3) = (50usize, 50usize, 3usize);
         let arr: Array3<u8> = Array3::from_shape_fn((sh1, sh2, sh3), |(a, b, c)| odd_func(a, b, c));
         let line = array_reshape::image_to_line(&arr.view());
-        let arr2 = array_reshape::line_to_image(&line.view(), sh1, sh2);
+        let arr2 = array_reshape::line_to_image(&line.view(), sh1, sh2).unwrap();
         assert!(arr.eq(&arr2))
     }
 
@@ -60,7 +87,16 @@ mod tests {
         let (sh1, sh2, sh3) = (50usize, 80usize, 3usize);
         let arr: Array3<u8> = Array3::from_shape_fn((sh1, sh2, sh3), |(a, b, c)| odd_func(a, b, c));
         let line = array_reshape::image_to_line(&arr.view());
-        let arr2 = array_reshape::line_to_image(&line.view(), sh1, sh2);
+        let arr2 = array_reshape::line_to_image(&line.view(), sh1, sh2).unwrap();
         assert!(arr.eq(&arr2))
     }
+
+    #[test]
+    fn line_to_image_line_too_short() {
+        let (sh1, sh2, sh3) = (50usize, 80usize, 3usize);
+        let arr: Array3<u8> = Array3::from_shape_fn((sh1, sh2, sh3), |(a, b, c)| odd_func(a, b, c));
+        let line = array_reshape::image_to_line(&arr.view());
+        let out = array_reshape::line_to_image(&line.view(), 200, sh2);
+        assert!(matches!(out, Err(PyLensError::InvalidArgument { name: "py_img", .. })));
+    }
 }
@@ -0,0 +1,114 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::PyErr;
+use std::fmt::{Display, Formatter};
+
+/// errors raised by invalid input to any of the py_lens entry points
+///
+/// every variant converts into a python `ValueError`, see the [`From`] impl below,
+/// wrong dtypes and dimension counts are rejected by pyo3 / numpy as a `TypeError`
+/// before any of this code is reached
+#[derive(Debug, Clone, PartialEq)]
+pub enum PyLensError {
+    /// a window dimension was zero
+    EmptyWindow { axis: usize },
+    /// a window dimension was larger than the matching array dimension
+    WindowTooLarge {
+        axis: usize,
+        window: usize,
+        array: usize,
+    },
+    /// an image with no pixels, or no colour channels
+    EmptyImage { shape: Vec<usize> },
+    /// a float argument that was NaN or infinite
+    NonFinite { name: &'static str, value: f64 },
+    /// any other argument outside its allowed range
+    InvalidArgument { name: &'static str, reason: String },
+}
+
+impl Display for PyLensError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PyLensError::EmptyWindow { axis } => {
+                write!(
+                    f,
+                    "window size along axis {} is 0, every window dimension must be at least 1",
+                    axis
+                )
+            }
+            PyLensError::WindowTooLarge {
+                axis,
+                window,
+                array,
+            } => {
+                write!(
+                    f,
+                    "window size {} along axis {} is larger than the array size {}, \
+                     use a window of at most {} on this axis",
+                    window, axis, array, array
+                )
+            }
+            PyLensError::EmptyImage { shape } => {
+                write!(
+                    f,
+                    "image with shape {:?} is empty, every dimension must be at least 1",
+                    shape
+                )
+            }
+            PyLensError::NonFinite { name, value } => {
+                write!(f, "`{}` must be a finite number, got {}", name, value)
+            }
+            PyLensError::InvalidArgument { name, reason } => {
+                write!(f, "invalid value for `{}`: {}", name, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PyLensError {}
+
+impl From<PyLensError> for PyErr {
+    fn from(err: PyLensError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// check a float argument is usable, returns [`PyLensError::NonFinite`] for NaN or infinity
+pub fn check_finite(name: &'static str, value: f64) -> Result<f64, PyLensError> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(PyLensError::NonFinite { name, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{check_finite, PyLensError};
+
+    #[test]
+    fn test_check_finite() {
+        assert_eq!(Ok(1.5), check_finite("u", 1.5));
+        assert!(matches!(
+            check_finite("u", f64::NAN),
+            Err(PyLensError::NonFinite { name: "u", .. })
+        ));
+        assert!(matches!(
+            check_finite("ctr_x", f64::INFINITY),
+            Err(PyLensError::NonFinite { name: "ctr_x", .. })
+        ));
+    }
+
+    #[test]
+    fn test_message_is_actionable() {
+        let err = PyLensError::WindowTooLarge {
+            axis: 1,
+            window: 12,
+            array: 8,
+        };
+        assert_eq!(
+            "window size 12 along axis 1 is larger than the array size 8, \
+             use a window of at most 8 on this axis",
+            err.to_string()
+        );
+    }
+}
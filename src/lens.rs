@@ -1,5 +1,8 @@
 use ndarray::{Array2, Array3, ArrayView3};
 
+use crate::error::PyLensError;
+use crate::pool;
+
 mod point_compute;
 
 pub use point_compute::{ComputePoint, WaveLine, WavePoint, StarPattern};
@@ -81,14 +84,22 @@ fn space_def_to_pos(spd: (f64, f64), mx: usize, my: usize) -> (usize, usize) {
 /// * `image`: image array in format (width, height rgb)
 /// * `wave_method`:
 ///
-/// returns: ArrayBase<OwnedRepr<u8>, Dim<[usize; 3]>>
+/// returns: Result<ArrayBase<OwnedRepr<u8>, Dim<[usize; 3]>>, PyLensError>
+///
+/// # Errors
+/// [`PyLensError::EmptyImage`] if the image has no pixels or no colour channels,
+/// see [`pool::set_num_threads`] for errors from the thread pool
 ///
-pub fn lens_rgb<'a, T>(image: &'a ArrayView3<'a, u8>, mut wave_method: T) -> Array3<u8>
+pub fn lens_rgb<'a, T>(
+    image: &'a ArrayView3<'a, u8>,
+    mut wave_method: T,
+) -> Result<Array3<u8>, PyLensError>
 where
     T: ComputePoint,
     T: Send,
     T: Sync,
 {
+    check_not_empty(image)?;
     let (mx, my) = (image.shape()[0] - 1, image.shape()[1] - 1);
     wave_method.setup_for_new_image(mx, my);
     let im_shape: (usize, usize, usize) = (image.shape()[0], image.shape()[1], image.shape()[2]);
@@ -96,17 +107,30 @@ where
     let mut indices: Array2<(usize, usize)> =
         Array2::from_shape_fn((im_shape.0, im_shape.1), |(a, b)| (a, b));
 
-    indices.par_map_inplace(|xy| {
-        *xy = space_def_to_pos(wave_method.point_shift(xy.0 as f64, xy.1 as f64), mx, my)
-    });
+    pool::install(|| {
+        indices.par_map_inplace(|xy| {
+            *xy = space_def_to_pos(wave_method.point_shift(xy.0 as f64, xy.1 as f64), mx, my)
+        })
+    })?;
 
     let mut out_img: Array3<u8> = Array3::zeros(im_shape);
     out_img.indexed_iter_mut().for_each(|((x, y, z), px)| {
         let xy = indices[[x, y]];
         *px = image[[xy.0, xy.1, z]]
     });
-    out_img
+    Ok(out_img)
 }
+
+/// lens functions index from `shape - 1`, so every dimension needs at least one element
+fn check_not_empty(image: &ArrayView3<u8>) -> Result<(), PyLensError> {
+    if image.shape().contains(&0) {
+        return Err(PyLensError::EmptyImage {
+            shape: image.shape().to_vec(),
+        });
+    }
+    Ok(())
+}
+
 #[allow(dead_code)]
 /// old, but it does work
 ///
@@ -117,9 +141,15 @@ where
 /// * `cy`: positional centre of wave format in y
 /// * `u`: modifier for strength of the wave form
 ///
-/// returns: ArrayBase<OwnedRepr<u8>, Dim<[usize; 3]>>
+/// returns: Result<ArrayBase<OwnedRepr<u8>, Dim<[usize; 3]>>, PyLensError>
 ///
-pub fn apply_lens_rgb<'a>(image: &'a ArrayView3<'a, u8>, cx: f64, cy: f64, u: f64) -> Array3<u8> {
+pub fn apply_lens_rgb<'a>(
+    image: &'a ArrayView3<'a, u8>,
+    cx: f64,
+    cy: f64,
+    u: f64,
+) -> Result<Array3<u8>, PyLensError> {
+    check_not_empty(image)?;
     let (mx, my) = (image.shape()[0]-1, image.shape()[1]-1);
     let im_shape: (usize, usize, usize) = (image.shape()[0], image.shape()[1], image.shape()[2]);
 
@@ -129,10 +159,12 @@ pub fn apply_lens_rgb<'a>(image: &'a ArrayView3<'a, u8>, cx: f64, cy: f64, u: f6
     let mut indices: Array2<(usize,usize)> = Array2::from_shape_fn((im_shape.0, im_shape.1),
                                                                 |(a, b)| { (a, b) });
 
-    indices.par_map_inplace(|xy| {
-        let psw = point_start_wave(xy.0 as f64, xy.1 as f64, cx, cy, u);
-        *xy = space_def_to_pos((psw[0],psw[1]), mx, my);
-    });
+    pool::install(|| {
+        indices.par_map_inplace(|xy| {
+            let psw = point_start_wave(xy.0 as f64, xy.1 as f64, cx, cy, u);
+            *xy = space_def_to_pos((psw[0],psw[1]), mx, my);
+        })
+    })?;
 
     let mut out_img: Array3<u8> = Array3::zeros(im_shape);
     out_img.indexed_iter_mut().for_each(|((x, y, z), px)| {
@@ -141,7 +173,7 @@ pub fn apply_lens_rgb<'a>(image: &'a ArrayView3<'a, u8>, cx: f64, cy: f64, u: f6
     });
 
 
-    out_img
+    Ok(out_img)
 }
 
 
@@ -149,6 +181,7 @@ pub fn apply_lens_rgb<'a>(image: &'a ArrayView3<'a, u8>, cx: f64, cy: f64, u: f6
 #[cfg(test)]
 mod tests {
     use crate::lens;
+    use crate::error::PyLensError;
     use crate::lens::point_compute;
     use ndarray::Array3;
 
@@ -213,13 +246,31 @@ mod tests {
         let u = 15f64;
         let arr: Array3<u8> = Array3::from_shape_fn((50, 50, 3), |(a, b, c)| odd_func(a, b, c));
         let trait_part = point_compute::WavePoint::new((cx, cy), u);
-        let arr = lens::lens_rgb(&arr.view(), trait_part);
+        let arr = lens::lens_rgb(&arr.view(), trait_part).unwrap();
 
         let mut hasher = DefaultHasher::new();
         arr.hash(&mut hasher);
         assert_eq!(3681281280540927891, hasher.finish());
     }
 
+    #[test]
+    fn test_lens_rgb_empty_image() {
+        for shape in [(0, 50, 3), (50, 0, 3), (50, 50, 0)] {
+            let arr: Array3<u8> = Array3::zeros(shape);
+            let trait_part = point_compute::WavePoint::new((0.5, 0.5), 15f64);
+            let out = lens::lens_rgb(&arr.view(), trait_part);
+            assert!(matches!(out, Err(PyLensError::EmptyImage { .. })));
+        }
+    }
+
+    #[test]
+    fn test_lens_rgb_single_pixel() {
+        let arr: Array3<u8> = Array3::from_elem((1, 1, 3), 7u8);
+        let trait_part = point_compute::WavePoint::new((0.5, 0.5), 15f64);
+        let out = lens::lens_rgb(&arr.view(), trait_part).unwrap();
+        assert_eq!(arr, out);
+    }
+
     //#[test]
     #[allow(dead_code)]
     fn test_trait_line_lens_rgb() {
@@ -228,7 +279,7 @@ mod tests {
         let u = 15f64;
         let arr: Array3<u8> = Array3::from_shape_fn((50, 50, 3), |(a, b, c)| odd_func(a, b, c));
         let trait_part = point_compute::WaveLine::new(cx, 4.4, u);
-        let arr = lens::lens_rgb(&arr.view(), trait_part);
+        let arr = lens::lens_rgb(&arr.view(), trait_part).unwrap();
 
 
         let mut hasher = DefaultHasher::new();
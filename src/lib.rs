@@ -1,11 +1,538 @@
-use numpy::{PyArray2, PyArray3, PyReadonlyArray2, PyReadonlyArray3, ToPyArray};
+use ndarray::{Array, Array2, Array3, Array4, ArrayView, ArrayView3, Axis, Dimension, Ix3};
+use num_traits::Zero;
+use numpy::{
+    Element, PyArray2, PyArray3, PyArray4, PyReadonlyArray2, PyReadonlyArray3, ToPyArray,
+};
 use pyo3::prelude::{pyfunction, pymodule};
-use pyo3::prelude::{PyModule, PyResult, Python};
+use pyo3::prelude::{FromPyObject, PyErr, PyModule, PyObject, PyResult, Python, ToPyObject};
+use std::str::FromStr;
+use pyo3::types::PyDict;
 use pyo3::wrap_pyfunction;
 mod array_reshape;
+mod error;
 mod lens;
+mod pool;
 mod window;
 
+use error::{check_finite, PyLensError};
+use window::bilateral::{thread_bilateral, Bilateral};
+use window::contrast::{thread_contrast_normalise, Channels};
+use window::edges::{
+    magnitude, orientation, thread_canny, thread_gradients, thread_laplacian, Canny,
+};
+use window::entropy::{thread_entropy, Bins, Entropy};
+use window::footprint::{Footprint, Masked};
+use window::glcm::{quantise, thread_glcm, Glcm, Haralick};
+use window::kernel::{thread_separable, Correlate, Factors, Kernel, Weighted, WeightedStat};
+use window::morphology::{thread_morphology, Morphology, Structure};
+use window::padding::{pad_for_mode, OutputMode, PadMode};
+use window::rank::{thread_rank_filter, Rank, RankKey};
+use window::structure_tensor::{thread_structure_tensor, StructureTensor};
+use window::threshold::{thread_threshold, Threshold};
+use window::window_methods::{
+    code_histogram, FractionAbove, Lbp, NumConv, Percentile, Reducer, Stat, Stats, Stdev, Variance,
+};
+
+
+/// a window size, stride or dilation given as an int or a tuple of 2 or 3 ints, following the
+/// `_single`, `_double` and `_triple` window functions
+#[derive(FromPyObject)]
+enum Triplet {
+    Single(usize),
+    Double((usize, usize)),
+    Triple((usize, usize, usize)),
+}
+
+impl Triplet {
+    fn window_shape(self) -> window::WindowShape {
+        match self {
+            Triplet::Single(a) => window::WindowShape::Single(a),
+            Triplet::Double((a, b)) => window::WindowShape::Double(a, b),
+            Triplet::Triple((a, b, c)) => window::WindowShape::Triple(a, b, c),
+        }
+    }
+
+    /// per axis steps, a single int applies to the first two axes like `WindowShape::Single`
+    fn steps(self) -> (usize, usize, usize) {
+        match self {
+            Triplet::Single(a) => (a, a, 1),
+            Triplet::Double((a, b)) => (a, b, 1),
+            Triplet::Triple(t) => t,
+        }
+    }
+}
+
+/// a gaussian sigma given as a float or a tuple of 2 or 3 floats, like [`Triplet`] a single
+/// float applies to the first two axes and the others get a sigma of 0
+#[derive(FromPyObject)]
+enum Sigma {
+    Single(f64),
+    Double((f64, f64)),
+    Triple((f64, f64, f64)),
+}
+
+impl Sigma {
+    fn per_axis(self) -> (f64, f64, f64) {
+        match self {
+            Sigma::Single(a) => (a, a, 0.0),
+            Sigma::Double((a, b)) => (a, b, 0.0),
+            Sigma::Triple(t) => t,
+        }
+    }
+}
+
+/// kernel weights given as a float64 numpy array with 2 or 3 dimensions, a 2 dimensional kernel
+/// has a depth of 1
+#[derive(FromPyObject)]
+enum KernelInput<'py> {
+    Two(PyReadonlyArray2<'py, f64>),
+    Three(PyReadonlyArray3<'py, f64>),
+}
+
+impl KernelInput<'_> {
+    fn kernel(&self) -> Result<Kernel, PyLensError> {
+        match self {
+            KernelInput::Two(a) => Kernel::new(a.as_array().insert_axis(Axis(2)).to_owned()),
+            KernelInput::Three(a) => Kernel::new(a.as_array().to_owned()),
+        }
+    }
+}
+
+/// footprint given as a boolean numpy array with 2 or 3 dimensions, a 2 dimensional footprint
+/// has a depth of 1
+#[derive(FromPyObject)]
+enum FootprintInput<'py> {
+    Two(PyReadonlyArray2<'py, bool>),
+    Three(PyReadonlyArray3<'py, bool>),
+}
+
+impl FootprintInput<'_> {
+    /// # Errors
+    /// see [`Footprint::new`], [`PyLensError::InvalidArgument`] if the footprint doesn't have the
+    /// shape of `window`
+    fn footprint(&self, window: window::Window) -> Result<Footprint, PyLensError> {
+        let footprint = self.to_footprint()?;
+        if footprint.shape().dims() != window.dims() {
+            return Err(PyLensError::InvalidArgument {
+                name: "footprint",
+                reason: format!(
+                    "shape {:?} doesn't match the window {:?}",
+                    footprint.mask().shape(),
+                    window.dims()
+                ),
+            });
+        }
+        Ok(footprint)
+    }
+
+    /// # Errors
+    /// see [`Footprint::new`]
+    fn to_footprint(&self) -> Result<Footprint, PyLensError> {
+        match self {
+            FootprintInput::Two(a) => Footprint::new(a.as_array().insert_axis(Axis(2)).to_owned()),
+            FootprintInput::Three(a) => Footprint::new(a.as_array().to_owned()),
+        }
+    }
+}
+
+/// element type of the arrays returned by the window functions, the `output_dtype` argument
+///
+/// # Input
+/// the element type of the input, integer types round statistics to the nearest value and
+/// saturate at their range
+///
+/// # Float32 / Float64
+/// the exact statistic
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum OutputDtype {
+    Input,
+    Float32,
+    Float64,
+}
+
+impl FromStr for OutputDtype {
+    type Err = PyLensError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "float32" => Ok(OutputDtype::Float32),
+            "float64" => Ok(OutputDtype::Float64),
+            _ => Err(PyLensError::InvalidArgument {
+                name: "output_dtype",
+                reason: format!("expected None, 'float32' or 'float64', got {:?}", s),
+            }),
+        }
+    }
+}
+
+/// how the window functions turn a statistic into an output element: multiplied by `scale`,
+/// then converted to `dtype`
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Output {
+    dtype: OutputDtype,
+    scale: f64,
+}
+
+impl Output {
+    /// `self` for a statistic not measured in the units of the input, which `Input` would round
+    /// away, float64 unless another dtype is asked for
+    fn unitless(self) -> Self {
+        match self.dtype {
+            OutputDtype::Input => Output { dtype: OutputDtype::Float64, ..self },
+            _ => self,
+        }
+    }
+}
+
+/// the values of a window function converted to the element type of an [`OutputDtype`], `T`
+/// being the input element type, before they become a numpy array
+#[derive(Debug, PartialEq)]
+enum Converted<T, D: Dimension> {
+    Input(Array<T, D>),
+    Float32(Array<f32, D>),
+    Float64(Array<f64, D>),
+}
+
+impl<T: WindowElement, D: Dimension> Converted<T, D> {
+    fn new(values: ArrayView<f64, D>, dtype: OutputDtype) -> Self {
+        match dtype {
+            OutputDtype::Input => Converted::Input(values.mapv(T::from_f64)),
+            OutputDtype::Float32 => Converted::Float32(values.mapv(f32::from_f64)),
+            OutputDtype::Float64 => Converted::Float64(values.to_owned()),
+        }
+    }
+
+    fn into_object(self, py: Python) -> PyObject {
+        match self {
+            Converted::Input(values) => values.to_pyarray(py).to_object(py),
+            Converted::Float32(values) => values.to_pyarray(py).to_object(py),
+            Converted::Float64(values) => values.to_pyarray(py).to_object(py),
+        }
+    }
+}
+
+/// smallest and largest finite value of `arr`, the default `value_range` of the functions
+/// that bin values, `(0, 1)` when there are fewer than two different values
+fn finite_range<T: NumConv>(arr: ArrayView3<T>) -> (f64, f64) {
+    let (lo, hi) = arr.iter().map(|x| x.as_f64()).filter(|x| x.is_finite()).fold(
+        (f64::INFINITY, f64::NEG_INFINITY),
+        |(lo, hi), x| (lo.min(x), hi.max(x)),
+    );
+    if hi > lo {
+        (lo, hi)
+    } else {
+        (0.0, 1.0)
+    }
+}
+
+/// pad the image for `mode` and apply `reducer` to the elements of every window under
+/// `footprint`, or the whole window without one
+fn reduce_windows<T, R>(
+    arr: ArrayView3<T>,
+    window_type: window::Window,
+    footprint: Option<&Footprint>,
+    (mode, pad_mode): (OutputMode, PadMode),
+    reducer: R,
+) -> Result<Array3<f64>, PyLensError>
+where
+    T: WindowElement,
+    R: Reducer<T, f64>,
+{
+    let padded = pad_for_mode(arr, window_type, mode, pad_mode)?;
+    match footprint {
+        Some(footprint) => {
+            let reducer = Masked { footprint, reducer };
+            window::thread_apply_over_window(padded.view(), window_type, reducer)
+        }
+        None => window::thread_apply_over_window(padded.view(), window_type, reducer),
+    }
+}
+
+/// entropy from a sliding histogram, or sorted windows with a dilation or footprint, as float64
+/// unless `output` asks for another float
+fn entropy_values<T: WindowElement>(
+    arr: ArrayView3<T>,
+    window_type: window::Window,
+    footprint: Option<&Footprint>,
+    border: (OutputMode, PadMode),
+    output: Output,
+    (bins, value_range): (usize, Option<(f64, f64)>),
+) -> Result<Converted<T, Ix3>, PyLensError> {
+    let bins = Bins::new(bins, value_range.unwrap_or_else(|| finite_range(arr)))?;
+    let values = match footprint {
+        Some(_) => reduce_windows(arr, window_type, footprint, border, Entropy { bins })?,
+        None => {
+            let padded = pad_for_mode(arr, window_type, border.0, border.1)?;
+            thread_entropy(padded.view(), window_type, bins)?
+        }
+    };
+    let output = output.unitless();
+    Ok(Converted::new(values.mapv(|v| output.scale * v).view(), output.dtype))
+}
+
+/// fraction of every window above `threshold`, as float64 unless `output` asks for another float
+fn fraction_above_values<T: WindowElement>(
+    arr: ArrayView3<T>,
+    window_type: window::Window,
+    footprint: Option<&Footprint>,
+    border: (OutputMode, PadMode),
+    output: Output,
+    threshold: f64,
+) -> Result<Converted<T, Ix3>, PyLensError> {
+    let threshold = check_finite("threshold", threshold)?;
+    let reducer = FractionAbove { threshold };
+    let values = reduce_windows(arr, window_type, footprint, border, reducer)?;
+    let output = output.unitless();
+    Ok(Converted::new(values.mapv(|v| output.scale * v).view(), output.dtype))
+}
+
+/// image passed to a window function, any numpy array with 3 dimensions and one of these dtypes
+#[derive(FromPyObject)]
+enum WindowInput<'py> {
+    U8(PyReadonlyArray3<'py, u8>),
+    U16(PyReadonlyArray3<'py, u16>),
+    U32(PyReadonlyArray3<'py, u32>),
+    U64(PyReadonlyArray3<'py, u64>),
+    I8(PyReadonlyArray3<'py, i8>),
+    I16(PyReadonlyArray3<'py, i16>),
+    I32(PyReadonlyArray3<'py, i32>),
+    I64(PyReadonlyArray3<'py, i64>),
+    F32(PyReadonlyArray3<'py, f32>),
+    F64(PyReadonlyArray3<'py, f64>),
+}
+
+/// element types the window functions accept and return, see [`WindowInput`]
+trait WindowElement: NumConv + RankKey + Element + Zero + Clone + Send + Sync {}
+
+impl<T: NumConv + RankKey + Element + Zero + Clone + Send + Sync> WindowElement for T {}
+
+/// evaluate `$body` with `$arr` bound to the [`WindowInput`] `$input` as an `ArrayView3` of its own
+/// element type, `$body` is compiled once for every type
+macro_rules! with_window_input {
+    ($input:expr, |$arr:ident| $body:expr) => {
+        match $input {
+            WindowInput::U8(a) => {
+                let $arr = a.as_array();
+                $body
+            }
+            WindowInput::U16(a) => {
+                let $arr = a.as_array();
+                $body
+            }
+            WindowInput::U32(a) => {
+                let $arr = a.as_array();
+                $body
+            }
+            WindowInput::U64(a) => {
+                let $arr = a.as_array();
+                $body
+            }
+            WindowInput::I8(a) => {
+                let $arr = a.as_array();
+                $body
+            }
+            WindowInput::I16(a) => {
+                let $arr = a.as_array();
+                $body
+            }
+            WindowInput::I32(a) => {
+                let $arr = a.as_array();
+                $body
+            }
+            WindowInput::I64(a) => {
+                let $arr = a.as_array();
+                $body
+            }
+            WindowInput::F32(a) => {
+                let $arr = a.as_array();
+                $body
+            }
+            WindowInput::F64(a) => {
+                let $arr = a.as_array();
+                $body
+            }
+        }
+    };
+}
+
+/// defines the `_single`, `_double` and `_triple` python functions of one windowed statistic and
+/// adds them to the module `$m`
+///
+/// each function takes the image, the window size, the statistic's own arguments `$arg` and then
+/// the keyword arguments shared by every window function, it builds the [`window::Window`] and
+/// calls `$run(py, array, window, footprint, (mode, pad_mode), output, $arg..., $kw...)` with the
+/// image as an `ArrayView3` of its own element type, see [`with_window_input`]. `apply f` runs the window
+/// function `f` through [`window::thread_apply_over_window`], `apply_unitless f` does the same for
+/// statistics not in the units of the input, see [`Output::unitless`], the optional
+/// `[$kw: $ty = "default"]` list adds keyword arguments of the statistic before the shared ones
+macro_rules! windowed_functions {
+    (
+        $m:ident,
+        apply $func:expr,
+        $($rest:tt)*
+    ) => {
+        windowed_functions!(
+            $m,
+            |py, arr, window_type, footprint, border, output| {
+                windowed_apply(py, arr, window_type, footprint, border, output, $func)
+            },
+            $($rest)*
+        )
+    };
+    (
+        $m:ident,
+        apply_unitless $func:expr,
+        $($rest:tt)*
+    ) => {
+        windowed_functions!(
+            $m,
+            |py, arr, window_type, footprint, border, output: Output| {
+                let output = output.unitless();
+                windowed_apply(py, arr, window_type, footprint, border, output, $func)
+            },
+            $($rest)*
+        )
+    };
+    (
+        $m:ident,
+        $run:expr,
+        ($($arg:ident: $ty:ty),*),
+        $single:ident = $sig_single:literal,
+        $($rest:tt)*
+    ) => {
+        windowed_functions!($m, $run, ($($arg: $ty),*), [], $single = $sig_single, $($rest)*)
+    };
+    (
+        $m:ident,
+        $run:expr,
+        ($($arg:ident: $ty:ty),*),
+        [$($kw:ident: $kw_ty:ty = $default:tt),*],
+        $single:ident = $sig_single:literal,
+        $double:ident = $sig_double:literal,
+        $triple:ident = $sig_triple:literal $(,)?
+    ) => {
+        #[allow(clippy::too_many_arguments)]
+        #[pyfunction(
+            $($kw = $default,)*
+            mode = "\"valid\"",
+            padding = "\"reflect\"",
+            cval = "0.0",
+            stride = "1",
+            dilation = "1",
+            output_dtype = "None",
+            scale = "1.0",
+            footprint = "None"
+        )]
+        #[pyo3(text_signature = $sig_single)]
+        fn $single<'py>(
+            py: Python<'py>,
+            py_img: WindowInput<'py>,
+            window_size: usize,
+            $($arg: $ty,)*
+            $($kw: $kw_ty,)*
+            mode: &str,
+            padding: &str,
+            cval: f64,
+            stride: usize,
+            dilation: usize,
+            output_dtype: Option<&str>,
+            scale: f64,
+            footprint: Option<FootprintInput<'py>>,
+        ) -> PyResult<PyObject> {
+            let window_type = window::Window::from(window::WindowShape::Single(window_size))
+                .with_stride((stride, stride, 1))
+                .with_dilation((dilation, dilation, 1));
+            let (border, output) = (border(mode, padding, cval)?, output(output_dtype, scale)?);
+            let footprint = footprint.map(|f| f.footprint(window_type)).transpose()?;
+            let footprint = footprint.as_ref();
+            with_window_input!(py_img, |arr| {
+                ($run)(py, arr, window_type, footprint, border, output, $($arg,)* $($kw),*)
+            })
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        #[pyfunction(
+            $($kw = $default,)*
+            mode = "\"valid\"",
+            padding = "\"reflect\"",
+            cval = "0.0",
+            stride = "(1, 1)",
+            dilation = "(1, 1)",
+            output_dtype = "None",
+            scale = "1.0",
+            footprint = "None"
+        )]
+        #[pyo3(text_signature = $sig_double)]
+        fn $double<'py>(
+            py: Python<'py>,
+            py_img: WindowInput<'py>,
+            window_size: (usize, usize),
+            $($arg: $ty,)*
+            $($kw: $kw_ty,)*
+            mode: &str,
+            padding: &str,
+            cval: f64,
+            stride: (usize, usize),
+            dilation: (usize, usize),
+            output_dtype: Option<&str>,
+            scale: f64,
+            footprint: Option<FootprintInput<'py>>,
+        ) -> PyResult<PyObject> {
+            let window_type = window::Window::from(window::WindowShape::Double(window_size.0, window_size.1))
+                .with_stride((stride.0, stride.1, 1))
+                .with_dilation((dilation.0, dilation.1, 1));
+            let (border, output) = (border(mode, padding, cval)?, output(output_dtype, scale)?);
+            let footprint = footprint.map(|f| f.footprint(window_type)).transpose()?;
+            let footprint = footprint.as_ref();
+            with_window_input!(py_img, |arr| {
+                ($run)(py, arr, window_type, footprint, border, output, $($arg,)* $($kw),*)
+            })
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        #[pyfunction(
+            $($kw = $default,)*
+            mode = "\"valid\"",
+            padding = "\"reflect\"",
+            cval = "0.0",
+            stride = "(1, 1, 1)",
+            dilation = "(1, 1, 1)",
+            output_dtype = "None",
+            scale = "1.0",
+            footprint = "None"
+        )]
+        #[pyo3(text_signature = $sig_triple)]
+        fn $triple<'py>(
+            py: Python<'py>,
+            py_img: WindowInput<'py>,
+            window_size: (usize, usize, usize),
+            $($arg: $ty,)*
+            $($kw: $kw_ty,)*
+            mode: &str,
+            padding: &str,
+            cval: f64,
+            stride: (usize, usize, usize),
+            dilation: (usize, usize, usize),
+            output_dtype: Option<&str>,
+            scale: f64,
+            footprint: Option<FootprintInput<'py>>,
+        ) -> PyResult<PyObject> {
+            let window_type = window::Window::from(window::WindowShape::Triple(window_size.0, window_size.1, window_size.2))
+                .with_stride(stride)
+                .with_dilation(dilation);
+            let (border, output) = (border(mode, padding, cval)?, output(output_dtype, scale)?);
+            let footprint = footprint.map(|f| f.footprint(window_type)).transpose()?;
+            let footprint = footprint.as_ref();
+            with_window_input!(py_img, |arr| {
+                ($run)(py, arr, window_type, footprint, border, output, $($arg,)* $($kw),*)
+            })
+        }
+
+        $m.add_function(wrap_pyfunction!($single, $m)?)?;
+        $m.add_function(wrap_pyfunction!($double, $m)?)?;
+        $m.add_function(wrap_pyfunction!($triple, $m)?)?;
+    };
+}
 
 /// A Python module implemented in Rust.
 #[pymodule]
@@ -15,12 +542,45 @@ fn py_lens(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(star_pattern, m)?)?;
     m.add_function(wrap_pyfunction!(image_to_line, m)?)?;
     m.add_function(wrap_pyfunction!(line_to_image, m)?)?;
-    m.add_function(wrap_pyfunction!(windowed_rms_single, m)?)?;
-    m.add_function(wrap_pyfunction!(windowed_rms_double, m)?)?;
-    m.add_function(wrap_pyfunction!(windowed_rms_triple, m)?)?;
-    m.add_function(wrap_pyfunction!(windowed_stdev_single, m)?)?;
-    m.add_function(wrap_pyfunction!(windowed_stdev_double, m)?)?;
-    m.add_function(wrap_pyfunction!(windowed_stdev_triple, m)?)?;
+    m.add_function(wrap_pyfunction!(window_output_shape, m)?)?;
+    m.add_function(wrap_pyfunction!(generic_window, m)?)?;
+    m.add_function(wrap_pyfunction!(windowed_gaussian, m)?)?;
+    m.add_function(wrap_pyfunction!(windowed_kernel, m)?)?;
+    m.add_function(wrap_pyfunction!(correlate, m)?)?;
+    m.add_function(wrap_pyfunction!(convolve, m)?)?;
+    m.add_function(wrap_pyfunction!(footprint_disk, m)?)?;
+    m.add_function(wrap_pyfunction!(footprint_annulus, m)?)?;
+    m.add_function(wrap_pyfunction!(footprint_diamond, m)?)?;
+    m.add_function(wrap_pyfunction!(footprint_cross, m)?)?;
+    m.add_function(wrap_pyfunction!(threshold_niblack, m)?)?;
+    m.add_function(wrap_pyfunction!(threshold_sauvola, m)?)?;
+    m.add_function(wrap_pyfunction!(threshold_wolf, m)?)?;
+    m.add_function(wrap_pyfunction!(threshold_bernsen, m)?)?;
+    m.add_function(wrap_pyfunction!(local_contrast_normalise, m)?)?;
+    m.add_function(wrap_pyfunction!(local_binary_pattern, m)?)?;
+    m.add_function(wrap_pyfunction!(windowed_lbp_histogram, m)?)?;
+    m.add_function(wrap_pyfunction!(windowed_glcm, m)?)?;
+    m.add_function(wrap_pyfunction!(morphology, m)?)?;
+    m.add_function(wrap_pyfunction!(gradients, m)?)?;
+    m.add_function(wrap_pyfunction!(laplacian, m)?)?;
+    m.add_function(wrap_pyfunction!(canny, m)?)?;
+    m.add_function(wrap_pyfunction!(structure_tensor, m)?)?;
+    m.add_function(wrap_pyfunction!(corner_peaks, m)?)?;
+    m.add_function(wrap_pyfunction!(bilateral, m)?)?;
+    m.add_function(wrap_pyfunction!(set_num_threads, m)?)?;
+    m.add_function(wrap_pyfunction!(get_num_threads, m)?)?;
+
+    #[pyfunction]
+    #[pyo3(text_signature = "(n:int, /)")]
+    fn set_num_threads(n: usize) -> PyResult<()> {
+        Ok(pool::set_num_threads(n)?)
+    }
+
+    #[pyfunction]
+    #[pyo3(text_signature = "()")]
+    fn get_num_threads() -> PyResult<usize> {
+        Ok(pool::num_threads()?)
+    }
 
     #[pyfunction]
     #[pyo3(text_signature = "(py_img:numpy.ndarray, ctr_x:float, ctr_y:float, u:float, /)")]
@@ -30,10 +590,13 @@ fn py_lens(_py: Python, m: &PyModule) -> PyResult<()> {
         ctr_x: f64,
         ctr_y: f64,
         u: f64,
-    ) -> &'py PyArray3<u8> {
-        let wp = lens::WavePoint::new((ctr_x, ctr_y), u);
-        let img_out = lens::lens_rgb(&py_img.as_array(), wp);
-        img_out.to_pyarray(py)
+    ) -> PyResult<&'py PyArray3<u8>> {
+        let wp = lens::WavePoint::new(
+            (check_finite("ctr_x", ctr_x)?, check_finite("ctr_y", ctr_y)?),
+            check_finite("u", u)?,
+        );
+        let img_out = lens::lens_rgb(&py_img.as_array(), wp)?;
+        Ok(img_out.to_pyarray(py))
     }
     //#[pyfunction]
     // this is unimplemented
@@ -45,10 +608,14 @@ fn py_lens(_py: Python, m: &PyModule) -> PyResult<()> {
         ctr_y: f64,
         angle: f64,
         u: f64,
-    ) -> &'py PyArray3<u8> {
-        let wp = lens::WaveLine::new((ctr_x, ctr_y), angle, u);
-        let img_out = lens::lens_rgb(&py_img.as_array(), wp);
-        img_out.to_pyarray(py)
+    ) -> PyResult<&'py PyArray3<u8>> {
+        let wp = lens::WaveLine::new(
+            (check_finite("ctr_x", ctr_x)?, check_finite("ctr_y", ctr_y)?),
+            check_finite("angle", angle)?,
+            check_finite("u", u)?,
+        );
+        let img_out = lens::lens_rgb(&py_img.as_array(), wp)?;
+        Ok(img_out.to_pyarray(py))
     }
     #[pyfunction]
     #[pyo3(text_signature = "(py_img:numpy.ndarray, ctr_x:float, ctr_y:float, point_count:int, u:float, /)")]
@@ -59,10 +626,21 @@ fn py_lens(_py: Python, m: &PyModule) -> PyResult<()> {
         ctr_y: f64,
         point_count: usize,
         u: f64,
-    ) -> &'py PyArray3<u8> {
-        let wp = lens::StarPattern::new((ctr_x, ctr_y), point_count, u);
-        let img_out = lens::lens_rgb(&py_img.as_array(), wp);
-        img_out.to_pyarray(py)
+    ) -> PyResult<&'py PyArray3<u8>> {
+        if point_count == 0 {
+            return Err(PyLensError::InvalidArgument {
+                name: "point_count",
+                reason: "the pattern needs at least 1 point".to_string(),
+            }
+            .into());
+        }
+        let wp = lens::StarPattern::new(
+            (check_finite("ctr_x", ctr_x)?, check_finite("ctr_y", ctr_y)?),
+            point_count,
+            check_finite("u", u)?,
+        );
+        let img_out = lens::lens_rgb(&py_img.as_array(), wp)?;
+        Ok(img_out.to_pyarray(py))
     }
 
     #[pyfunction]
@@ -78,107 +656,1418 @@ fn py_lens(_py: Python, m: &PyModule) -> PyResult<()> {
         py_img: PyReadonlyArray2<u8>,
         shape0: usize,
         shape1: usize,
-    ) -> &'py PyArray3<u8> {
-        let image_out = array_reshape::line_to_image(&py_img.as_array(), shape0, shape1);
+    ) -> PyResult<&'py PyArray3<u8>> {
+        let image_out = array_reshape::line_to_image(&py_img.as_array(), shape0, shape1)?;
 
-        image_out.to_pyarray(py)
+        Ok(image_out.to_pyarray(py))
     }
 
-    #[pyfunction]
-    #[pyo3(text_signature = "(py_img:numpy.ndarray, window_size:int, /)")]
-    fn windowed_rms_single<'py>(
-        py: Python<'py>,
-        py_img: PyReadonlyArray3<u8>,
-        window_size: usize,
-    ) -> &'py PyArray3<u8> {
-        let window_type = window::WindowShape::Single(window_size);
-        windowed_rms(py, py_img, window_type)
+    windowed_functions!(
+        m,
+        windowed_rms,
+        (),
+        windowed_rms_single = "(py_img:numpy.ndarray, window_size:int, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_rms_double = "(py_img:numpy.ndarray, window_size:(int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_rms_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        windowed_stdev,
+        (ddof1: bool),
+        windowed_stdev_single = "(py_img:numpy.ndarray, window_size:int, ddof1:bool, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_stdev_double = "(py_img:numpy.ndarray, window_size:(int,int), ddof1:bool, /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_stdev_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), ddof1:bool, /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        windowed_mean,
+        (),
+        windowed_mean_single = "(py_img:numpy.ndarray, window_size:int, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_mean_double = "(py_img:numpy.ndarray, window_size:(int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_mean_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        windowed_variance,
+        (ddof1: bool),
+        windowed_variance_single = "(py_img:numpy.ndarray, window_size:int, ddof1:bool, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_variance_double = "(py_img:numpy.ndarray, window_size:(int,int), ddof1:bool, /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_variance_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), ddof1:bool, /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        apply window::window_methods::min,
+        (),
+        windowed_min_single = "(py_img:numpy.ndarray, window_size:int, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_min_double = "(py_img:numpy.ndarray, window_size:(int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_min_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        apply window::window_methods::max,
+        (),
+        windowed_max_single = "(py_img:numpy.ndarray, window_size:int, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_max_double = "(py_img:numpy.ndarray, window_size:(int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_max_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        apply window::window_methods::range,
+        (),
+        windowed_range_single = "(py_img:numpy.ndarray, window_size:int, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_range_double = "(py_img:numpy.ndarray, window_size:(int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_range_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        windowed_median,
+        (),
+        windowed_median_single = "(py_img:numpy.ndarray, window_size:int, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_median_double = "(py_img:numpy.ndarray, window_size:(int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_median_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        windowed_percentile,
+        (q: f64),
+        windowed_percentile_single = "(py_img:numpy.ndarray, window_size:int, q:float, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_percentile_double = "(py_img:numpy.ndarray, window_size:(int,int), q:float, /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_percentile_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), q:float, /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        windowed_rank,
+        (rank: usize),
+        windowed_rank_single = "(py_img:numpy.ndarray, window_size:int, rank:int, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_rank_double = "(py_img:numpy.ndarray, window_size:(int,int), rank:int, /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_rank_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), rank:int, /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        windowed_fraction_above,
+        (threshold: f64),
+        windowed_fraction_above_single = "(py_img:numpy.ndarray, window_size:int, threshold:float, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_fraction_above_double = "(py_img:numpy.ndarray, window_size:(int,int), threshold:float, /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_fraction_above_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), threshold:float, /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        apply window::window_methods::mode,
+        (),
+        windowed_mode_single = "(py_img:numpy.ndarray, window_size:int, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_mode_double = "(py_img:numpy.ndarray, window_size:(int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_mode_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        apply_unitless window::window_methods::skewness,
+        (),
+        windowed_skewness_single = "(py_img:numpy.ndarray, window_size:int, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_skewness_double = "(py_img:numpy.ndarray, window_size:(int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_skewness_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        apply_unitless window::window_methods::kurtosis,
+        (),
+        windowed_kurtosis_single = "(py_img:numpy.ndarray, window_size:int, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_kurtosis_double = "(py_img:numpy.ndarray, window_size:(int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_kurtosis_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        apply window::window_methods::mean_abs_dev,
+        (),
+        windowed_mean_abs_dev_single = "(py_img:numpy.ndarray, window_size:int, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_mean_abs_dev_double = "(py_img:numpy.ndarray, window_size:(int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_mean_abs_dev_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), /, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+    windowed_functions!(
+        m,
+        windowed_entropy,
+        (),
+        [bins: usize = "256", value_range: Option<(f64, f64)> = "None"],
+        windowed_entropy_single = "(py_img:numpy.ndarray, window_size:int, /, bins=256, value_range=None, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_entropy_double = "(py_img:numpy.ndarray, window_size:(int,int), /, bins=256, value_range=None, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_entropy_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), /, bins=256, value_range=None, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+
+    windowed_functions!(
+        m,
+        windowed_stats,
+        (stats: Vec<&str>),
+        [ddof1: bool = "false", as_dict: bool = "false"],
+        windowed_stats_single = "(py_img:numpy.ndarray, window_size:int, stats:list[str], /, ddof1=False, as_dict=False, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0, footprint=None)",
+        windowed_stats_double = "(py_img:numpy.ndarray, window_size:(int,int), stats:list[str], /, ddof1=False, as_dict=False, mode='valid', padding='reflect', cval=0.0, stride=(1,1), dilation=(1,1), output_dtype=None, scale=1.0, footprint=None)",
+        windowed_stats_triple = "(py_img:numpy.ndarray, window_size:(int,int,int), stats:list[str], /, ddof1=False, as_dict=False, mode='valid', padding='reflect', cval=0.0, stride=(1,1,1), dilation=(1,1,1), output_dtype=None, scale=1.0, footprint=None)",
+    );
+
+    #[pyfunction(stride = "Triplet::Single(1)", dilation = "Triplet::Single(1)", mode = "\"valid\"")]
+    #[pyo3(
+        text_signature = "(shape:(int,int,int), window_size:int|(int,int)|(int,int,int), /, stride=1, dilation=1, mode='valid')"
+    )]
+    fn window_output_shape(
+        shape: (usize, usize, usize),
+        window_size: Triplet,
+        stride: Triplet,
+        dilation: Triplet,
+        mode: &str,
+    ) -> PyResult<(usize, usize, usize)> {
+        let mode: OutputMode = mode.parse()?;
+        let win = window::Window::from(window_size.window_shape())
+            .with_stride(stride.steps())
+            .with_dilation(dilation.steps());
+        let (before_after, shape) = (mode.pad_widths(win), [shape.0, shape.1, shape.2]);
+        let padded: Vec<usize> = shape
+            .iter()
+            .zip(before_after)
+            .map(|(s, (b, a))| s + b + a)
+            .collect();
+        let out = win.output_shape(&padded)?;
+        Ok((out[0], out[1], out[2]))
     }
 
-    #[pyfunction]
-    #[pyo3(text_signature = "(py_img:numpy.ndarray, window_size:(int,int), /)")]
-    fn windowed_rms_double<'py>(
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        batch_size = "None",
+        mode = "\"valid\"",
+        padding = "\"reflect\"",
+        cval = "0.0",
+        stride = "Triplet::Single(1)",
+        dilation = "Triplet::Single(1)",
+        footprint = "None"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, window_size:int|(int,int)|(int,int,int), func, /, batch_size=None, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, footprint=None)"
+    )]
+    fn generic_window<'py>(
         py: Python<'py>,
-        py_img: PyReadonlyArray3<u8>,
-        window_size: (usize, usize),
-    ) -> &'py PyArray3<u8> {
-        let window_type = window::WindowShape::Double(window_size.0, window_size.1);
-        windowed_rms(py, py_img, window_type)
+        py_img: WindowInput<'py>,
+        window_size: Triplet,
+        func: PyObject,
+        batch_size: Option<usize>,
+        mode: &str,
+        padding: &str,
+        cval: f64,
+        stride: Triplet,
+        dilation: Triplet,
+        footprint: Option<FootprintInput<'py>>,
+    ) -> PyResult<PyObject> {
+        let window_type = window::Window::from(window_size.window_shape())
+            .with_stride(stride.steps())
+            .with_dilation(dilation.steps());
+        let footprint = footprint.map(|f| f.footprint(window_type)).transpose()?;
+        if batch_size == Some(0) {
+            return Err(PyLensError::InvalidArgument {
+                name: "batch_size",
+                reason: "a batch needs at least 1 window".to_string(),
+            }
+            .into());
+        }
+        let border = border(mode, padding, cval)?;
+        with_window_input!(py_img, |arr| {
+            let footprint = footprint.as_ref();
+            generic_window_apply(py, arr, window_type, footprint, border, &func, batch_size)
+        })
     }
-    #[pyfunction]
-    #[pyo3(text_signature = "(py_img:numpy.ndarray, window_size:(int,int,int), /)")]
-    fn windowed_rms_triple<'py>(
-        py: Python<'py>,
-        py_img: PyReadonlyArray3<u8>,
-        window_size: (usize, usize, usize),
-    ) -> &'py PyArray3<u8> {
-        let window_type = window::WindowShape::Triple(window_size.0, window_size.1, window_size.2);
-        windowed_rms(py, py_img, window_type)
+
+    /// pad the image for `mode` and call the python callable `func` on the windows, one at a
+    /// time or `batch_size` at a time, see [`window::thread_apply_batched`]. with a `footprint`
+    /// every window is first gathered into the `(1, n, 1)` elements under it like [`Masked`]
+    ///
+    /// the tiles are gathered on the shared thread pool with the GIL released, each call to
+    /// `func` takes the GIL back
+    fn generic_window_apply<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        (mode, pad_mode): (OutputMode, PadMode),
+        func: &PyObject,
+        batch_size: Option<usize>,
+    ) -> PyResult<PyObject> {
+        let padded = pad_for_mode(arr, window_type, mode, pad_mode)?;
+        let view = padded.view();
+        let out = py.allow_threads(|| {
+            let batch = batch_size.unwrap_or(1);
+            window::thread_apply_batched(view, window_type, batch, |windows, out: &mut [f64]| {
+                let masked = footprint.map(|footprint| {
+                    let mut buf = Vec::with_capacity(footprint.count());
+                    let mut values = Vec::with_capacity(out.len() * footprint.count());
+                    for w in windows.outer_iter() {
+                        values.extend(footprint.masked(w, &mut buf).iter().cloned());
+                    }
+                    let shape = (out.len(), 1, footprint.count(), 1);
+                    Array4::from_shape_vec(shape, values).expect("one masked window per result")
+                });
+                let windows = masked.as_ref().map_or(windows.view(), |m| m.view());
+                Python::with_gil(|py| {
+                    match batch_size {
+                        None => {
+                            let window = windows.index_axis(Axis(0), 0);
+                            out[0] = func.call1(py, (window.to_pyarray(py),))?.extract(py)?;
+                        }
+                        Some(_) => {
+                            let found: Vec<f64> =
+                                func.call1(py, (windows.to_pyarray(py),))?.extract(py)?;
+                            if found.len() != out.len() {
+                                return Err(PyLensError::InvalidArgument {
+                                    name: "func",
+                                    reason: format!(
+                                        "expected a value for each of the {} windows, got {}",
+                                        out.len(),
+                                        found.len()
+                                    ),
+                                }
+                                .into());
+                            }
+                            out.copy_from_slice(&found);
+                        }
+                    }
+                    Ok::<(), PyErr>(())
+                })
+            })
+        })?;
+        Ok(out.to_pyarray(py).to_object(py))
     }
 
-    #[pyfunction]
-    #[pyo3(text_signature = "(py_img:numpy.ndarray, window_size:int, ddof1:bool, /)")]
-    fn windowed_stdev_single<'py>(
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        truncate = "4.0",
+        ddof1 = "false",
+        mode = "\"valid\"",
+        padding = "\"reflect\"",
+        cval = "0.0",
+        stride = "Triplet::Single(1)",
+        output_dtype = "None",
+        scale = "1.0"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, sigma:float|(float,float)|(float,float,float), stat:str, /, truncate=4.0, ddof1=False, mode='valid', padding='reflect', cval=0.0, stride=1, output_dtype=None, scale=1.0)"
+    )]
+    fn windowed_gaussian<'py>(
         py: Python<'py>,
-        py_img: PyReadonlyArray3<u8>,
-        window_size: usize,
+        py_img: WindowInput<'py>,
+        sigma: Sigma,
+        stat: &str,
+        truncate: f64,
         ddof1: bool,
-    ) -> &'py PyArray3<u8> {
-        let window_type = window::WindowShape::Single(window_size);
-        windowed_stdev(py, py_img, window_type, ddof1)
+        mode: &str,
+        padding: &str,
+        cval: f64,
+        stride: Triplet,
+        output_dtype: Option<&str>,
+        scale: f64,
+    ) -> PyResult<PyObject> {
+        let kernel = Kernel::gaussian(sigma.per_axis(), truncate)?;
+        let window_type = window::Window::from(kernel.shape()).with_stride(stride.steps());
+        let (stat, ddof) = (stat.parse()?, if ddof1 { 1.0 } else { 0.0 });
+        let (border, output) = (border(mode, padding, cval)?, output(output_dtype, scale)?);
+        with_window_input!(py_img, |arr| {
+            windowed_weighted(py, arr, window_type, border, output, &kernel, stat, ddof)
+        })
     }
-    #[pyfunction]
-    #[pyo3(text_signature = "(py_img:numpy.ndarray, window_size:(int,int), ddof1:bool, /)")]
-    fn windowed_stdev_double<'py>(
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        ddof1 = "false",
+        mode = "\"valid\"",
+        padding = "\"reflect\"",
+        cval = "0.0",
+        stride = "Triplet::Single(1)",
+        dilation = "Triplet::Single(1)",
+        output_dtype = "None",
+        scale = "1.0"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, kernel:numpy.ndarray, stat:str, /, ddof1=False, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0)"
+    )]
+    fn windowed_kernel<'py>(
         py: Python<'py>,
-        py_img: PyReadonlyArray3<u8>,
-        window_size: (usize, usize),
+        py_img: WindowInput<'py>,
+        kernel: KernelInput<'py>,
+        stat: &str,
         ddof1: bool,
-    ) -> &'py PyArray3<u8> {
-        let window_type = window::WindowShape::Double(window_size.0, window_size.1);
-        windowed_stdev(py, py_img, window_type, ddof1)
+        mode: &str,
+        padding: &str,
+        cval: f64,
+        stride: Triplet,
+        dilation: Triplet,
+        output_dtype: Option<&str>,
+        scale: f64,
+    ) -> PyResult<PyObject> {
+        let kernel = kernel.kernel()?;
+        let window_type = window::Window::from(kernel.shape())
+            .with_stride(stride.steps())
+            .with_dilation(dilation.steps());
+        let (stat, ddof) = (stat.parse()?, if ddof1 { 1.0 } else { 0.0 });
+        let (border, output) = (border(mode, padding, cval)?, output(output_dtype, scale)?);
+        with_window_input!(py_img, |arr| {
+            windowed_weighted(py, arr, window_type, border, output, &kernel, stat, ddof)
+        })
     }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        mode = "\"valid\"",
+        padding = "\"reflect\"",
+        cval = "0.0",
+        stride = "Triplet::Single(1)",
+        dilation = "Triplet::Single(1)",
+        output_dtype = "None",
+        scale = "1.0"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, kernel:numpy.ndarray, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0)"
+    )]
+    fn correlate<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        kernel: KernelInput<'py>,
+        mode: &str,
+        padding: &str,
+        cval: f64,
+        stride: Triplet,
+        dilation: Triplet,
+        output_dtype: Option<&str>,
+        scale: f64,
+    ) -> PyResult<PyObject> {
+        let kernel = kernel.kernel()?;
+        let window_type = window::Window::from(kernel.shape())
+            .with_stride(stride.steps())
+            .with_dilation(dilation.steps());
+        let (border, output) = (border(mode, padding, cval)?, output(output_dtype, scale)?);
+        with_window_input!(py_img, |arr| {
+            windowed_correlate(py, arr, window_type, border, output, &kernel)
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        mode = "\"valid\"",
+        padding = "\"reflect\"",
+        cval = "0.0",
+        stride = "Triplet::Single(1)",
+        dilation = "Triplet::Single(1)",
+        output_dtype = "None",
+        scale = "1.0"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, kernel:numpy.ndarray, /, mode='valid', padding='reflect', cval=0.0, stride=1, dilation=1, output_dtype=None, scale=1.0)"
+    )]
+    fn convolve<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        kernel: KernelInput<'py>,
+        mode: &str,
+        padding: &str,
+        cval: f64,
+        stride: Triplet,
+        dilation: Triplet,
+        output_dtype: Option<&str>,
+        scale: f64,
+    ) -> PyResult<PyObject> {
+        let kernel = kernel.kernel()?.flipped();
+        let window_type = window::Window::from(kernel.shape())
+            .with_stride(stride.steps())
+            .with_dilation(dilation.steps());
+        let (border, output) = (border(mode, padding, cval)?, output(output_dtype, scale)?);
+        with_window_input!(py_img, |arr| {
+            windowed_correlate(py, arr, window_type, border, output, &kernel)
+        })
+    }
+
     #[pyfunction]
-    #[pyo3(text_signature = "(py_img:numpy.ndarray, window_size:(int,int,int), ddof1:bool, /)")]
-    fn windowed_stdev_triple<'py>(
+    #[pyo3(text_signature = "(radius:int, /)")]
+    fn footprint_disk(py: Python<'_>, radius: usize) -> &PyArray2<bool> {
+        footprint_array(py, &Footprint::disk(radius))
+    }
+
+    #[pyfunction]
+    #[pyo3(text_signature = "(inner:int, outer:int, /)")]
+    fn footprint_annulus(py: Python<'_>, inner: usize, outer: usize) -> PyResult<&PyArray2<bool>> {
+        Ok(footprint_array(py, &Footprint::annulus(inner, outer)?))
+    }
+
+    #[pyfunction]
+    #[pyo3(text_signature = "(radius:int, /)")]
+    fn footprint_diamond(py: Python<'_>, radius: usize) -> &PyArray2<bool> {
+        footprint_array(py, &Footprint::diamond(radius))
+    }
+
+    #[pyfunction]
+    #[pyo3(text_signature = "(radius:int, /)")]
+    fn footprint_cross(py: Python<'_>, radius: usize) -> &PyArray2<bool> {
+        footprint_array(py, &Footprint::cross(radius))
+    }
+
+    #[pyfunction(k = "-0.2", mode = "\"same\"", padding = "\"reflect\"", cval = "0.0")]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, window_size, /, k=-0.2, mode='same', padding='reflect', cval=0.0)"
+    )]
+    fn threshold_niblack<'py>(
         py: Python<'py>,
-        py_img: PyReadonlyArray3<u8>,
-        window_size: (usize, usize, usize),
-        ddof1: bool,
-    ) -> &'py PyArray3<u8> {
-        let window_type = window::WindowShape::Triple(window_size.0, window_size.1, window_size.2);
-        windowed_stdev(py, py_img, window_type, ddof1)
+        py_img: WindowInput<'py>,
+        window_size: Triplet,
+        k: f64,
+        mode: &str,
+        padding: &str,
+        cval: f64,
+    ) -> PyResult<&'py PyArray3<bool>> {
+        let method = Threshold::Niblack { k };
+        threshold_mask(py, py_img, window_size, border(mode, padding, cval)?, method)
     }
 
-    fn windowed_rms<'py>(
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(k = "0.2", r = "None", mode = "\"same\"", padding = "\"reflect\"", cval = "0.0")]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, window_size, /, k=0.2, r=None, mode='same', padding='reflect', cval=0.0)"
+    )]
+    fn threshold_sauvola<'py>(
         py: Python<'py>,
-        py_img: PyReadonlyArray3<u8>,
-        window_type: window::WindowShape,
-    ) -> &'py PyArray3<u8> {
-        let image_out = window::thread_apply_over_window(
-            py_img.to_owned_array(),
-            window_type,
-            window::window_methods::faster_rms_u64_adding,
-        );
-        image_out.to_pyarray(py)
+        py_img: WindowInput<'py>,
+        window_size: Triplet,
+        k: f64,
+        r: Option<f64>,
+        mode: &str,
+        padding: &str,
+        cval: f64,
+    ) -> PyResult<&'py PyArray3<bool>> {
+        let method = Threshold::Sauvola { k, r };
+        threshold_mask(py, py_img, window_size, border(mode, padding, cval)?, method)
     }
 
-    fn windowed_stdev<'py>(
+    #[pyfunction(k = "0.5", mode = "\"same\"", padding = "\"reflect\"", cval = "0.0")]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, window_size, /, k=0.5, mode='same', padding='reflect', cval=0.0)"
+    )]
+    fn threshold_wolf<'py>(
         py: Python<'py>,
-        py_img: PyReadonlyArray3<u8>,
-        window_type: window::WindowShape,
+        py_img: WindowInput<'py>,
+        window_size: Triplet,
+        k: f64,
+        mode: &str,
+        padding: &str,
+        cval: f64,
+    ) -> PyResult<&'py PyArray3<bool>> {
+        let method = Threshold::Wolf { k };
+        threshold_mask(py, py_img, window_size, border(mode, padding, cval)?, method)
+    }
+
+    #[pyfunction(
+        contrast_threshold = "15.0",
+        mode = "\"same\"",
+        padding = "\"reflect\"",
+        cval = "0.0"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, window_size, /, contrast_threshold=15.0, mode='same', padding='reflect', cval=0.0)"
+    )]
+    fn threshold_bernsen<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        window_size: Triplet,
+        contrast_threshold: f64,
+        mode: &str,
+        padding: &str,
+        cval: f64,
+    ) -> PyResult<&'py PyArray3<bool>> {
+        let method = Threshold::Bernsen { contrast: contrast_threshold };
+        threshold_mask(py, py_img, window_size, border(mode, padding, cval)?, method)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        eps = "1e-3",
+        luminance = "false",
+        mode = "\"same\"",
+        padding = "\"reflect\"",
+        cval = "0.0",
+        output_dtype = "\"float64\""
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, window_size, /, eps=1e-3, luminance=False, mode='same', padding='reflect', cval=0.0, output_dtype='float64')"
+    )]
+    fn local_contrast_normalise<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        window_size: Triplet,
+        eps: f64,
+        luminance: bool,
+        mode: &str,
+        padding: &str,
+        cval: f64,
+        output_dtype: &str,
+    ) -> PyResult<PyObject> {
+        let window_type = window_size.window_shape();
+        let (mode, pad_mode) = border(mode, padding, cval)?;
+        let dtype = output_dtype.parse()?;
+        let channels = if luminance { Channels::Luminance } else { Channels::Each };
+        let values = with_window_input!(py_img, |arr| {
+            thread_contrast_normalise(arr, window_type, mode, pad_mode, eps, channels)?
+        });
+        Ok(convert_output::<f64, _>(py, values.view(), dtype))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        points = "None",
+        radius = "1.0",
+        method = "\"default\"",
+        mode = "\"same\"",
+        padding = "\"reflect\"",
+        cval = "0.0"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, /, points=None, radius=1.0, method='default', mode='same', padding='reflect', cval=0.0)"
+    )]
+    fn local_binary_pattern<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        points: Option<usize>,
+        radius: f64,
+        method: &str,
+        mode: &str,
+        padding: &str,
+        cval: f64,
+    ) -> PyResult<&'py PyArray3<u32>> {
+        let lbp = lbp(points, radius, method)?;
+        let border = border(mode, padding, cval)?;
+        let codes = with_window_input!(py_img, |arr| lbp_codes(arr, &lbp, border)?);
+        Ok(codes.to_pyarray(py))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        points = "None",
+        radius = "1.0",
+        method = "\"uniform\"",
+        mode = "\"valid\"",
+        padding = "\"reflect\"",
+        cval = "0.0",
+        stride = "Triplet::Single(1)"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, window_size, /, points=None, radius=1.0, method='uniform', mode='valid', padding='reflect', cval=0.0, stride=1)"
+    )]
+    fn windowed_lbp_histogram<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        window_size: Triplet,
+        points: Option<usize>,
+        radius: f64,
+        method: &str,
+        mode: &str,
+        padding: &str,
+        cval: f64,
+        stride: Triplet,
+    ) -> PyResult<&'py PyArray4<f64>> {
+        let lbp = lbp(points, radius, method)?;
+        if lbp.bins() > 1 << 16 {
+            return Err(PyLensError::InvalidArgument {
+                name: "points",
+                reason: format!("{} bins are too many, use at most 16 points", lbp.bins()),
+            }
+            .into());
+        }
+        let window_type =
+            window::Window::from(window_size.window_shape()).with_stride(stride.steps());
+        let (mode, pad_mode) = border(mode, padding, cval)?;
+        let codes = with_window_input!(py_img, |arr| {
+            lbp_codes(arr, &lbp, (OutputMode::Same, pad_mode))?
+        });
+        let padded = pad_for_mode(codes.view(), window_type, mode, pad_mode)?;
+        let histograms =
+            window::thread_apply_stacked(padded.view(), window_type, lbp.bins(), code_histogram)?;
+        Ok(histograms.to_pyarray(py))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        offsets = "vec![(0, 1)]",
+        levels = "8",
+        features = "None",
+        symmetric = "true",
+        average = "true",
+        value_range = "None",
+        as_dict = "false",
+        mode = "\"valid\"",
+        padding = "\"reflect\"",
+        cval = "0.0",
+        stride = "Triplet::Single(1)"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, window_size, /, offsets=[(0, 1)], levels=8, features=None, symmetric=True, average=True, value_range=None, as_dict=False, mode='valid', padding='reflect', cval=0.0, stride=1)"
+    )]
+    fn windowed_glcm<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        window_size: Triplet,
+        offsets: Vec<(isize, isize)>,
+        levels: usize,
+        features: Option<Vec<&str>>,
+        symmetric: bool,
+        average: bool,
+        value_range: Option<(f64, f64)>,
+        as_dict: bool,
+        mode: &str,
+        padding: &str,
+        cval: f64,
+        stride: Triplet,
+    ) -> PyResult<PyObject> {
+        let parsed = match features {
+            Some(names) => names.iter().map(|s| s.parse()).collect::<Result<Vec<_>, _>>()?,
+            None => Haralick::ALL.to_vec(),
+        };
+        let names: Vec<&str> = parsed.iter().map(|f| f.name()).collect();
+        let glcm = Glcm::new(offsets, parsed, symmetric, average)?;
+        let window_type =
+            window::Window::from(window_size.window_shape()).with_stride(stride.steps());
+        let (mode, pad_mode) = border(mode, padding, cval)?;
+        let stacked = with_window_input!(py_img, |arr| {
+            let range = value_range.unwrap_or_else(|| finite_range(arr));
+            let padded = pad_for_mode(arr, window_type, mode, pad_mode)?;
+            thread_glcm(quantise(padded.view(), levels, range)?.view(), window_type, &glcm)?
+        });
+        if !as_dict {
+            return Ok(stacked.to_pyarray(py).to_object(py));
+        }
+        let dict = PyDict::new(py);
+        for (f, name) in names.iter().enumerate() {
+            let indices: Vec<usize> = (f..glcm.count()).step_by(names.len()).collect();
+            let values = stacked.select(Axis(3), &indices);
+            match average {
+                true => dict.set_item(name, values.index_axis(Axis(3), 0).to_pyarray(py))?,
+                false => dict.set_item(name, values.to_pyarray(py))?,
+            }
+        }
+        Ok(dict.to_object(py))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        size = "Triplet::Single(3)",
+        footprint = "None",
+        padding = "\"reflect\"",
+        cval = "0.0",
+        output_dtype = "None"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, operation, /, size=3, footprint=None, padding='reflect', cval=0.0, output_dtype=None)"
+    )]
+    fn morphology<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        operation: &str,
+        size: Triplet,
+        footprint: Option<FootprintInput<'py>>,
+        padding: &str,
+        cval: f64,
+        output_dtype: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let operation: Morphology = operation.parse()?;
+        let pad_mode = PadMode::parse(padding, cval)?;
+        let dtype = output(output_dtype, 1.0)?.dtype;
+        let footprint = footprint.map(|f| f.to_footprint()).transpose()?;
+        let structure = match &footprint {
+            Some(footprint) => Structure::Footprint(footprint),
+            None => Structure::Box(size.window_shape()),
+        };
+        with_window_input!(py_img, |arr| {
+            morphology_of(py, arr, structure, pad_mode, operation, dtype)
+        })
+    }
+
+    #[pyfunction(operator = "\"sobel\"", padding = "\"reflect\"", cval = "0.0", as_dict = "false")]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, /, operator='sobel', padding='reflect', cval=0.0, as_dict=False)"
+    )]
+    fn gradients<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        operator: &str,
+        padding: &str,
+        cval: f64,
+        as_dict: bool,
+    ) -> PyResult<PyObject> {
+        let operator = operator.parse()?;
+        let pad_mode = PadMode::parse(padding, cval)?;
+        let (dy, dx) = with_window_input!(py_img, |arr| thread_gradients(arr, operator, pad_mode)?);
+        let (m, angle) = (magnitude(dy.view(), dx.view()), orientation(dy.view(), dx.view()));
+        let layers = [dy.view(), dx.view(), m.view(), angle.view()];
+        let stacked = ndarray::stack(Axis(3), &layers).expect("the layers have one shape");
+        let names = ["dy", "dx", "magnitude", "orientation"];
+        stacked_or_dict(py, stacked, &names, as_dict)
+    }
+
+    #[pyfunction(padding = "\"reflect\"", cval = "0.0")]
+    #[pyo3(text_signature = "(py_img:numpy.ndarray, /, padding='reflect', cval=0.0)")]
+    fn laplacian<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        padding: &str,
+        cval: f64,
+    ) -> PyResult<&'py PyArray3<f64>> {
+        let pad_mode = PadMode::parse(padding, cval)?;
+        let values = with_window_input!(py_img, |arr| thread_laplacian(arr, pad_mode)?);
+        Ok(values.to_pyarray(py))
+    }
+
+    #[pyfunction(
+        sigma = "1.0",
+        low_threshold = "None",
+        high_threshold = "None",
+        padding = "\"reflect\"",
+        cval = "0.0"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, /, sigma=1.0, low_threshold=None, high_threshold=None, padding='reflect', cval=0.0)"
+    )]
+    fn canny<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        sigma: f64,
+        low_threshold: Option<f64>,
+        high_threshold: Option<f64>,
+        padding: &str,
+        cval: f64,
+    ) -> PyResult<&'py PyArray3<bool>> {
+        let pad_mode = PadMode::parse(padding, cval)?;
+        let canny = Canny { sigma, low: low_threshold, high: high_threshold };
+        let edges = with_window_input!(py_img, |arr| thread_canny(arr, canny, pad_mode)?);
+        Ok(edges.to_pyarray(py))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        sigma = "1.0",
+        k = "0.04",
+        operator = "\"sobel\"",
+        padding = "\"reflect\"",
+        cval = "0.0",
+        as_dict = "false"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, /, sigma=1.0, k=0.04, operator='sobel', padding='reflect', cval=0.0, as_dict=False)"
+    )]
+    fn structure_tensor<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        sigma: f64,
+        k: f64,
+        operator: &str,
+        padding: &str,
+        cval: f64,
+        as_dict: bool,
+    ) -> PyResult<PyObject> {
+        let k = check_finite("k", k)?;
+        let tensor = tensor(py_img, sigma, operator, padding, cval)?;
+        let maps = [
+            tensor.coherence(),
+            tensor.orientation(),
+            tensor.harris(k),
+            tensor.shi_tomasi(),
+        ];
+        let mut layers = vec![tensor.jyy.view(), tensor.jxy.view(), tensor.jxx.view()];
+        layers.extend(maps.iter().map(|m| m.view()));
+        let stacked = ndarray::stack(Axis(3), &layers).expect("the layers have one shape");
+        let names = ["jyy", "jxy", "jxx", "coherence", "orientation", "harris", "shi_tomasi"];
+        stacked_or_dict(py, stacked, &names, as_dict)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        method = "\"harris\"",
+        sigma = "1.0",
+        k = "0.04",
+        min_distance = "1",
+        threshold = "None",
+        operator = "\"sobel\"",
+        padding = "\"reflect\"",
+        cval = "0.0"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, /, method='harris', sigma=1.0, k=0.04, min_distance=1, threshold=None, operator='sobel', padding='reflect', cval=0.0)"
+    )]
+    fn corner_peaks<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        method: &str,
+        sigma: f64,
+        k: f64,
+        min_distance: usize,
+        threshold: Option<f64>,
+        operator: &str,
+        padding: &str,
+        cval: f64,
+    ) -> PyResult<&'py PyArray2<usize>> {
+        let k = check_finite("k", k)?;
+        let tensor = tensor(py_img, sigma, operator, padding, cval)?;
+        let response = match method {
+            "harris" => tensor.harris(k),
+            "shi_tomasi" => tensor.shi_tomasi(),
+            _ => {
+                return Err(PyLensError::InvalidArgument {
+                    name: "method",
+                    reason: format!("expected 'harris' or 'shi_tomasi', got {:?}", method),
+                }
+                .into())
+            }
+        };
+        let largest = response.fold(0.0, |a: f64, &b| a.max(b));
+        let threshold = threshold.unwrap_or(largest / 100.0);
+        let peaks =
+            window::structure_tensor::corner_peaks(response.view(), min_distance, threshold)?;
+        let rows = peaks.iter().flat_map(|&(i, j, c)| [i, j, c]).collect();
+        let coordinates = Array2::from_shape_vec((peaks.len(), 3), rows).expect("3 per peak");
+        Ok(coordinates.to_pyarray(py))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyfunction(
+        size = "Triplet::Single(5)",
+        spatial_sigma = "1.0",
+        joint = "false",
+        padding = "\"reflect\"",
+        cval = "0.0",
+        output_dtype = "None"
+    )]
+    #[pyo3(
+        text_signature = "(py_img:numpy.ndarray, range_sigma, /, size=5, spatial_sigma=1.0, joint=False, padding='reflect', cval=0.0, output_dtype=None)"
+    )]
+    fn bilateral<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        range_sigma: f64,
+        size: Triplet,
+        spatial_sigma: f64,
+        joint: bool,
+        padding: &str,
+        cval: f64,
+        output_dtype: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let filter = Bilateral::new(spatial_sigma, range_sigma, joint)?;
+        let pad_mode = PadMode::parse(padding, cval)?;
+        let dtype = output(output_dtype, 1.0)?.dtype;
+        let size = size.window_shape();
+        with_window_input!(py_img, |arr| bilateral_of(py, arr, size, filter, pad_mode, dtype))
+    }
+
+    /// the structure tensor of the python arguments, see [`thread_structure_tensor`]
+    fn tensor(
+        py_img: WindowInput,
+        sigma: f64,
+        operator: &str,
+        padding: &str,
+        cval: f64,
+    ) -> PyResult<StructureTensor> {
+        let operator = operator.parse()?;
+        let pad_mode = PadMode::parse(padding, cval)?;
+        Ok(with_window_input!(py_img, |arr| {
+            thread_structure_tensor(arr, operator, sigma, pad_mode)?
+        }))
+    }
+
+    /// `stacked` as one array, or a dict from each of `names` to its layer of the last axis
+    fn stacked_or_dict(
+        py: Python,
+        stacked: Array4<f64>,
+        names: &[&str],
+        as_dict: bool,
+    ) -> PyResult<PyObject> {
+        if !as_dict {
+            return Ok(stacked.to_pyarray(py).to_object(py));
+        }
+        let dict = PyDict::new(py);
+        for (i, name) in names.iter().enumerate() {
+            dict.set_item(name, stacked.index_axis(Axis(3), i).to_pyarray(py))?;
+        }
+        Ok(dict.to_object(py))
+    }
+
+    /// the [`Lbp`] of the python arguments, the basic 3x3 pattern without `points`
+    fn lbp(points: Option<usize>, radius: f64, method: &str) -> PyResult<Lbp> {
+        let mapping = method.parse()?;
+        Ok(match points {
+            None => Lbp::basic(mapping),
+            Some(points) => Lbp::circular(points, radius, mapping)?,
+        })
+    }
+
+    /// the local binary pattern code of every element, in the shape `mode` gives
+    fn lbp_codes<T: WindowElement>(
+        arr: ArrayView3<T>,
+        lbp: &Lbp,
+        (mode, pad_mode): (OutputMode, PadMode),
+    ) -> Result<Array3<u32>, PyLensError> {
+        let window_type = window::Window::from(lbp.shape());
+        let padded = pad_for_mode(arr, window_type, mode, pad_mode)?;
+        window::thread_apply_over_window(padded.view(), window_type, lbp.clone())
+    }
+
+    /// `operation` of the image converted to `dtype`, see [`thread_morphology`]
+    fn morphology_of<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        structure: Structure,
+        pad_mode: PadMode,
+        operation: Morphology,
+        dtype: OutputDtype,
+    ) -> PyResult<PyObject> {
+        let values = thread_morphology(arr, structure, pad_mode, operation)?;
+        Ok(convert_output::<T, _>(py, values.view(), dtype))
+    }
+
+    fn bilateral_of<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        size: window::WindowShape,
+        filter: Bilateral,
+        pad_mode: PadMode,
+        dtype: OutputDtype,
+    ) -> PyResult<PyObject> {
+        let values = thread_bilateral(arr, size, filter, pad_mode)?;
+        Ok(convert_output::<T, _>(py, values.view(), dtype))
+    }
+
+    /// the `method` mask of the image as a numpy bool array, see [`thread_threshold`]
+    fn threshold_mask<'py>(
+        py: Python<'py>,
+        py_img: WindowInput<'py>,
+        window_size: Triplet,
+        (mode, pad_mode): (OutputMode, PadMode),
+        method: Threshold,
+    ) -> PyResult<&'py PyArray3<bool>> {
+        let window_type = window_size.window_shape();
+        let mask = with_window_input!(py_img, |arr| {
+            thread_threshold(arr, window_type, mode, pad_mode, method)?
+        });
+        Ok(mask.to_pyarray(py))
+    }
+
+    /// the mask of a footprint with a depth of 1 as a 2 dimensional numpy array
+    fn footprint_array<'py>(py: Python<'py>, footprint: &Footprint) -> &'py PyArray2<bool> {
+        footprint.mask().index_axis(Axis(2), 0).to_pyarray(py)
+    }
+
+    /// parse the `mode`, `padding` and `cval` arguments shared by the window functions
+    fn border(mode: &str, padding: &str, cval: f64) -> PyResult<(OutputMode, PadMode)> {
+        Ok((mode.parse()?, PadMode::parse(padding, cval)?))
+    }
+
+    /// parse the `output_dtype` and `scale` arguments shared by the window functions
+    fn output(output_dtype: Option<&str>, scale: f64) -> PyResult<Output> {
+        Ok(Output {
+            dtype: output_dtype.map_or(Ok(OutputDtype::Input), str::parse)?,
+            scale: check_finite("scale", scale)?,
+        })
+    }
+
+    fn windowed_rms<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        (mode, pad_mode): (OutputMode, PadMode),
+        output: Output,
+    ) -> PyResult<PyObject> {
+        if footprint.is_some() {
+            // the summed area tables only hold boxes
+            let border = (mode, pad_mode);
+            return windowed_apply(py, arr, window_type, footprint, border, output, Stdev { ddof: 0.0 });
+        }
+        let padded = pad_for_mode(arr, window_type, mode, pad_mode)?;
+        let (view, scale) = (padded.view(), output.scale);
+        Ok(match output.dtype {
+            OutputDtype::Input => {
+                let out: Array3<T> = window::integral::thread_integral_rms(view, window_type, scale)?;
+                out.to_pyarray(py).to_object(py)
+            }
+            OutputDtype::Float32 => {
+                let out: Array3<f32> = window::integral::thread_integral_rms(view, window_type, scale)?;
+                out.to_pyarray(py).to_object(py)
+            }
+            OutputDtype::Float64 => {
+                let out: Array3<f64> = window::integral::thread_integral_rms(view, window_type, scale)?;
+                out.to_pyarray(py).to_object(py)
+            }
+        })
+    }
+
+    fn windowed_stdev<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        (mode, pad_mode): (OutputMode, PadMode),
+        output: Output,
         ddof1: bool,
-    ) -> &'py PyArray3<u8> {
-        let image_out = window::thread_apply_over_window(
-            py_img.to_owned_array(),
-            window_type,
-            match ddof1 {
-                false => window::window_methods::stdev_ddof_0,
-                true => window::window_methods::stdev_ddof_1,
-            },
-        );
-        image_out.to_pyarray(py)
+    ) -> PyResult<PyObject> {
+        let ddof = match ddof1 {
+            false => 0f64,
+            true => 1f64,
+        };
+        if footprint.is_some() {
+            let border = (mode, pad_mode);
+            return windowed_apply(py, arr, window_type, footprint, border, output, Stdev { ddof });
+        }
+        let padded = pad_for_mode(arr, window_type, mode, pad_mode)?;
+        let (view, scale) = (padded.view(), output.scale);
+        Ok(match output.dtype {
+            OutputDtype::Input => {
+                let out: Array3<T> =
+                    window::integral::thread_integral_stdev(view, window_type, ddof, scale)?;
+                out.to_pyarray(py).to_object(py)
+            }
+            OutputDtype::Float32 => {
+                let out: Array3<f32> =
+                    window::integral::thread_integral_stdev(view, window_type, ddof, scale)?;
+                out.to_pyarray(py).to_object(py)
+            }
+            OutputDtype::Float64 => {
+                let out: Array3<f64> =
+                    window::integral::thread_integral_stdev(view, window_type, ddof, scale)?;
+                out.to_pyarray(py).to_object(py)
+            }
+        })
+    }
+
+    fn windowed_variance<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        border: (OutputMode, PadMode),
+        output: Output,
+        ddof1: bool,
+    ) -> PyResult<PyObject> {
+        let ddof = match ddof1 {
+            false => 0f64,
+            true => 1f64,
+        };
+        windowed_apply(py, arr, window_type, footprint, border, output, Variance { ddof })
+    }
+
+    fn windowed_percentile<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        border: (OutputMode, PadMode),
+        output: Output,
+        q: f64,
+    ) -> PyResult<PyObject> {
+        let rank = Rank::Percentile(Percentile::new(q)?);
+        rank_filter(py, arr, window_type, footprint, border, output, rank)
+    }
+
+    fn windowed_median<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        border: (OutputMode, PadMode),
+        output: Output,
+    ) -> PyResult<PyObject> {
+        rank_filter(py, arr, window_type, footprint, border, output, Rank::median())
+    }
+
+    fn windowed_rank<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        border: (OutputMode, PadMode),
+        output: Output,
+        rank: usize,
+    ) -> PyResult<PyObject> {
+        rank_filter(py, arr, window_type, footprint, border, output, Rank::Nth(rank))
+    }
+
+    /// `rank` from a sliding histogram, or selected from every window with a footprint
+    fn rank_filter<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        (mode, pad_mode): (OutputMode, PadMode),
+        output: Output,
+        rank: Rank,
+    ) -> PyResult<PyObject> {
+        if let Some(footprint) = footprint {
+            let rank = rank.check(footprint.count())?;
+            let border = (mode, pad_mode);
+            return windowed_apply(py, arr, window_type, Some(footprint), border, output, rank);
+        }
+        let padded = pad_for_mode(arr, window_type, mode, pad_mode)?;
+        let values = thread_rank_filter(padded.view(), window_type, rank)?;
+        let values = values.mapv(|v| output.scale * v);
+        Ok(convert_output::<T, _>(py, values.view(), output.dtype))
+    }
+
+    /// see [`entropy_values`]
+    #[allow(clippy::too_many_arguments)]
+    fn windowed_entropy<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        border: (OutputMode, PadMode),
+        output: Output,
+        bins: usize,
+        value_range: Option<(f64, f64)>,
+    ) -> PyResult<PyObject> {
+        let histogram = (bins, value_range);
+        let values = entropy_values(arr, window_type, footprint, border, output, histogram)?;
+        Ok(values.into_object(py))
+    }
+
+    /// see [`fraction_above_values`]
+    fn windowed_fraction_above<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        border: (OutputMode, PadMode),
+        output: Output,
+        threshold: f64,
+    ) -> PyResult<PyObject> {
+        let values = fraction_above_values(arr, window_type, footprint, border, output, threshold)?;
+        Ok(values.into_object(py))
+    }
+
+    /// every statistic named in `stats` in one pass over the windows, stacked along a new last
+    /// axis in the order given, or a dict from each name to its own array with `as_dict`
+    #[allow(clippy::too_many_arguments)]
+    fn windowed_stats<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        (mode, pad_mode): (OutputMode, PadMode),
+        output: Output,
+        stats: Vec<&str>,
+        ddof1: bool,
+        as_dict: bool,
+    ) -> PyResult<PyObject> {
+        let ddof = match ddof1 {
+            false => 0f64,
+            true => 1f64,
+        };
+        let parsed = stats.iter().map(|s| s.parse()).collect::<Result<Vec<Stat>, _>>()?;
+        let reducer = Stats::new(parsed, ddof)?;
+        let padded = pad_for_mode(arr, window_type, mode, pad_mode)?;
+        let (view, k) = (padded.view(), reducer.count());
+        let stacked = window::thread_apply_stacked(view, window_type, k, |w, out| {
+            match footprint {
+                Some(footprint) => reducer.reduce_into(footprint.masked(w, &mut Vec::new()), out),
+                None => reducer.reduce_into(w, out),
+            }
+            out.iter_mut().for_each(|x| *x *= output.scale);
+        })?;
+        if !as_dict {
+            return Ok(convert_output::<T, _>(py, stacked.view(), output.dtype));
+        }
+        let dict = PyDict::new(py);
+        for (i, name) in stats.iter().enumerate() {
+            let values = stacked.index_axis(Axis(3), i);
+            dict.set_item(name, convert_output::<T, _>(py, values, output.dtype))?;
+        }
+        Ok(dict.to_object(py))
+    }
+
+    /// box mean, one axis at a time without a footprint, see [`thread_separable`]
+    fn windowed_mean<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        border: (OutputMode, PadMode),
+        output: Output,
+    ) -> PyResult<PyObject> {
+        if footprint.is_some() {
+            let mean = window::window_methods::mean;
+            return windowed_apply(py, arr, window_type, footprint, border, output, mean);
+        }
+        let (a, b, c) = window_type.dims();
+        let factors = [vec![1.0; a], vec![1.0; b], vec![1.0; c]];
+        windowed_separable(py, arr, window_type, border, output, &factors, (a * b * c) as f64)
+    }
+
+    /// weighted statistic, the weighted mean of a separable kernel runs one axis at a time
+    #[allow(clippy::too_many_arguments)]
+    fn windowed_weighted<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        border: (OutputMode, PadMode),
+        output: Output,
+        kernel: &Kernel,
+        stat: WeightedStat,
+        ddof: f64,
+    ) -> PyResult<PyObject> {
+        let reducer = Weighted::new(kernel, stat, ddof)?;
+        match (stat, kernel.factors()) {
+            (WeightedStat::Mean, Some(factors)) => {
+                let total = kernel.weights().sum();
+                windowed_separable(py, arr, window_type, border, output, factors, total)
+            }
+            _ => windowed_apply(py, arr, window_type, None, border, output, reducer),
+        }
+    }
+
+    /// correlation with `kernel`, one axis at a time for separable kernels
+    fn windowed_correlate<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        border: (OutputMode, PadMode),
+        output: Output,
+        kernel: &Kernel,
+    ) -> PyResult<PyObject> {
+        match kernel.factors() {
+            Some(factors) => windowed_separable(py, arr, window_type, border, output, factors, 1.0),
+            None => {
+                let reducer = Correlate { kernel };
+                windowed_apply(py, arr, window_type, None, border, output, reducer)
+            }
+        }
+    }
+
+    /// pad the image for `mode` and correlate it with the separable kernel `factors`, then divide
+    /// by `divisor`, scale and convert as `output` asks, see [`thread_separable`]
+    fn windowed_separable<T: WindowElement>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        (mode, pad_mode): (OutputMode, PadMode),
+        output: Output,
+        factors: &Factors,
+        divisor: f64,
+    ) -> PyResult<PyObject> {
+        let padded = pad_for_mode(arr, window_type, mode, pad_mode)?;
+        let sums = thread_separable(padded.view(), window_type, factors)?;
+        let values = sums.mapv(|v| output.scale * (v / divisor));
+        Ok(convert_output::<T, _>(py, values.view(), output.dtype))
+    }
+
+    /// `values` as a numpy array of `dtype`, `T` being the input element type
+    fn convert_output<T: WindowElement, D: Dimension>(
+        py: Python,
+        values: ArrayView<f64, D>,
+        dtype: OutputDtype,
+    ) -> PyObject {
+        Converted::<T, D>::new(values, dtype).into_object(py)
+    }
+
+    /// pad the image for `mode` and apply `reducer` to the elements of every window under
+    /// `footprint`, or the whole window without one, then scale and convert the results as
+    /// `output` asks, see [`window::thread_apply_over_window`]
+    fn windowed_apply<T, S, R>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        footprint: Option<&Footprint>,
+        border: (OutputMode, PadMode),
+        output: Output,
+        reducer: R,
+    ) -> PyResult<PyObject>
+    where
+        T: WindowElement,
+        S: NumConv,
+        R: Reducer<T, S>,
+    {
+        match footprint {
+            Some(footprint) => {
+                let reducer = Masked { footprint, reducer };
+                apply_reducer(py, arr, window_type, border, output, reducer)
+            }
+            None => apply_reducer(py, arr, window_type, border, output, reducer),
+        }
+    }
+
+    /// [`windowed_apply`] over the whole window
+    fn apply_reducer<T, S, R>(
+        py: Python,
+        arr: ArrayView3<T>,
+        window_type: window::Window,
+        (mode, pad_mode): (OutputMode, PadMode),
+        output: Output,
+        reducer: R,
+    ) -> PyResult<PyObject>
+    where
+        T: WindowElement,
+        S: NumConv,
+        R: Reducer<T, S>,
+    {
+        let padded = pad_for_mode(arr, window_type, mode, pad_mode)?;
+        let view = padded.view();
+        let stat = |w: ArrayView3<T>| output.scale * reducer.reduce(w).as_f64();
+        Ok(match output.dtype {
+            OutputDtype::Input => {
+                let out = window::thread_apply_over_window(view, window_type, |w: ArrayView3<T>| {
+                    T::from_f64(stat(w))
+                })?;
+                out.to_pyarray(py).to_object(py)
+            }
+            OutputDtype::Float32 => {
+                let out = window::thread_apply_over_window(view, window_type, |w: ArrayView3<T>| {
+                    f32::from_f64(stat(w))
+                })?;
+                out.to_pyarray(py).to_object(py)
+            }
+            OutputDtype::Float64 => {
+                let out = window::thread_apply_over_window(view, window_type, stat)?;
+                out.to_pyarray(py).to_object(py)
+            }
+        })
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::window::footprint::Footprint;
+    use crate::window::padding::{OutputMode, PadMode};
+    use crate::window::{Window, WindowShape};
+    use crate::{entropy_values, fraction_above_values, Converted, Output, OutputDtype};
+    use ndarray::Array3;
+
+    #[test]
+    fn entropy_of_integers_is_float64() {
+        let img = Array3::from_shape_fn((8, 8, 1), |(a, b, _)| ((a * 3 + b * 5) % 7) as u8);
+        let win = Window::from(WindowShape::Single(3));
+        let border = (OutputMode::Valid, PadMode::Reflect);
+        // `output_dtype=None`
+        let input = Output { dtype: OutputDtype::Input, scale: 1.0 };
+        let disk = Footprint::disk(1);
+        for footprint in [None, Some(&disk)] {
+            match entropy_values(img.view(), win, footprint, border, input, (256, None)).unwrap() {
+                Converted::Float64(values) => assert!(values.iter().any(|e| e.fract() != 0.0)),
+                other => panic!("expected float64 entropy, got {:?}", other),
+            }
+        }
+        let float32 = Output { dtype: OutputDtype::Float32, scale: 1.0 };
+        let found = entropy_values(img.view(), win, None, border, float32, (256, None)).unwrap();
+        assert!(matches!(found, Converted::Float32(_)));
+    }
+
+    #[test]
+    fn fraction_of_integers_is_float64() {
+        let img = Array3::from_shape_fn((6, 6, 1), |(a, b, _)| (a * 6 + b) as u8);
+        let win = Window::from(WindowShape::Single(3));
+        let border = (OutputMode::Valid, PadMode::Reflect);
+        // `output_dtype=None`
+        let input = Output { dtype: OutputDtype::Input, scale: 1.0 };
+        match fraction_above_values(img.view(), win, None, border, input, 10.0).unwrap() {
+            Converted::Float64(values) => {
+                assert_eq!(values.shape(), [4, 4, 1]);
+                assert_eq!(values[[0, 0, 0]], 3.0 / 9.0);
+                assert_eq!(values[[0, 3, 0]], 4.0 / 9.0);
+                assert_eq!(values[[1, 0, 0]], 6.0 / 9.0);
+            }
+            other => panic!("expected float64 fractions, got {:?}", other),
+        }
+    }
+}
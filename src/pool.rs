@@ -0,0 +1,161 @@
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+use crate::error::PyLensError;
+
+/// environment variable read the first time the pool is used, overridden by [`set_num_threads`]
+pub const NUM_THREADS_ENV: &str = "PY_LENS_NUM_THREADS";
+
+/// shared rayon pool used by the window functions and the lens functions
+///
+/// the process id is kept alongside the pool, a forked child process (python multiprocessing on
+/// linux) inherits the pool but not its worker threads, so the pool is rebuilt when the id changes.
+/// the thread count requested through [`set_num_threads`] is kept with it, 0 for the default, so
+/// the rebuilt pool always has the count the inherited one was built for
+struct SharedPool {
+    pid: u32,
+    requested: usize,
+    pool: Arc<ThreadPool>,
+}
+
+static POOL: RwLock<Option<SharedPool>> = RwLock::new(None);
+
+/// thread count to use when none has been requested,
+/// [`NUM_THREADS_ENV`] if it is set, otherwise the available parallelism
+///
+/// # Errors
+/// [`PyLensError::InvalidArgument`] if [`NUM_THREADS_ENV`] is set but is not a positive integer
+fn default_num_threads() -> Result<usize, PyLensError> {
+    match std::env::var(NUM_THREADS_ENV) {
+        Ok(value) => parse_num_threads(&value),
+        Err(_) => Ok(thread::available_parallelism().map_or(1, |n| n.get())),
+    }
+}
+
+fn parse_num_threads(value: &str) -> Result<usize, PyLensError> {
+    match value.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(PyLensError::InvalidArgument {
+            name: NUM_THREADS_ENV,
+            reason: format!(
+                "expected a positive integer, got {:?}, unset it or use set_num_threads()",
+                value
+            ),
+        }),
+    }
+}
+
+/// a pool of `requested` threads, or the default count for 0
+fn build_pool(requested: usize) -> Result<SharedPool, PyLensError> {
+    let threads = match requested {
+        0 => default_num_threads()?,
+        n => n,
+    };
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|i| format!("py_lens-{}", i))
+        .build()
+        .map_err(|e| PyLensError::InvalidArgument {
+            name: "num_threads",
+            reason: format!("could not start {} worker threads: {}", threads, e),
+        })?;
+    Ok(SharedPool {
+        pid: std::process::id(),
+        requested,
+        pool: Arc::new(pool),
+    })
+}
+
+/// get the shared pool, building it on first use or after a fork
+fn pool() -> Result<Arc<ThreadPool>, PyLensError> {
+    let pid = std::process::id();
+    if let Some(shared) = POOL.read().unwrap().as_ref() {
+        if shared.pid == pid {
+            return Ok(shared.pool.clone());
+        }
+    }
+    let mut guard = POOL.write().unwrap();
+    // another thread may have built the pool while this one waited on the lock
+    if let Some(shared) = guard.as_ref() {
+        if shared.pid == pid {
+            return Ok(shared.pool.clone());
+        }
+    }
+    let requested = guard.as_ref().map_or(0, |shared| shared.requested);
+    let shared = build_pool(requested)?;
+    let pool = shared.pool.clone();
+    *guard = Some(shared);
+    Ok(pool)
+}
+
+/// set the number of worker threads used by every py_lens function,
+/// `0` goes back to the default, see [`NUM_THREADS_ENV`]
+///
+/// the current pool is replaced, calls already running keep their old pool until they finish
+///
+/// # Errors
+/// [`PyLensError::InvalidArgument`] if `0` is given and [`NUM_THREADS_ENV`] is invalid,
+/// or the threads could not be started
+pub fn set_num_threads(threads: usize) -> Result<(), PyLensError> {
+    // under one lock, concurrent calls can't keep the count of one with the pool of another
+    let mut guard = POOL.write().unwrap();
+    *guard = Some(build_pool(threads)?);
+    Ok(())
+}
+
+/// number of worker threads in the shared pool
+///
+/// # Errors
+/// see [`set_num_threads`]
+pub fn num_threads() -> Result<usize, PyLensError> {
+    Ok(pool()?.current_num_threads())
+}
+
+/// run `op` inside the shared pool, any rayon parallel iterator used by `op` runs on its threads
+///
+/// # Errors
+/// see [`set_num_threads`]
+pub fn install<OP, R>(op: OP) -> Result<R, PyLensError>
+where
+    OP: FnOnce() -> R + Send,
+    R: Send,
+{
+    Ok(pool()?.install(op))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::pool::{num_threads, parse_num_threads, set_num_threads, POOL};
+    use std::thread;
+
+    #[test]
+    fn test_parse_num_threads() {
+        assert_eq!(Ok(1), parse_num_threads("1"));
+        assert_eq!(Ok(8), parse_num_threads(" 8\n"));
+        for bad in ["0", "-2", "four", ""] {
+            assert!(matches!(
+                parse_num_threads(bad),
+                Err(PyLensError::InvalidArgument { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_set_num_threads() {
+        set_num_threads(3).unwrap();
+        assert_eq!(Ok(3), num_threads());
+        set_num_threads(1).unwrap();
+        assert_eq!(Ok(1), num_threads());
+        set_num_threads(0).unwrap();
+        assert!(num_threads().unwrap() >= 1);
+        let handles: Vec<_> =
+            (1..=4).map(|n| thread::spawn(move || set_num_threads(n).unwrap())).collect();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+        // whichever call came last, the requested count is the one its pool was built with
+        let guard = POOL.read().unwrap();
+        let shared = guard.as_ref().unwrap();
+        assert_eq!(shared.requested, shared.pool.current_num_threads());
+    }
+}
@@ -1,21 +1,46 @@
-use ndarray::Array3;
 use ndarray::s;
+use ndarray::Array;
+use ndarray::Array3;
+use ndarray::Array4;
+use ndarray::ArrayBase;
+use ndarray::ArrayView3;
+use ndarray::ArrayView4;
+use ndarray::ArrayViewMut;
+use ndarray::ArrayViewMut3;
+use ndarray::Data;
 
 use ndarray::AssignElem;
 use ndarray::Axis;
 use ndarray::Dim;
-use ndarray::Ix;
+use ndarray::Dimension;
+use ndarray::Ix3;
 
 use ndarray::Shape;
 use num_traits::identities::Zero;
+use rayon::prelude::*;
 use std::fmt::{Display, Formatter};
-use std::sync::mpsc;
-use std::thread;
-
+use std::sync::Mutex;
+
+use crate::error::PyLensError;
+use crate::pool;
+use window_methods::Reducer;
+
+pub mod bilateral;
+pub mod contrast;
+pub mod edges;
+pub mod entropy;
+pub mod footprint;
+pub mod glcm;
+pub mod histogram;
+pub mod integral;
+pub mod kernel;
+pub mod morphology;
+pub mod padding;
+pub mod rank;
+pub mod structure_tensor;
+pub mod threshold;
 pub mod window_methods;
 
-use crate::window::window_methods::NumConv;
-use window_methods::WinFunc;
 
 ///
 /// # Single(a)
@@ -30,9 +55,9 @@ use window_methods::WinFunc;
 ///
 /// access to automatic sizing within using [`WindowShape::array_size`]
 ///
-/// access to positional splits for threading using [`WindowShape::create_v_splits`]
+/// access to row tiles for threading using [`WindowShape::tile_rows`]
 ///
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum WindowShape {
     Single(usize),
     Double(usize, usize),
@@ -46,96 +71,60 @@ impl WindowShape {
     ///
     /// * `ar`: input array, used to get the input shape
     ///
-    /// returns: (Shape<Dim<[usize; 3]>>, Dim<[usize; 3]>)
+    /// returns: Result<(Shape<Ix3>, Ix3), PyLensError>
     ///
-    /// # PANIC
-    /// if any window size is greater than it's corresponding array size
+    /// # Errors
+    /// see [`WindowShape::validate`]
     ///
     /// # Usage
     ///
     /// see: [`apply_over_window`]
     ///
-    pub fn array_size<U>(self, ar: &Array3<U>) -> (Shape<Dim<[Ix; 3]>>, Dim<[Ix; 3]>) {
-        let w = match self {
+    #[allow(dead_code)]
+    pub fn array_size<S: Data>(
+        self,
+        ar: &ArrayBase<S, Ix3>,
+    ) -> Result<(Shape<Ix3>, Ix3), PyLensError> {
+        Window::from(self).array_size(ar)
+    }
+
+    /// window dimensions as `(a, b, c)`, `Single` and `Double` have a depth of 1
+    pub fn dims(self) -> (usize, usize, usize) {
+        match self {
             WindowShape::Single(a) => (a, a, 1),
             WindowShape::Double(a, b) => (a, b, 1),
             WindowShape::Triple(a, b, c) => (a, b, c),
-        };
-        assert!(
-            ar.shape()[0] >= w.0,
-            "First Dimension of Array must be larger than of Window"
-        );
-        assert!(
-            ar.shape()[1] >= w.1,
-            "Second Dimension of Array must be larger than of Window"
-        );
-        assert!(
-            ar.shape()[2] >= w.2,
-            "Third Dimension of Array must be larger than of Window"
-        );
-        let sh = ar.raw_dim();
-        let dim: Dim<[Ix; 3]> = Dim([
-            // note brackets matter, this is a - (b - 1)  NOT (a - b) - 1 this would be bad
-            sh[0].saturating_sub(w.0.saturating_sub(1)),
-            sh[1].saturating_sub(w.1.saturating_sub(1)),
-            sh[2].saturating_sub(w.2.saturating_sub(1)),
-        ]);
+        }
+    }
 
-        (Shape::from(dim), Dim([w.0, w.1, w.2]))
+    /// check the window fits inside an array of the given shape
+    ///
+    /// # Errors
+    /// see [`Window::validate`]
+    #[allow(dead_code)]
+    pub fn validate(self, shape: &[usize]) -> Result<(), PyLensError> {
+        Window::from(self).validate(shape)
     }
 
-    /// do not mix WindowShape instances
+    /// number of output rows in each tile when the output is split across `threads` workers
     ///
-    /// # Arguments
+    /// the output is cut into [`TILES_PER_THREAD`] tiles per thread so idle workers can steal
+    /// tiles from busy ones, every tile has at least one row and the output does not depend on
+    /// the tile size
     ///
-    /// * `arr`: input array that will be used for window functions
+    /// # Arguments
     ///
-    /// returns: Vec<(usize, usize), Global>
+    /// * `out_rows`: number of rows in the output array, see [`WindowShape::array_size`]
+    /// * `threads`: number of worker threads, values of 0 are treated as 1
     ///
-    /// # Errors:
-    /// will panic if:
-    /// ``` rust
-    /// CORES > arr.shape()[0] - WindowShape[0]]
-    /// ```
+    /// returns: usize
     ///
     /// # Examples
     ///
     /// See code for: [`thread_apply_over_window`]
-    ///
-    /// ``` rust
-    /// let v_splits_for_array = win_size.create_v_splits(&input_array);
-    /// ```
-    pub fn create_v_splits<U>(self, arr: &Array3<U>) -> Vec<(usize, usize)> {
-        let shape_0 = arr.shape()[0];
-        let win_0 = match self {
-            WindowShape::Single(a) => a,
-            WindowShape::Double(a, _) => a,
-            WindowShape::Triple(a, _, _) => a,
-        };
-        assert!(
-            CORES < shape_0 - win_0,
-            "Not enough splits for multithreading"
-        );
-        // TODO: Setup method of split even when CORES >  shape_0 - win_0.
-        let v_split_size = (shape_0 - win_0.saturating_sub(1)) as f32 / CORES as f32;
-        let split_shape = |c_: usize| -> (usize, usize) {
-            let c = c_ as f32;
-            let a = c * v_split_size;
-            let b = (c + 1f32) * v_split_size;
-            // last split needs to grab any leftover stuff, this just makes sure rounding errors
-            // don't break things again, could be refactored out at some point
-
-            (
-                a.round() as usize,
-                if c_ == CORES {
-                    shape_0
-                } else {
-                    b.round() as usize + win_0.saturating_sub(1)
-                },
-            )
-        };
-        let v_splits_for_array: Vec<_> = (0..CORES).map(split_shape).collect();
-        v_splits_for_array
+    pub fn tile_rows(self, out_rows: usize, threads: usize) -> usize {
+        let tiles = threads.max(1) * TILES_PER_THREAD;
+        out_rows.div_ceil(tiles).max(1)
     }
     #[allow(dead_code)]
     fn window_size(self) -> usize {
@@ -163,8 +152,164 @@ impl Display for WindowShape {
     }
 }
 
-#[allow(dead_code)]
-const CORES: usize = 12;
+/// a [`WindowShape`] with a stride and a dilation, every window function takes one of these,
+/// a plain [`WindowShape`] converts into a window with a stride and dilation of 1
+///
+/// # stride
+/// distance between the starts of neighbouring windows, a stride of 2 keeps every second
+/// window so the output is downsampled by 2 along that axis
+///
+/// # dilation
+/// distance between the elements read inside one window (à trous), a window of 3 with a
+/// dilation of 2 reads elements `0, 2, 4` so it covers 5 elements of the input while still
+/// only reading 3
+///
+/// the number of input elements one window covers along an axis is its extent,
+/// `(size - 1) * dilation + 1`, and the output has `(input - extent) / stride + 1` elements
+/// along that axis, see [`Window::output_shape`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Window {
+    pub shape: WindowShape,
+    pub stride: (usize, usize, usize),
+    pub dilation: (usize, usize, usize),
+}
+
+impl From<WindowShape> for Window {
+    fn from(shape: WindowShape) -> Self {
+        Window {
+            shape,
+            stride: (1, 1, 1),
+            dilation: (1, 1, 1),
+        }
+    }
+}
+
+impl Window {
+    pub fn with_stride(self, stride: (usize, usize, usize)) -> Window {
+        Window { stride, ..self }
+    }
+
+    pub fn with_dilation(self, dilation: (usize, usize, usize)) -> Window {
+        Window { dilation, ..self }
+    }
+
+    /// window dimensions as `(a, b, c)`, see [`WindowShape::dims`]
+    pub fn dims(self) -> (usize, usize, usize) {
+        self.shape.dims()
+    }
+
+    /// number of input elements one window covers along each axis
+    pub fn extent(self) -> (usize, usize, usize) {
+        let w = self.dims();
+        let d = self.dilation;
+        (
+            w.0.saturating_sub(1) * d.0 + 1,
+            w.1.saturating_sub(1) * d.1 + 1,
+            w.2.saturating_sub(1) * d.2 + 1,
+        )
+    }
+
+    /// true for a stride and dilation of 1 on every axis, a plain sliding window
+    pub fn is_dense(self) -> bool {
+        self.stride == (1, 1, 1) && self.dilation == (1, 1, 1)
+    }
+
+    /// check the window fits inside an array of the given shape
+    ///
+    /// # Errors
+    /// * [`PyLensError::InvalidArgument`] if `shape` is not 3 dimensional, or a stride or
+    ///   dilation is 0
+    /// * [`PyLensError::EmptyWindow`] if any window size is 0
+    /// * [`PyLensError::WindowTooLarge`] if any window extent is greater than it's corresponding
+    ///   array size
+    pub fn validate(self, shape: &[usize]) -> Result<(), PyLensError> {
+        if shape.len() != 3 {
+            return Err(PyLensError::InvalidArgument {
+                name: "py_img",
+                reason: format!("expected a 3 dimensional array, got shape {:?}", shape),
+            });
+        }
+        for (name, step) in [("stride", self.stride), ("dilation", self.dilation)] {
+            if [step.0, step.1, step.2].contains(&0) {
+                return Err(PyLensError::InvalidArgument {
+                    name,
+                    reason: format!("{:?} has a 0, every {} must be at least 1", step, name),
+                });
+            }
+        }
+        let w = self.dims();
+        let e = self.extent();
+        for axis in 0..3 {
+            let (window, extent) = ([w.0, w.1, w.2][axis], [e.0, e.1, e.2][axis]);
+            if window == 0 {
+                return Err(PyLensError::EmptyWindow { axis });
+            }
+            if extent > shape[axis] {
+                return Err(PyLensError::WindowTooLarge {
+                    axis,
+                    window: extent,
+                    array: shape[axis],
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// shape of the output for an input of shape `shape`
+    ///
+    /// # Errors
+    /// see [`Window::validate`]
+    pub fn output_shape(self, shape: &[usize]) -> Result<[usize; 3], PyLensError> {
+        self.validate(shape)?;
+        let e = self.extent();
+        let s = self.stride;
+        Ok([
+            (shape[0] - e.0) / s.0 + 1,
+            (shape[1] - e.1) / s.1 + 1,
+            (shape[2] - e.2) / s.2 + 1,
+        ])
+    }
+
+    /// output shape for `ar` and the window dimensions, see [`Window::output_shape`]
+    ///
+    /// # Errors
+    /// see [`Window::validate`]
+    pub fn array_size<S: Data>(
+        self,
+        ar: &ArrayBase<S, Ix3>,
+    ) -> Result<(Shape<Ix3>, Ix3), PyLensError> {
+        let out = self.output_shape(ar.shape())?;
+        let w = self.dims();
+        Ok((Shape::from(Dim(out)), Dim([w.0, w.1, w.2])))
+    }
+
+    /// view of the window producing output element `pos`
+    fn window_at<'a, T>(self, arr: &'a ArrayView3<T>, pos: (usize, usize, usize)) -> ArrayView3<'a, T> {
+        let (e, st, d) = (self.extent(), self.stride, self.dilation);
+        let start = (pos.0 * st.0, pos.1 * st.1, pos.2 * st.2);
+        arr.slice(s![
+            start.0..start.0 + e.0;d.0,
+            start.1..start.1 + e.1;d.1,
+            start.2..start.2 + e.2;d.2
+        ])
+    }
+}
+
+impl Display for Window {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.shape)?;
+        if self.stride != (1, 1, 1) {
+            write!(f, " stride {:?}", self.stride)?;
+        }
+        if self.dilation != (1, 1, 1) {
+            write!(f, " dilation {:?}", self.dilation)?;
+        }
+        Ok(())
+    }
+}
+
+/// see [`WindowShape::tile_rows`]
+const TILES_PER_THREAD: usize = 4;
 
 /// apply function where `fn(Array3<u8>)->u8` for moving rms calculations or similar
 /// return array will be smaller by the size of `s`
@@ -173,9 +318,9 @@ const CORES: usize = 12;
 ///
 /// * `arr`: 3 dimensional array, image format (width, height, colour_value)
 /// * `s`: size of moving window `[s,s,1]` (ignores z-depth of array for image editing purposes)
-/// * `func`: `fn(Array3<u8>)->u8`
+/// * `func`: a [`Reducer`], e.g. a [`WinFunc`](window_methods::WinFunc), a closure or a reducer holding parameters
 ///
-/// returns: ArrayBase<OwnedRepr<u8>, Dim<[usize; 3]>>
+/// returns: Result<ArrayBase<OwnedRepr<u8>, Dim<[usize; 3]>>, PyLensError>
 ///
 /// # Examples
 ///
@@ -183,31 +328,56 @@ const CORES: usize = 12;
 /// arr = Array3::from_image(image)
 /// arr.shape()
 /// >>> [325,325,3]
-/// ar2 = apply_over_window(arr, WindowShape::Single(3), window::rms)
+/// ar2 = apply_over_window(arr, WindowShape::Single(3), window::rms)?
 /// ar2.shape()
 /// >>> [323,323,3]
 ///
 /// ```
-fn apply_over_window<T>(arr: Array3<T>, win_size: WindowShape, func: WinFunc<T>) -> Array3<T>
+#[allow(dead_code)] // single threaded reference for thread_apply_over_window
+fn apply_over_window<T, O, R>(
+    arr: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    func: R,
+) -> Result<Array3<O>, PyLensError>
 where
-    T: Zero,
-    T: NumConv,
-    T: Clone,
+    O: Zero + Clone,
+    R: Reducer<T, O>,
 {
-    let (sh2, d) = win_size.array_size(&arr);
-    // create windowed parts of the array
-    let win = arr.windows(d);
+    let win = win_size.into();
+    let (sh2, _) = win.array_size(&arr)?;
     // create an uninitiated base array for the output, shape descried by windowed_array_size
-    let mut un_arr = Array3::<T>::zeros(sh2);
+    let mut un_arr = Array3::<O>::zeros(sh2);
+    apply_over_window_into(arr, win, &func, un_arr.view_mut());
+    Ok(un_arr)
+}
 
-    // iter through the output array and the windowed array
-    for (a, w) in un_arr.iter_mut().zip(win.into_iter()) {
-        a.assign_elem(func(w)); // assignments for some reason, I think = was being unhelpful
+/// fill `out` with `func` applied over every window of `win` in `arr`,
+/// `out` must have the shape given by [`Window::array_size`]
+fn apply_over_window_into<T, O, R>(arr: ArrayView3<T>, win: Window, func: &R, mut out: ArrayViewMut3<O>)
+where
+    R: Reducer<T, O>,
+{
+    if win.is_dense() {
+        // create windowed parts of the array
+        let w = win.dims();
+        let windows = arr.windows((w.0, w.1, w.2));
+        // iter through the output array and the windowed array
+        for (a, w) in out.iter_mut().zip(windows) {
+            a.assign_elem(func.reduce(w)); // assignments for some reason, I think = was being unhelpful
+        }
+    } else {
+        for (pos, a) in out.indexed_iter_mut() {
+            a.assign_elem(func.reduce(win.window_at(&arr, pos)));
+        }
     }
-    un_arr
 }
 
-/// run n threads to compute the given function over a moving window of the array
+/// compute the given function over a moving window of the array on the shared thread pool,
+/// see [`crate::pool`]
+///
+/// the output is split into tiles of rows, see [`WindowShape::tile_rows`], each tile reads the
+/// input rows it needs straight from `input_array` and writes into its own part of the output,
+/// so nothing is copied between threads
 ///
 /// Thread count: 12 on 12 thread cpu using rms calculation over |  Total Pixels: 16777216
 ///  Multi Thread rms calc over window:6 | Timed: 2.468437s | Shape: in: (4096, 4096, 3), out: [4091, 4091, 3]
@@ -216,64 +386,216 @@ where
 /// # Arguments
 ///
 /// * `input_array`:  3 dimensional array, image format (width, height, colour_value)
-/// * `window_size`:  [`WindowShape`]
-/// * `func`:  fn(Array3<u8>)->u8
+/// * `window_size`:  [`WindowShape`] or [`Window`]
+/// * `func`:  a [`Reducer`], any `fn(ArrayView3<T>)->O` such as a [`WinFunc`](window_methods::WinFunc)
+///   or a closure, or a reducer holding parameters such as
+///   [`Percentile`](window_methods::Percentile)
+///
+/// returns: Result<ArrayBase<OwnedRepr<u8>, Dim<[usize; 3]>>, PyLensError>
 ///
-/// returns: ArrayBase<OwnedRepr<u8>, Dim<[usize; 3]>>
+/// # Errors
+/// see [`WindowShape::validate`] and [`pool::set_num_threads`]
 ///
 /// # Examples
 ///
 /// ```
 ///
 /// ```
-pub fn thread_apply_over_window<T>(
-    input_array: Array3<T>,
-    win_size: WindowShape,
-    func: WinFunc<T>,
-) -> Array3<T>
+pub fn thread_apply_over_window<T, O, R>(
+    input_array: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    func: R,
+) -> Result<Array3<O>, PyLensError>
 where
-    T: Zero + NumConv + Clone + Copy + Send + 'static,
+    T: Sync,
+    O: Zero + Clone + Send + Sync,
+    R: Reducer<T, O>,
 {
-    // see WindowShape
-    let v_splits_for_array = win_size.create_v_splits(&input_array);
-
-    // thread return items keeper. Reminder: order here is important,
-    // I'm not sending ordering information
-    let mut thread_workers: Vec<_> = vec![];
-    for (va, vb) in v_splits_for_array {
-        let (tx, rx) = mpsc::channel();
-        //let send_copy_of_window_size = window_size; // I dont think this is required
-
-        // create a slice view of the array before sending it
-        let pre_compute_sliced_array = input_array.slice(s![va..vb, .., ..]);
-        // needs ownership, probably possible to refactor that out
-        let pre_compute_slice = pre_compute_sliced_array.to_owned();
-        thread::spawn(move || {
-            // thread open, do compute and send to `rx`
-            let computed_array_output = apply_over_window(pre_compute_slice, win_size, func);
-            tx.send(computed_array_output).unwrap();
+    let win = win_size.into();
+    map_tiles(input_array, win, |src, out| {
+        apply_over_window_into(src, win, &func, out)
+    })
+}
+
+/// like [`thread_apply_over_window`] but `func` gets up to `batch` windows at a time, copied into
+/// one array of shape `(n, w0, w1, w2)`, and fills the `n` results in `out`
+///
+/// meant for functions with a large cost per call, e.g. python callables, the windows of every
+/// batch come from one row tile and are in the order of the output. the first error returned by
+/// `func` stops the remaining batches and is returned
+///
+/// # Errors
+/// the error from `func`, or see [`Window::validate`] and [`pool::set_num_threads`]
+pub fn thread_apply_batched<T, O, E, F>(
+    input_array: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    batch: usize,
+    func: F,
+) -> Result<Array3<O>, E>
+where
+    T: Clone + Sync,
+    O: Zero + Clone + Send + Sync,
+    E: From<PyLensError> + Send,
+    F: Fn(ArrayView4<T>, &mut [O]) -> Result<(), E> + Send + Sync,
+{
+    let win = win_size.into();
+    let batch = batch.max(1);
+    let w = win.dims();
+    let first_error: Mutex<Option<E>> = Mutex::new(None);
+    let out = map_tiles(input_array, win, |src, mut out| {
+        let shape = out.raw_dim();
+        let positions: Vec<(usize, usize, usize)> = ndarray::indices(shape).into_iter().collect();
+        let mut values: Vec<T> = Vec::with_capacity(batch * w.0 * w.1 * w.2);
+        let mut results: Vec<O> = vec![O::zero(); batch];
+        for chunk in positions.chunks(batch) {
+            if first_error.lock().unwrap().is_some() {
+                return;
+            }
+            values.clear();
+            for &pos in chunk {
+                values.extend(win.window_at(&src, pos).iter().cloned());
+            }
+            let windows = ArrayView4::from_shape((chunk.len(), w.0, w.1, w.2), &values)
+                .expect("one window of values per position");
+            match func(windows, &mut results[..chunk.len()]) {
+                Ok(()) => {
+                    for (&pos, r) in chunk.iter().zip(&results) {
+                        out[pos] = r.clone();
+                    }
+                }
+                Err(e) => {
+                    first_error.lock().unwrap().get_or_insert(e);
+                    return;
+                }
+            }
+        }
+    })?;
+    match first_error.into_inner().unwrap() {
+        Some(e) => Err(e),
+        None => Ok(out),
+    }
+}
+
+/// compute `k` values for every window in one pass, `func` writes the values of one window into
+/// its slice, the output has the shape of [`thread_apply_over_window`] with an extra last axis of
+/// length `k`
+///
+/// # Errors
+/// see [`Window::validate`] and [`pool::set_num_threads`]
+pub fn thread_apply_stacked<T, O, F>(
+    input_array: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    k: usize,
+    func: F,
+) -> Result<Array4<O>, PyLensError>
+where
+    T: Sync,
+    O: Zero + Clone + Send + Sync,
+    F: Fn(ArrayView3<T>, &mut [O]) + Send + Sync,
+{
+    let win = win_size.into();
+    let (sh2, _) = win.array_size(&input_array)?;
+    let d = sh2.raw_dim();
+    let out = Array4::<O>::zeros((d[0], d[1], d[2], k));
+    pool::install(|| {
+        let threads = rayon::current_num_threads();
+        tiled_fill(
+            input_array,
+            win,
+            |out_rows| win.shape.tile_rows(out_rows, threads),
+            out,
+            |src, mut out| {
+                let shape = (out.shape()[0], out.shape()[1], out.shape()[2]);
+                for pos in ndarray::indices(shape) {
+                    let mut values = out.slice_mut(s![pos.0, pos.1, pos.2, ..]);
+                    let values = values.as_slice_mut().expect("the last axis is contiguous");
+                    func(win.window_at(&src, pos), values);
+                }
+            },
+        )
+    })
+}
+
+/// run `tile_fn` over row tiles of the output on the shared thread pool,
+/// see [`thread_apply_over_window`] for how the tiles are made
+///
+/// `tile_fn` gets the input rows for one tile and the part of the output it fills, output row
+/// `i` of a tile uses the window starting on input row `i * stride` of the tile's input
+fn map_tiles<T, O, F>(input_array: ArrayView3<T>, win: Window, tile_fn: F) -> Result<Array3<O>, PyLensError>
+where
+    T: Sync,
+    O: Zero + Clone + Send + Sync,
+    F: Fn(ArrayView3<T>, ArrayViewMut3<O>) + Send + Sync,
+{
+    pool::install(|| {
+        let threads = rayon::current_num_threads();
+        tiled_map(
+            input_array,
+            win,
+            |out_rows| win.shape.tile_rows(out_rows, threads),
+            tile_fn,
+        )
+    })?
+}
+
+/// [`map_tiles`] with the tile size picked by `tile_rows(out_rows)`,
+/// runs on whichever rayon pool it is called from
+fn tiled_map<T, O, F>(
+    input_array: ArrayView3<T>,
+    win: Window,
+    tile_rows: impl Fn(usize) -> usize,
+    tile_fn: F,
+) -> Result<Array3<O>, PyLensError>
+where
+    T: Sync,
+    O: Zero + Clone + Send + Sync,
+    F: Fn(ArrayView3<T>, ArrayViewMut3<O>) + Send + Sync,
+{
+    let (sh2, _) = win.array_size(&input_array)?;
+    Ok(tiled_fill(input_array, win, tile_rows, Array3::<O>::zeros(sh2), tile_fn))
+}
+
+/// split `out` into tiles of rows along its first axis and fill them in parallel,
+/// `out` starts with the output shape of `win` and may have more axes after it
+fn tiled_fill<T, O, D, F>(
+    input_array: ArrayView3<T>,
+    win: Window,
+    tile_rows: impl Fn(usize) -> usize,
+    mut out: Array<O, D>,
+    tile_fn: F,
+) -> Array<O, D>
+where
+    T: Sync,
+    O: Send + Sync,
+    D: Dimension,
+    F: Fn(ArrayView3<T>, ArrayViewMut<O, D>) + Send + Sync,
+{
+    let tile = tile_rows(out.shape()[0]);
+    let (stride_0, extent_0) = (win.stride.0, win.extent().0);
+    out.axis_chunks_iter_mut(Axis(0), tile)
+        .into_par_iter()
+        .enumerate()
+        .for_each(|(i, out_tile)| {
+            // n output rows start n windows, the last one reaches `extent` rows past its start
+            let va = i * tile * stride_0;
+            let vb = va + (out_tile.shape()[0] - 1) * stride_0 + extent_0;
+            tile_fn(input_array.slice(s![va..vb, .., ..]), out_tile);
         });
-        // attach the new thread receiver to the worker vec
-        thread_workers.push(rx);
-    }
-    // export all the threads once they're finished, must wait for all finished
-    // otherwise we end up with things out of order
-    let array_stacks: Vec<_> = thread_workers.iter().map(|rx| rx.recv().unwrap()).collect();
-    // views, concat doesn't like actual arrays
-    let array_stacks_view: Vec<_> = array_stacks.iter().map(|a| a.view()).collect();
-    // stack the arrays back into a single array, then return it
-    let re_stacked_array = ndarray::concatenate(Axis(0), array_stacks_view.as_slice()).unwrap();
-
-    re_stacked_array
+    out
 }
 
 #[cfg(test)]
 mod tests {
 
     use crate::window;
-    use ndarray::Array3;
-
-    use crate::window::{thread_apply_over_window, WindowShape};
+    use ndarray::{Array3, ArrayView3};
+
+    use crate::error::PyLensError;
+    use ndarray::s;
+    use crate::window::{
+        apply_over_window, apply_over_window_into, thread_apply_batched, thread_apply_over_window,
+        thread_apply_stacked, tiled_map, Window, WindowShape,
+    };
     use window::window_methods::*;
 
     fn generate_tst_array3u8() -> Array3<u8> {
@@ -301,98 +623,363 @@ mod tests {
     fn u8_test_with_std_array_rms_u64() {
         let test_array = generate_tst_array3u8();
         let win_shape = WindowShape::Triple(5, 5, 1);
-        let out = thread_apply_over_window(test_array, win_shape, faster_rms_u64_adding);
+        let out = thread_apply_over_window(test_array.view(), win_shape, faster_rms_u64_adding).unwrap();
 
         let mut hasher = DefaultHasher::new();
         out.hash(&mut hasher);
-        assert_eq!(13253406290038557312, hasher.finish());
+        assert_eq!(16514049242894983183, hasher.finish());
     }
 
     #[test]
     fn u8_test_with_std_array_stdev_ddof_1() {
         let test_array = generate_tst_array3u8();
         let win_shape = WindowShape::Triple(5, 5, 1);
-        let out = thread_apply_over_window(test_array, win_shape, stdev_ddof_1);
+        let out = thread_apply_over_window(test_array.view(), win_shape, stdev_ddof_1).unwrap();
 
         let mut hasher = DefaultHasher::new();
         out.hash(&mut hasher);
-        assert_eq!(1348078754090702269, hasher.finish());
+        assert_eq!(15239208087884075672, hasher.finish());
     }
 
     #[test]
     fn u8_test_with_std_array_stdev_ddof_0() {
         let test_array = generate_tst_array3u8();
         let win_shape = WindowShape::Triple(5, 5, 1);
-        let out = thread_apply_over_window(test_array, win_shape, stdev_ddof_0);
+        let out = thread_apply_over_window(test_array.view(), win_shape, stdev_ddof_0).unwrap();
 
         let mut hasher = DefaultHasher::new();
         out.hash(&mut hasher);
-        assert_eq!(16665323007214972568, hasher.finish());
+        assert_eq!(16514049242894983183, hasher.finish());
     }
 
     #[test]
     fn u16_test_with_std_array_rms_u64() {
         let test_array = generate_tst_array3u16();
         let win_shape = WindowShape::Triple(5, 5, 1);
-        let out = thread_apply_over_window(test_array, win_shape, faster_rms_u64_adding);
+        let out = thread_apply_over_window(test_array.view(), win_shape, faster_rms_u64_adding).unwrap();
 
         let mut hasher = DefaultHasher::new();
         out.hash(&mut hasher);
-        assert_eq!(8798330982248845663, hasher.finish());
+        assert_eq!(6149010415961624562, hasher.finish());
     }
 
     #[test]
     fn u16_test_with_std_array_stdev_ddof_1() {
         let test_array = generate_tst_array3u16();
         let win_shape = WindowShape::Triple(5, 5, 1);
-        let out = thread_apply_over_window(test_array, win_shape, stdev_ddof_1);
+        let out = thread_apply_over_window(test_array.view(), win_shape, stdev_ddof_1).unwrap();
 
         let mut hasher = DefaultHasher::new();
         out.hash(&mut hasher);
-        assert_eq!(11549407550617260967, hasher.finish());
+        assert_eq!(11443599686512809984, hasher.finish());
     }
 
     #[test]
     fn u16_test_with_std_array_stdev_ddof_0() {
         let test_array = generate_tst_array3u16();
         let win_shape = WindowShape::Triple(5, 5, 1);
-        let out = thread_apply_over_window(test_array, win_shape, stdev_ddof_0);
+        let out = thread_apply_over_window(test_array.view(), win_shape, stdev_ddof_0).unwrap();
 
         let mut hasher = DefaultHasher::new();
         out.hash(&mut hasher);
-        assert_eq!(8077758819790012028, hasher.finish());
+        assert_eq!(6149010415961624562, hasher.finish());
     }
 
     #[test]
     fn u32_test_with_std_array_rms_u64() {
         let test_array = generate_tst_array3u32();
         let win_shape = WindowShape::Triple(5, 5, 1);
-        let out = thread_apply_over_window(test_array, win_shape, faster_rms_u64_adding);
+        let out = thread_apply_over_window(test_array.view(), win_shape, faster_rms_u64_adding).unwrap();
 
         let mut hasher = DefaultHasher::new();
         out.hash(&mut hasher);
-        assert_eq!(12289755379548528329, hasher.finish());
+        assert_eq!(10496513265275582721, hasher.finish());
     }
 
     #[test]
     fn u32_test_with_std_array_stdev_ddof_1() {
         let test_array = generate_tst_array3u32();
         let win_shape = WindowShape::Triple(5, 5, 1);
-        let out = thread_apply_over_window(test_array, win_shape, stdev_ddof_1);
+        let out = thread_apply_over_window(test_array.view(), win_shape, stdev_ddof_1).unwrap();
 
         let mut hasher = DefaultHasher::new();
         out.hash(&mut hasher);
-        assert_eq!(7961783534460445393, hasher.finish());
+        assert_eq!(14171583695112833006, hasher.finish());
     }
 
     #[test]
     fn u32_test_with_std_array_stdev_ddof_0() {
         let test_array = generate_tst_array3u32();
         let win_shape = WindowShape::Triple(5, 5, 1);
-        let out = thread_apply_over_window(test_array, win_shape, stdev_ddof_0);
+        let out = thread_apply_over_window(test_array.view(), win_shape, stdev_ddof_0).unwrap();
 
         let mut hasher = DefaultHasher::new();
         out.hash(&mut hasher);
-        assert_eq!(5942265300642722970, hasher.finish());
+        assert_eq!(10496513265275582721, hasher.finish());
+    }
+
+    #[test]
+    fn window_larger_than_array() {
+        let test_array: Array3<u8> = Array3::zeros((50, 8, 3));
+        let out = thread_apply_over_window(test_array.view(), WindowShape::Double(5, 12), stdev_ddof_0);
+        assert_eq!(
+            Err(PyLensError::WindowTooLarge {
+                axis: 1,
+                window: 12,
+                array: 8
+            }),
+            out
+        );
+    }
+
+    #[test]
+    fn window_deeper_than_array() {
+        let test_array: Array3<u8> = Array3::zeros((50, 50, 3));
+        let out = WindowShape::Triple(5, 5, 4).array_size(&test_array);
+        assert!(matches!(
+            out,
+            Err(PyLensError::WindowTooLarge { axis: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn window_of_size_zero() {
+        let test_array: Array3<u8> = Array3::zeros((50, 50, 3));
+        let out = thread_apply_over_window(test_array.view(), WindowShape::Single(0), stdev_ddof_0);
+        assert_eq!(Err(PyLensError::EmptyWindow { axis: 0 }), out);
+    }
+
+    #[test]
+    fn small_arrays_any_tile_size() {
+        // arrays with fewer output rows than threads, down to a window the size of the array
+        for (rows, win) in [(1, 1), (3, 3), (4, 2), (5, 5), (13, 3), (17, 4)] {
+            let test_array: Array3<u8> =
+                Array3::from_shape_fn((rows, 9, 3), |(a, b, c)| ((a * 31) ^ (b * 7) ^ c) as u8);
+            let win_shape = WindowShape::Double(win, 3);
+            let single = apply_over_window(test_array.view(), win_shape, stdev_ddof_0).unwrap();
+            assert_eq!([rows - win + 1, 7, 3], single.shape());
+            let threaded =
+                thread_apply_over_window(test_array.view(), win_shape, stdev_ddof_0).unwrap();
+            assert_eq!(single, threaded);
+            for tile in 1..=rows + 1 {
+                let win_shape = Window::from(win_shape);
+                let tiled = tiled_map(test_array.view(), win_shape, |_| tile, |src, out| {
+                    apply_over_window_into(src, win_shape, &stdev_ddof_0, out)
+                })
+                .unwrap();
+                assert_eq!(single, tiled, "rows: {} win: {} tile: {}", rows, win, tile);
+            }
+        }
+    }
+
+    #[test]
+    fn any_thread_count() {
+        let test_array = generate_tst_array3u8();
+        let win_shape = WindowShape::Triple(5, 5, 1);
+        let single = apply_over_window(test_array.view(), win_shape, faster_rms_u64_adding).unwrap();
+        for threads in [1, 2, 3, 7, 12] {
+            let local = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let out = local
+                .install(|| {
+                    tiled_map(
+                        test_array.view(),
+                        win_shape.into(),
+                        |r| win_shape.tile_rows(r, threads),
+                        |src, out| {
+                            apply_over_window_into(src, win_shape.into(), &faster_rms_u64_adding, out)
+                        },
+                    )
+                })
+                .unwrap();
+            assert_eq!(single, out, "threads: {}", threads);
+        }
+    }
+
+    #[test]
+    fn tile_rows_cover_output() {
+        let win_shape = WindowShape::Single(5);
+        for threads in 0..=40 {
+            for out_rows in 1..=100 {
+                let tile = win_shape.tile_rows(out_rows, threads);
+                assert!(tile >= 1);
+                // no more than TILES_PER_THREAD tiles per thread
+                assert!(out_rows.div_ceil(tile) <= threads.max(1) * 4);
+            }
+        }
+    }
+
+    #[test]
+    fn strided_is_subsampled_dense() {
+        let test_array = generate_tst_array3u8();
+        let dense = thread_apply_over_window(test_array.view(), WindowShape::Single(5), stdev_ddof_0)
+            .unwrap();
+        let win = Window::from(WindowShape::Single(5)).with_stride((2, 3, 1));
+        let strided = thread_apply_over_window(test_array.view(), win, stdev_ddof_0).unwrap();
+        assert_eq!(dense.slice(s![..;2, ..;3, ..]), strided);
+        assert_eq!([248, 166, 3], strided.shape());
+    }
+
+    #[test]
+    fn dilated_reads_every_nth_element() {
+        let test_array = generate_tst_array3u16();
+        let win = Window::from(WindowShape::Double(3, 2)).with_dilation((4, 3, 1));
+        let out = thread_apply_over_window(test_array.view(), win, faster_rms_u64_adding).unwrap();
+        // extent is (3 - 1) * 4 + 1 = 9 rows and (2 - 1) * 3 + 1 = 4 columns
+        assert_eq!([492, 497, 3], out.shape());
+        for (i, j, k) in [(0, 0, 0), (17, 3, 1), (491, 496, 2)] {
+            let expected = test_array.slice(s![i..i + 9;4, j..j + 4;3, k..k + 1]);
+            assert_eq!(expected.shape(), [3, 2, 1]);
+            assert_eq!(faster_rms_u64_adding(expected), out[[i, j, k]]);
+        }
+    }
+
+    #[test]
+    fn strided_dilated_any_tile_size() {
+        let test_array: Array3<u8> =
+            Array3::from_shape_fn((23, 19, 2), |(a, b, c)| ((a * 13) ^ (b * 5) ^ c) as u8);
+        let win = Window::from(WindowShape::Triple(3, 4, 2))
+            .with_stride((3, 2, 1))
+            .with_dilation((2, 1, 1));
+        let single = apply_over_window(test_array.view(), win, stdev_ddof_1).unwrap();
+        assert_eq!([7, 8, 1], single.shape());
+        for tile in 1..=7 {
+            let tiled = tiled_map(test_array.view(), win, |_| tile, |src, out| {
+                apply_over_window_into(src, win, &stdev_ddof_1, out)
+            })
+            .unwrap();
+            assert_eq!(single, tiled, "tile: {}", tile);
+        }
+    }
+
+    #[test]
+    fn window_output_shape() {
+        let win = Window::from(WindowShape::Single(3));
+        assert_eq!(Ok([8, 8, 3]), win.output_shape(&[10, 10, 3]));
+        let win = win.with_stride((2, 3, 1));
+        assert_eq!(Ok([4, 3, 3]), win.output_shape(&[10, 10, 3]));
+        let win = win.with_dilation((4, 1, 1));
+        assert_eq!((9, 3, 1), win.extent());
+        assert_eq!(Ok([1, 3, 3]), win.output_shape(&[10, 10, 3]));
+        assert!(matches!(
+            win.output_shape(&[8, 10, 3]),
+            Err(PyLensError::WindowTooLarge { axis: 0, window: 9, array: 8 })
+        ));
+        for bad in [win.with_stride((0, 1, 1)), win.with_dilation((1, 1, 0))] {
+            assert!(matches!(
+                bad.output_shape(&[10, 10, 3]),
+                Err(PyLensError::InvalidArgument { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn closure_window_function() {
+        use crate::window::window_methods::{median, percentile, Percentile};
+        let test_array = generate_tst_array3u8();
+        let q = 75.0;
+        let threaded = thread_apply_over_window(test_array.view(), WindowShape::Single(4), |w: ArrayView3<u8>| {
+            percentile(w, q)
+        })
+        .unwrap();
+        let single = apply_over_window(test_array.view(), WindowShape::Single(4), |w: ArrayView3<u8>| {
+            percentile(w, q)
+        })
+        .unwrap();
+        assert_eq!(single, threaded);
+        let reducer = thread_apply_over_window(test_array.view(), WindowShape::Single(4), Percentile::new(q).unwrap())
+            .unwrap();
+        assert_eq!(single, reducer);
+        assert_eq!(
+            thread_apply_over_window(test_array.view(), WindowShape::Single(4), median).unwrap(),
+            thread_apply_over_window(test_array.view(), WindowShape::Single(4), Percentile::new(50.0).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn reducer_with_parameters() {
+        use crate::window::window_methods::{Stdev, Variance};
+        let test_array = generate_tst_array3u16();
+        let win = Window::from(WindowShape::Double(3, 4)).with_stride((2, 2, 1));
+        let stdev: Array3<f64> = thread_apply_over_window(test_array.view(), win, Stdev { ddof: 1.0 }).unwrap();
+        let variance: Array3<f64> =
+            thread_apply_over_window(test_array.view(), win, Variance { ddof: 1.0 }).unwrap();
+        let reference = thread_apply_over_window(test_array.view(), win, stdev_ddof_1).unwrap();
+        for ((s, v), r) in stdev.iter().zip(&variance).zip(&reference) {
+            assert!((s * s - v).abs() <= 1e-6 * v.max(1.0));
+            assert_eq!(*r, s.round() as u16);
+        }
+    }
+
+    #[test]
+    fn rms_is_unscaled() {
+        // rms of the deviations from the window mean is the population standard deviation
+        let test_array = generate_tst_array3u16().mapv(|x| x as f64);
+        let win_shape = WindowShape::Double(4, 6);
+        let stdev = thread_apply_over_window(test_array.view(), win_shape, stdev_ddof_0).unwrap();
+        let rms = thread_apply_over_window(test_array.view(), win_shape, faster_rms_u64_adding).unwrap();
+        assert!(stdev.iter().zip(&rms).all(|(a, b)| (a - b).abs() <= 1e-9 * a.max(1.0)));
+    }
+
+    #[test]
+    fn batched_matches_single_windows() {
+        use crate::window::window_methods::mean;
+        let test_array = generate_tst_array3u8();
+        let win = Window::from(WindowShape::Triple(3, 4, 2)).with_stride((1, 2, 1));
+        let expected: Array3<f64> = thread_apply_over_window(test_array.view(), win, mean).unwrap();
+        for batch in [0, 1, 7, 5000] {
+            let found = thread_apply_batched(test_array.view(), win, batch, |windows, out: &mut [f64]| {
+                assert!(windows.shape()[0] <= batch.max(1));
+                assert_eq!(&windows.shape()[1..], [3, 4, 2]);
+                for (o, w) in out.iter_mut().zip(windows.outer_iter()) {
+                    *o = mean(w);
+                }
+                Ok::<(), PyLensError>(())
+            })
+            .unwrap();
+            assert_eq!(expected, found, "batch: {}", batch);
+        }
+    }
+
+    #[test]
+    fn batched_returns_first_error() {
+        let test_array = generate_tst_array3u8();
+        let out = thread_apply_batched(test_array.view(), WindowShape::Single(3), 64, |_, _: &mut [u8]| {
+            Err(PyLensError::InvalidArgument {
+                name: "func",
+                reason: "failed".to_string(),
+            })
+        });
+        assert!(matches!(out, Err(PyLensError::InvalidArgument { name: "func", .. })));
+        let out = thread_apply_batched(test_array.view(), WindowShape::Single(0), 64, |_, _: &mut [u8]| {
+            Ok::<(), PyLensError>(())
+        });
+        assert!(matches!(out, Err(PyLensError::EmptyWindow { .. })));
+    }
+
+    #[test]
+    fn stacked_matches_single_statistics() {
+        let test_array = generate_tst_array3u16();
+        let win = Window::from(WindowShape::Triple(4, 3, 2))
+            .with_stride((3, 1, 1))
+            .with_dilation((1, 2, 1));
+        let stats = Stats::new(vec![Stat::Mean, Stat::Max, Stat::Stdev], 1.0).unwrap();
+        let stacked = thread_apply_stacked(test_array.view(), win, stats.count(), |w, out| {
+            stats.reduce_into(w, out)
+        })
+        .unwrap();
+        let means: Array3<f64> = thread_apply_over_window(test_array.view(), win, mean).unwrap();
+        let maxes: Array3<u16> = thread_apply_over_window(test_array.view(), win, max).unwrap();
+        let stdevs: Array3<f64> =
+            thread_apply_over_window(test_array.view(), win, Stdev { ddof: 1.0 }).unwrap();
+        assert_eq!([166, 496, 2, 3], stacked.shape());
+        assert_eq!(means, stacked.slice(s![.., .., .., 0]));
+        assert_eq!(maxes.mapv(f64::from), stacked.slice(s![.., .., .., 1]));
+        for (a, b) in stdevs.iter().zip(stacked.slice(s![.., .., .., 2])) {
+            assert!((a - b).abs() < 1e-9);
+        }
     }
 }
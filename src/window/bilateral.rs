@@ -0,0 +1,197 @@
+//! edge preserving bilateral filter, every pixel becomes the mean of its window weighted by a
+//! gaussian of the distance to the centre and a gaussian of the difference to the centre value
+//!
+//! channels are filtered on their own, or jointly with the difference taken as the euclidean
+//! distance between the colours of two pixels, so an edge in any channel is kept in all of them
+
+use ndarray::{s, Array3, ArrayView3, Axis, Zip};
+
+use super::padding::{pad_for_mode, OutputMode, PadMode};
+use super::window_methods::NumConv;
+use super::{thread_apply_over_window, thread_apply_stacked, WindowShape};
+use crate::error::{check_finite, PyLensError};
+
+/// the two gaussians of a bilateral filter and whether the channels are filtered together
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bilateral {
+    spatial_sigma: f64,
+    range_sigma: f64,
+    joint: bool,
+}
+
+impl Bilateral {
+    /// # Errors
+    /// [`PyLensError::NonFinite`] or [`PyLensError::InvalidArgument`] if a sigma isn't a positive
+    /// number
+    pub fn new(spatial_sigma: f64, range_sigma: f64, joint: bool) -> Result<Self, PyLensError> {
+        for (name, sigma) in [("spatial_sigma", spatial_sigma), ("range_sigma", range_sigma)] {
+            if check_finite(name, sigma)? <= 0.0 {
+                return Err(PyLensError::InvalidArgument {
+                    name,
+                    reason: format!("must be positive, got {}", sigma),
+                });
+            }
+        }
+        Ok(Bilateral { spatial_sigma, range_sigma, joint })
+    }
+
+    /// weight of every position of a window of `dims` from its distance to the centre
+    fn spatial_weights(self, dims: (usize, usize, usize)) -> Array3<f64> {
+        let scale = -0.5 / self.spatial_sigma.powi(2);
+        let offset2 = |i: usize, n: usize| (i as f64 - (n / 2) as f64).powi(2);
+        Array3::from_shape_fn(dims, |(a, b, c)| {
+            let d2 = offset2(a, dims.0) + offset2(b, dims.1) + offset2(c, dims.2);
+            (d2 * scale).exp()
+        })
+    }
+
+    /// the range weight of a squared difference
+    fn range_weight(self, d2: f64) -> f64 {
+        (d2 * -0.5 / self.range_sigma.powi(2)).exp()
+    }
+}
+
+/// bilateral filter of every pixel over a window of `size` around it, with the shape of the input
+///
+/// each channel on its own weights every position of a window by its value, the joint filter
+/// weights every pixel of a window of `size` by its colour, so its depth must be 1. NaN spreads to
+/// every window it is in
+///
+/// # Errors
+/// [`PyLensError::InvalidArgument`] if any size is even or a joint window is deeper than 1, see
+/// [`pad_for_mode`]
+pub fn thread_bilateral<T>(
+    input_array: ArrayView3<T>,
+    size: WindowShape,
+    bilateral: Bilateral,
+    pad_mode: PadMode,
+) -> Result<Array3<f64>, PyLensError>
+where
+    T: NumConv + Clone + Sync,
+{
+    let (w0, w1, w2) = size.dims();
+    if [w0, w1, w2].iter().any(|n| n % 2 == 0) {
+        return Err(PyLensError::InvalidArgument {
+            name: "size",
+            reason: format!("every size must be odd to have a centre, got {}", size),
+        });
+    }
+    if bilateral.joint && w2 != 1 {
+        return Err(PyLensError::InvalidArgument {
+            name: "size",
+            reason: format!("a joint window spans the channels, its depth must be 1, got {}", size),
+        });
+    }
+    let padded = pad_for_mode(input_array, size, OutputMode::Same, pad_mode)?;
+    let centre = (w0 / 2, w1 / 2, w2 / 2);
+    if !bilateral.joint {
+        let spatial = bilateral.spatial_weights((w0, w1, w2));
+        let filter = |w: ArrayView3<T>| {
+            let mid = w[centre].as_f64();
+            let (mut sum, mut norm) = (0.0, 0.0);
+            Zip::from(&w).and(&spatial).for_each(|x, &near| {
+                let x = x.as_f64();
+                let weight = near * bilateral.range_weight((x - mid).powi(2));
+                sum += weight * x;
+                norm += weight;
+            });
+            sum / norm
+        };
+        return thread_apply_over_window(padded.view(), size, filter);
+    }
+    let channels = input_array.shape()[2];
+    let spatial = bilateral.spatial_weights((w0, w1, 1));
+    let filter = |w: ArrayView3<T>, out: &mut [f64]| {
+        let mid = w.slice(s![centre.0, centre.1, ..]);
+        let mut norm = 0.0;
+        out.iter_mut().for_each(|o| *o = 0.0);
+        for ((a, b, _), &near) in spatial.indexed_iter() {
+            let colour = w.slice(s![a, b, ..]);
+            let d2 = Zip::from(&colour)
+                .and(&mid)
+                .fold(0.0, |d2, x, m| d2 + (x.as_f64() - m.as_f64()).powi(2));
+            let weight = near * bilateral.range_weight(d2);
+            for (o, x) in out.iter_mut().zip(colour) {
+                *o += weight * x.as_f64();
+            }
+            norm += weight;
+        }
+        out.iter_mut().for_each(|o| *o /= norm);
+    };
+    let joint = WindowShape::Triple(w0, w1, channels);
+    let stacked = thread_apply_stacked(padded.view(), joint, channels, filter)?;
+    Ok(stacked.index_axis_move(Axis(2), 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::window::bilateral::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn keeps_edges_smooths_noise() {
+        let mut rng = StdRng::seed_from_u64(50);
+        let img = Array3::from_shape_fn((20, 20, 1), |(_, b, _)| {
+            let step = if b < 10 { 40 } else { 200 };
+            (step + rng.gen_range(-5..=5)) as u8
+        });
+        let bilateral = Bilateral::new(2.0, 20.0, false).unwrap();
+        let size = WindowShape::Single(7);
+        let out = thread_bilateral(img.view(), size, bilateral, PadMode::Reflect).unwrap();
+        assert_eq!(img.shape(), out.shape());
+        for ((_, b, _), &x) in out.indexed_iter() {
+            // the noise is averaged away but nothing leaks across the edge
+            let step = if b < 10 { 40.0 } else { 200.0 };
+            assert!((x - step).abs() < 4.0, "{} {}", b, x);
+        }
+        // u16 and f32 inputs give the same values
+        let wide = thread_bilateral(img.mapv(u16::from).view(), size, bilateral, PadMode::Reflect);
+        assert_eq!(out, wide.unwrap());
+        let float = thread_bilateral(img.mapv(f32::from).view(), size, bilateral, PadMode::Reflect);
+        assert_eq!(out, float.unwrap());
+    }
+
+    #[test]
+    fn joint_keeps_edges_of_every_channel() {
+        // a strong edge in the first channel and a faint one at the same place in the second
+        let img = Array3::from_shape_fn((9, 12, 2), |(_, b, c)| match (b < 6, c) {
+            (true, 0) => 0.0_f32,
+            (false, 0) => 200.0,
+            (true, _) => 100.0,
+            (false, _) => 110.0,
+        });
+        let size = WindowShape::Single(5);
+        let apart = Bilateral::new(2.0, 30.0, false).unwrap();
+        let apart = thread_bilateral(img.view(), size, apart, PadMode::Edge).unwrap();
+        let joint = Bilateral::new(2.0, 30.0, true).unwrap();
+        let joint = thread_bilateral(img.view(), size, joint, PadMode::Edge).unwrap();
+        // on its own the faint edge blurs, with the strong edge next to it it is kept
+        assert!(apart[[4, 5, 1]] > 101.0);
+        assert!((joint[[4, 5, 1]] - 100.0).abs() < 1e-3);
+        assert!((joint[[4, 6, 1]] - 110.0).abs() < 1e-3);
+        // the strong edge is kept either way
+        assert!(apart[[4, 5, 0]] < 1e-3 && joint[[4, 5, 0]] < 1e-3);
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        assert!(matches!(
+            Bilateral::new(0.0, 1.0, false),
+            Err(PyLensError::InvalidArgument { name: "spatial_sigma", .. })
+        ));
+        assert!(matches!(
+            Bilateral::new(1.0, f64::NAN, false),
+            Err(PyLensError::NonFinite { name: "range_sigma", .. })
+        ));
+        let img = Array3::<u8>::zeros((6, 6, 3));
+        let joint = Bilateral::new(1.0, 1.0, true).unwrap();
+        for size in [WindowShape::Double(3, 4), WindowShape::Triple(3, 3, 3)] {
+            assert!(matches!(
+                thread_bilateral(img.view(), size, joint, PadMode::Reflect),
+                Err(PyLensError::InvalidArgument { name: "size", .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! local contrast normalisation, every pixel becomes `(x - m) / (s + eps)` with the mean `m` and
+//! population standard deviation `s` of the window centred on it
+//!
+//! the mean and standard deviation come from [`super::integral`], so the cost doesn't depend on
+//! the window size, and the output lines up with the input like [`super::threshold`]
+
+use ndarray::{Array3, ArrayView3, Axis, Zip};
+
+use super::integral::thread_integral_moments;
+use super::padding::{pad_for_mode, window_centres, OutputMode, PadMode};
+use super::window_methods::NumConv;
+use super::Window;
+use crate::error::{check_finite, PyLensError};
+
+/// rec. 601 weights of the red, green and blue channels
+const LUMA: [f64; 3] = [0.299, 0.587, 0.114];
+
+/// which values are normalised
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Channels {
+    /// every channel with its own mean and standard deviation
+    Each,
+    /// only the luminance, the output has one channel
+    Luminance,
+}
+
+/// the luminance of every pixel as a `(h, w, 1)` array, rec. 601 weights for 3 or 4 channels,
+/// a 4th channel being alpha and ignored, the mean of the channels for any other count
+pub fn luminance<T: NumConv>(input_array: ArrayView3<T>) -> Array3<f64> {
+    let c = input_array.shape()[2];
+    let luma = input_array.map_axis(Axis(2), |px| match c {
+        3 | 4 => px.iter().zip(LUMA).map(|(x, w)| w * x.as_f64()).sum(),
+        _ => px.iter().map(|x| x.as_f64()).sum::<f64>() / c as f64,
+    });
+    luma.insert_axis(Axis(2))
+}
+
+/// `(x - m) / (s + eps)` for every pixel `x` of `input_array`, or of its [`luminance`], with the
+/// mean `m` and population standard deviation `s` of the window of `win_size` centred on it,
+/// the output has the shape `mode` gives, padded with `pad_mode`
+///
+/// the pixels whose window holds a NaN or infinite value are NaN, other pixels are unaffected
+///
+/// # Errors
+/// [`PyLensError::NonFinite`] or [`PyLensError::InvalidArgument`] if `eps` is negative,
+/// see [`pad_for_mode`] and [`Window::validate`]
+pub fn thread_contrast_normalise<T>(
+    input_array: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    mode: OutputMode,
+    pad_mode: PadMode,
+    eps: f64,
+    channels: Channels,
+) -> Result<Array3<f64>, PyLensError>
+where
+    T: NumConv + Clone + Sync,
+{
+    if check_finite("eps", eps)? < 0.0 {
+        return Err(PyLensError::InvalidArgument {
+            name: "eps",
+            reason: format!("must be at least 0, got {}", eps),
+        });
+    }
+    match channels {
+        Channels::Each => normalise(input_array, win_size.into(), mode, pad_mode, eps),
+        Channels::Luminance => {
+            let luma = luminance(input_array);
+            normalise(luma.view(), win_size.into(), mode, pad_mode, eps)
+        }
+    }
+}
+
+/// [`thread_contrast_normalise`] of every channel of `input_array`
+fn normalise<T>(
+    input_array: ArrayView3<T>,
+    win: Window,
+    mode: OutputMode,
+    pad_mode: PadMode,
+    eps: f64,
+) -> Result<Array3<f64>, PyLensError>
+where
+    T: NumConv + Clone + Sync,
+{
+    let padded = pad_for_mode(input_array, win, mode, pad_mode)?;
+    let padded = padded.view();
+    let centres = window_centres(padded, win)?;
+    let mean = thread_integral_moments(padded, win, |_, m, _| m)?;
+    let spread = thread_integral_moments(padded, win, |n, _, m2| (m2 / n).sqrt() + eps)?;
+    Ok(Zip::from(&centres)
+        .and(&mean)
+        .and(&spread)
+        .map_collect(|x, m, &s| {
+            // a flat window with no eps
+            if s == 0.0 {
+                0.0
+            } else {
+                (x.as_f64() - m) / s
+            }
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::window::contrast::*;
+    use crate::window::WindowShape;
+    use ndarray::{s, Array3};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn normalised_against_reference() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let img: Array3<u16> = Array3::from_shape_fn((23, 18, 3), |_| rng.gen_range(0..4000));
+        let w = img.mapv(|x| x as f64);
+        let win = WindowShape::Double(5, 3);
+        let (mode, pad_mode) = (OutputMode::Valid, PadMode::Reflect);
+        let found = thread_contrast_normalise(img.view(), win, mode, pad_mode, 0.5, Channels::Each)
+            .unwrap();
+        assert_eq!([19, 16, 3], found.shape());
+        for ((i, j, k), f) in found.indexed_iter() {
+            let win = w.slice(s![i..i + 5, j..j + 3, k]);
+            let expected = (w[[i + 2, j + 1, k]] - win.mean().unwrap()) / (win.std(0.0) + 0.5);
+            assert!((expected - f).abs() < 1e-9, "expected {} found {}", expected, f);
+        }
+    }
+
+    #[test]
+    fn nan_only_in_its_windows() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut img: Array3<f32> = Array3::from_shape_fn((20, 16, 1), |_| rng.gen_range(0.0..9.0));
+        img[[8, 7, 0]] = f32::NAN;
+        let w = img.mapv(|x| x as f64);
+        let win = WindowShape::Single(3);
+        let (mode, pad_mode) = (OutputMode::Valid, PadMode::Reflect);
+        let found = thread_contrast_normalise(img.view(), win, mode, pad_mode, 0.5, Channels::Each)
+            .unwrap();
+        for ((i, j, k), f) in found.indexed_iter() {
+            let win = w.slice(s![i..i + 3, j..j + 3, k]);
+            let expected = (w[[i + 1, j + 1, k]] - win.mean().unwrap()) / (win.std(0.0) + 0.5);
+            // the 3 x 3 windows over the NaN, nothing else in its tile
+            let over_nan = (6..=8).contains(&i) && (5..=7).contains(&j);
+            assert_eq!(over_nan, f.is_nan(), "{} {}", i, j);
+            assert!(over_nan || (expected - f).abs() < 1e-9, "expected {} found {}", expected, f);
+        }
+    }
+
+    #[test]
+    fn luminance_is_centred() {
+        let img = Array3::from_shape_fn((9, 11, 4), |(a, b, c)| match c {
+            3 => 255u8,
+            _ => ((a * 7 + b * 3) % 50) as u8 * (c as u8 + 1),
+        });
+        let luma = luminance(img.view());
+        assert_eq!([9, 11, 1], luma.shape());
+        let px = |c| img[[4, 5, c]] as f64;
+        let expected = 0.299 * px(0) + 0.587 * px(1) + 0.114 * px(2);
+        assert!((luma[[4, 5, 0]] - expected).abs() < 1e-12);
+
+        let win = WindowShape::Single(3);
+        let (mode, pad_mode) = (OutputMode::Same, PadMode::Edge);
+        let found =
+            thread_contrast_normalise(img.view(), win, mode, pad_mode, 1e-3, Channels::Luminance)
+                .unwrap();
+        let reference =
+            thread_contrast_normalise(luma.view(), win, mode, pad_mode, 1e-3, Channels::Each)
+                .unwrap();
+        assert_eq!([9, 11, 1], found.shape());
+        assert_eq!(reference, found);
+        let centre = luma.slice(s![3..6, 4..7, 0]);
+        let expected = (luma[[4, 5, 0]] - centre.mean().unwrap()) / (centre.std(0.0) + 1e-3);
+        assert!((expected - found[[4, 5, 0]]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn flat_and_invalid() {
+        let img = Array3::<f32>::from_elem((6, 6, 2), 3.5);
+        let (win, mode, pad_mode) = (WindowShape::Single(3), OutputMode::Same, PadMode::Reflect);
+        let run =
+            |eps| thread_contrast_normalise(img.view(), win, mode, pad_mode, eps, Channels::Each);
+        assert!(run(0.0).unwrap().iter().all(|&x| x == 0.0));
+        assert!(matches!(run(-1.0), Err(PyLensError::InvalidArgument { name: "eps", .. })));
+        assert!(matches!(run(f64::NAN), Err(PyLensError::NonFinite { name: "eps", .. })));
+    }
+}
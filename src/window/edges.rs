@@ -0,0 +1,319 @@
+//! edge and gradient filters, the derivatives of every pixel from a 3x3 operator, the laplacian
+//! and the canny edge detector
+//!
+//! the operators are separable and run one axis at a time with [`thread_separable`] on the image
+//! padded for [`OutputMode::Same`], so every output has the shape of the input. the derivatives
+//! are not normalised, like `scipy.ndimage.sobel`, and each channel is filtered on its own
+
+use ndarray::{Array3, ArrayView3, Zip};
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+use super::kernel::{thread_separable, Kernel};
+use super::padding::{pad_for_mode, OutputMode, PadMode};
+use super::window_methods::NumConv;
+use super::{Window, WindowShape};
+use crate::error::{check_finite, PyLensError};
+
+/// central difference across the derivative axis
+const DERIVATIVE: [f64; 3] = [-1.0, 0.0, 1.0];
+
+/// 3x3 derivative operators, a central difference along one axis smoothed along the other
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Operator {
+    /// smoothed with `[1, 2, 1]`
+    Sobel,
+    /// smoothed with `[3, 10, 3]`, closer to rotation invariant than sobel
+    Scharr,
+    /// smoothed with `[1, 1, 1]`
+    Prewitt,
+}
+
+impl Operator {
+    fn smoothing(self) -> Vec<f64> {
+        match self {
+            Operator::Sobel => vec![1.0, 2.0, 1.0],
+            Operator::Scharr => vec![3.0, 10.0, 3.0],
+            Operator::Prewitt => vec![1.0, 1.0, 1.0],
+        }
+    }
+}
+
+impl FromStr for Operator {
+    type Err = PyLensError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "sobel" => Operator::Sobel,
+            "scharr" => Operator::Scharr,
+            "prewitt" => Operator::Prewitt,
+            _ => {
+                return Err(PyLensError::InvalidArgument {
+                    name: "operator",
+                    reason: format!("expected 'sobel', 'scharr' or 'prewitt', got {:?}", s),
+                })
+            }
+        })
+    }
+}
+
+/// correlation of the `(3, 3, 1)` kernel of `factors` with every pixel of `input_array`
+fn filter_3x3<T>(
+    input_array: ArrayView3<T>,
+    pad_mode: PadMode,
+    factors: [Vec<f64>; 2],
+) -> Result<Array3<f64>, PyLensError>
+where
+    T: NumConv + Clone + Sync,
+{
+    let win = Window::from(WindowShape::Single(3));
+    let padded = pad_for_mode(input_array, win, OutputMode::Same, pad_mode)?;
+    let [a, b] = factors;
+    thread_separable(padded.view(), win, &[a, b, vec![1.0]])
+}
+
+/// derivatives of every pixel down the rows, `dy`, and along the columns to the right, `dx`
+///
+/// # Errors
+/// see [`pad_for_mode`]
+pub fn thread_gradients<T>(
+    input_array: ArrayView3<T>,
+    operator: Operator,
+    pad_mode: PadMode,
+) -> Result<(Array3<f64>, Array3<f64>), PyLensError>
+where
+    T: NumConv + Clone + Sync,
+{
+    let smooth = operator.smoothing();
+    let dy = filter_3x3(input_array, pad_mode, [DERIVATIVE.to_vec(), smooth.clone()])?;
+    let dx = filter_3x3(input_array, pad_mode, [smooth, DERIVATIVE.to_vec()])?;
+    Ok((dy, dx))
+}
+
+/// length `hypot(dy, dx)` of every gradient
+pub fn magnitude(dy: ArrayView3<f64>, dx: ArrayView3<f64>) -> Array3<f64> {
+    Zip::from(&dy).and(&dx).map_collect(|y, x| y.hypot(*x))
+}
+
+/// direction `atan2(dy, dx)` of every gradient in radians, 0 points right and `pi / 2` down
+pub fn orientation(dy: ArrayView3<f64>, dx: ArrayView3<f64>) -> Array3<f64> {
+    Zip::from(&dy).and(&dx).map_collect(|y, x| y.atan2(*x))
+}
+
+/// the 4 neighbour laplacian `[[0, 1, 0], [1, -4, 1], [0, 1, 0]]`, the sum of the second
+/// differences down the rows and along the columns
+///
+/// # Errors
+/// see [`pad_for_mode`]
+pub fn thread_laplacian<T>(
+    input_array: ArrayView3<T>,
+    pad_mode: PadMode,
+) -> Result<Array3<f64>, PyLensError>
+where
+    T: NumConv + Clone + Sync,
+{
+    let (second, centre) = (vec![1.0, -2.0, 1.0], vec![0.0, 1.0, 0.0]);
+    let rows = filter_3x3(input_array, pad_mode, [second.clone(), centre.clone()])?;
+    Ok(rows + filter_3x3(input_array, pad_mode, [centre, second])?)
+}
+
+/// parameters of [`thread_canny`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Canny {
+    /// standard deviation of the gaussian smoothing before the sobel gradients, 0 doesn't smooth
+    pub sigma: f64,
+    /// gradients between `low` and `high` are edges when they connect to one above `high`,
+    /// defaults to half of `high`
+    pub low: Option<f64>,
+    /// gradients above it are always edges, defaults to a fifth of the largest gradient
+    pub high: Option<f64>,
+}
+
+impl Canny {
+    /// `(low, high)` for an image whose largest gradient is `largest`
+    ///
+    /// # Errors
+    /// [`PyLensError::NonFinite`] for a NaN or infinite threshold,
+    /// [`PyLensError::InvalidArgument`] if `low` is negative or above `high`
+    fn thresholds(self, largest: f64) -> Result<(f64, f64), PyLensError> {
+        let high = check_finite("high_threshold", self.high.unwrap_or(largest / 5.0))?;
+        let low = check_finite("low_threshold", self.low.unwrap_or(high / 2.0))?;
+        if !(0.0..=high).contains(&low) {
+            return Err(PyLensError::InvalidArgument {
+                name: "low_threshold",
+                reason: format!("must be between 0 and the high threshold {}, got {}", high, low),
+            });
+        }
+        Ok((low, high))
+    }
+}
+
+/// neighbours either side of a pixel along its gradient, as `(row, column)` steps
+fn across_edge(dy: f64, dx: f64) -> (isize, isize) {
+    // the direction rounded to the nearest 45 degrees, opposite directions are the same
+    let sector = ((dy.atan2(dx) / (PI / 4.0)).round() as isize).rem_euclid(4);
+    match sector {
+        0 => (0, 1),
+        1 => (1, 1),
+        2 => (1, 0),
+        _ => (1, -1),
+    }
+}
+
+/// `magnitude` where it is a maximum along its gradient, 0 elsewhere, a plateau keeps its last
+/// pixel along the gradient so edges are one pixel wide
+fn non_maximum_suppression(
+    magnitude: &Array3<f64>,
+    dy: &Array3<f64>,
+    dx: &Array3<f64>,
+) -> Array3<f64> {
+    let (h, w, _) = magnitude.dim();
+    let at = |i: usize, j: usize, k: usize, (si, sj): (isize, isize)| {
+        let (i, j) = (i as isize + si, j as isize + sj);
+        match (0..h as isize).contains(&i) && (0..w as isize).contains(&j) {
+            true => magnitude[[i as usize, j as usize, k]],
+            false => 0.0,
+        }
+    };
+    Array3::from_shape_fn(magnitude.raw_dim(), |(i, j, k)| {
+        let m = magnitude[[i, j, k]];
+        let (si, sj) = across_edge(dy[[i, j, k]], dx[[i, j, k]]);
+        match m >= at(i, j, k, (-si, -sj)) && m > at(i, j, k, (si, sj)) {
+            true => m,
+            false => 0.0,
+        }
+    })
+}
+
+/// pixels of `thin` above `high` and those above `low` connected to them through their 8
+/// neighbours, in the same channel
+fn hysteresis(thin: &Array3<f64>, low: f64, high: f64) -> Array3<bool> {
+    let (h, w, _) = thin.dim();
+    let mut edges = Array3::from_elem(thin.raw_dim(), false);
+    let strong = thin.indexed_iter().filter(|(_, &m)| m > high);
+    let mut stack: Vec<_> = strong.map(|(i, _)| i).collect();
+    while let Some((i, j, k)) = stack.pop() {
+        if edges[[i, j, k]] {
+            continue;
+        }
+        edges[[i, j, k]] = true;
+        for a in i.saturating_sub(1)..(i + 2).min(h) {
+            for b in j.saturating_sub(1)..(j + 2).min(w) {
+                if !edges[[a, b, k]] && thin[[a, b, k]] > low {
+                    stack.push((a, b, k));
+                }
+            }
+        }
+    }
+    edges
+}
+
+/// canny edges of every channel, gaussian smoothing, sobel gradients, thinning to the maxima
+/// along the gradient and hysteresis between the two thresholds
+///
+/// # Errors
+/// see [`Canny::thresholds`], [`Kernel::gaussian`] and [`pad_for_mode`]
+pub fn thread_canny<T>(
+    input_array: ArrayView3<T>,
+    canny: Canny,
+    pad_mode: PadMode,
+) -> Result<Array3<bool>, PyLensError>
+where
+    T: NumConv + Clone + Sync,
+{
+    let kernel = Kernel::gaussian((canny.sigma, canny.sigma, 0.0), 4.0)?;
+    let factors = kernel.factors().expect("gaussians are separable");
+    let win = Window::from(kernel.shape());
+    let padded = pad_for_mode(input_array, win, OutputMode::Same, pad_mode)?;
+    let smoothed = thread_separable(padded.view(), win, factors)?;
+    let (dy, dx) = thread_gradients(smoothed.view(), Operator::Sobel, pad_mode)?;
+    let magnitude = magnitude(dy.view(), dx.view());
+    let (low, high) = canny.thresholds(magnitude.fold(0.0, |a: f64, &b| a.max(b)))?;
+    let thin = non_maximum_suppression(&magnitude, &dy, &dx);
+    Ok(hysteresis(&thin, low, high))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::window::edges::*;
+    use crate::window::kernel::Correlate;
+    use crate::window::thread_apply_over_window;
+    use ndarray::{arr2, s, Array3, Axis};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn operators_match_kernels() {
+        let mut rng = StdRng::seed_from_u64(48);
+        let img: Array3<u8> = Array3::from_shape_fn((17, 23, 2), |_| rng.gen());
+        let win = Window::from(WindowShape::Single(3));
+        let padded = pad_for_mode(img.view(), win, OutputMode::Same, PadMode::Reflect).unwrap();
+        let direct = |k: [[f64; 3]; 3]| {
+            let kernel = Kernel::new(arr2(&k).insert_axis(Axis(2))).unwrap();
+            thread_apply_over_window(padded.view(), win, Correlate { kernel: &kernel }).unwrap()
+        };
+        let (dy, dx) = thread_gradients(img.view(), Operator::Scharr, PadMode::Reflect).unwrap();
+        let scharr_y = [[-3.0, -10.0, -3.0], [0.0, 0.0, 0.0], [3.0, 10.0, 3.0]];
+        let scharr_x = [[-3.0, 0.0, 3.0], [-10.0, 0.0, 10.0], [-3.0, 0.0, 3.0]];
+        let laplacian = [[0.0, 1.0, 0.0], [1.0, -4.0, 1.0], [0.0, 1.0, 0.0]];
+        let found = thread_laplacian(img.view(), PadMode::Reflect).unwrap();
+        for (expected, found) in [(scharr_y, dy), (scharr_x, dx), (laplacian, found)] {
+            let expected = direct(expected);
+            assert_eq!(img.shape(), found.shape());
+            assert!(expected.iter().zip(&found).all(|(a, b)| (a - b).abs() < 1e-9));
+        }
+    }
+
+    #[test]
+    fn gradient_of_a_ramp() {
+        // rises by 2 down the rows and 1 along the columns
+        let img = Array3::from_shape_fn((6, 7, 1), |(a, b, _)| (2 * a + b) as f32);
+        let (dy, dx) = thread_gradients(img.view(), Operator::Prewitt, PadMode::Edge).unwrap();
+        // the central difference spans 2 pixels and the smoothing sums 3
+        assert_eq!(12.0, dy[[3, 3, 0]]);
+        assert_eq!(6.0, dx[[3, 3, 0]]);
+        let m = magnitude(dy.view(), dx.view());
+        assert!((m[[3, 3, 0]] - 180f64.sqrt()).abs() < 1e-12);
+        let angle = orientation(dy.view(), dx.view());
+        assert!((angle[[3, 3, 0]] - 2f64.atan()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn canny_square() {
+        let mut img = Array3::<u8>::zeros((20, 20, 1));
+        img.slice_mut(s![5..15, 5..15, ..]).fill(200);
+        let canny = Canny { sigma: 1.0, low: None, high: None };
+        let edges = thread_canny(img.view(), canny, PadMode::Reflect).unwrap();
+        // a thin outline, every side crossed once, and nothing far from it
+        assert_eq!(2, edges.slice(s![10, .., 0]).iter().filter(|&&e| e).count());
+        assert!(edges.slice(s![8..12, 8..12, 0]).iter().all(|&e| !e));
+        assert!(edges.slice(s![..2, .., 0]).iter().all(|&e| !e));
+        assert_eq!(2, edges.slice(s![.., 10, 0]).iter().filter(|&&e| e).count());
+        // a flat image has no edges
+        let flat = Array3::<u8>::from_elem((8, 8, 1), 9);
+        let edges = thread_canny(flat.view(), canny, PadMode::Reflect).unwrap();
+        assert!(!edges.iter().any(|&e| e));
+    }
+
+    #[test]
+    fn invalid_edge_parameters() {
+        let img = Array3::<u8>::zeros((8, 8, 1));
+        let run = |low, high| {
+            let canny = Canny { sigma: 1.0, low, high };
+            thread_canny(img.view(), canny, PadMode::Reflect)
+        };
+        assert!(matches!(
+            run(Some(5.0), Some(1.0)),
+            Err(PyLensError::InvalidArgument { name: "low_threshold", .. })
+        ));
+        assert!(matches!(
+            run(None, Some(f64::NAN)),
+            Err(PyLensError::NonFinite { name: "high_threshold", .. })
+        ));
+        assert!(matches!(
+            "roberts".parse::<Operator>(),
+            Err(PyLensError::InvalidArgument { name: "operator", .. })
+        ));
+    }
+}
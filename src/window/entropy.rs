@@ -0,0 +1,205 @@
+//! windowed shannon entropy of a histogram of the values, in bits
+//!
+//! windows without a dilation slide one histogram along each row, see [`super::histogram`], and
+//! update the entropy with every count from `sum c * log2(c)`, so a step costs the same for any
+//! number of bins
+
+use ndarray::{Array1, Array3, ArrayView3, ArrayViewMut3};
+
+use super::histogram::{slide_histogram, SlidingHistogram};
+use super::window_methods::{NumConv, Reducer};
+use super::{map_tiles, thread_apply_over_window, Window};
+use crate::error::{check_finite, PyLensError};
+
+/// equal width histogram bins over a range of values
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bins {
+    lo: f64,
+    width: f64,
+    count: usize,
+}
+
+impl Bins {
+    /// `count` bins from `lo` to `hi`, values outside go to the first or last bin and NaN to the
+    /// first
+    ///
+    /// # Errors
+    /// [`PyLensError::InvalidArgument`] if `count` is not in `1..=65536` or `hi` is not above
+    /// `lo`, [`PyLensError::NonFinite`] for a NaN or infinite `lo` or `hi`
+    pub fn new(count: usize, (lo, hi): (f64, f64)) -> Result<Self, PyLensError> {
+        if !(1..=1 << 16).contains(&count) {
+            return Err(PyLensError::InvalidArgument {
+                name: "bins",
+                reason: format!("expected 1 to 65536 bins, got {}", count),
+            });
+        }
+        if check_finite("value_range", hi)? <= check_finite("value_range", lo)? {
+            return Err(PyLensError::InvalidArgument {
+                name: "value_range",
+                reason: format!("the upper value {} must be above the lower {}", hi, lo),
+            });
+        }
+        Ok(Bins { lo, width: (hi - lo) / count as f64, count })
+    }
+
+    fn index<T: NumConv>(&self, x: &T) -> usize {
+        let i = ((x.as_f64() - self.lo) / self.width).floor();
+        i.clamp(0.0, (self.count - 1) as f64) as usize
+    }
+}
+
+/// entropy of the histogram of each window, see [`thread_entropy`] for the faster version of
+/// dense windows
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Entropy {
+    pub bins: Bins,
+}
+
+impl<T: NumConv> Reducer<T, f64> for Entropy {
+    fn reduce(&self, w: ArrayView3<T>) -> f64 {
+        let mut indices: Vec<usize> = w.iter().map(|x| self.bins.index(x)).collect();
+        indices.sort_unstable();
+        let n = indices.len() as f64;
+        let s: f64 = indices
+            .chunk_by(|a, b| a == b)
+            .map(|run| run.len() as f64 * (run.len() as f64).log2())
+            .sum();
+        n.log2() - s / n
+    }
+}
+
+/// histogram of one window with the running `sum c * log2(c)` of its counts
+struct EntropyHistogram<'a> {
+    counts: Vec<u32>,
+    /// `c * log2(c)` for every count up to the window size
+    clogc: &'a [f64],
+    sum: f64,
+}
+
+impl SlidingHistogram for EntropyHistogram<'_> {
+    fn add(&mut self, bin: usize) {
+        let c = self.counts[bin] as usize;
+        self.sum += self.clogc[c + 1] - self.clogc[c];
+        self.counts[bin] += 1;
+    }
+
+    fn remove(&mut self, bin: usize) {
+        let c = self.counts[bin] as usize;
+        self.sum += self.clogc[c - 1] - self.clogc[c];
+        self.counts[bin] -= 1;
+    }
+
+    /// the sum starts again from an exact 0 so rounding doesn't build up over the rows
+    fn reset(&mut self) {
+        self.sum = 0.0;
+    }
+}
+
+/// fill `out` with the entropy of every window of `win` in `arr`, `win` has no dilation
+fn sliding_entropy_into<T: NumConv>(
+    arr: ArrayView3<T>,
+    win: Window,
+    bins: Bins,
+    out: ArrayViewMut3<f64>,
+) {
+    let indices = arr.map(|x| bins.index(x) as u16);
+    let (w0, w1, w2) = win.dims();
+    let n = w0 * w1 * w2;
+    let clogc = Array1::from_shape_fn(n + 1, |c| match c {
+        0 => 0.0,
+        c => c as f64 * (c as f64).log2(),
+    });
+    let mut hist = EntropyHistogram {
+        counts: vec![0; bins.count],
+        clogc: clogc.as_slice().expect("a new array is contiguous"),
+        sum: 0.0,
+    };
+    let log_n = (n as f64).log2();
+    slide_histogram(indices.view(), win, &mut hist, out, |h| log_n - h.sum / n as f64);
+}
+
+/// shannon entropy in bits of the histogram of every window, with `bins`
+///
+/// # Errors
+/// see [`Window::validate`]
+pub fn thread_entropy<T>(
+    input_array: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    bins: Bins,
+) -> Result<Array3<f64>, PyLensError>
+where
+    T: NumConv + Sync,
+{
+    let win = win_size.into();
+    match win.dilation {
+        (1, 1, 1) => map_tiles(input_array, win, |src, out| {
+            sliding_entropy_into(src, win, bins, out)
+        }),
+        _ => thread_apply_over_window(input_array, win, Entropy { bins }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::window::entropy::*;
+    use crate::window::WindowShape;
+    use ndarray::Array3;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn sliding_matches_sorted() {
+        let mut rng = StdRng::seed_from_u64(45);
+        let img: Array3<u8> = Array3::from_shape_fn((31, 40, 2), |_| rng.gen_range(0..12));
+        let bins = Bins::new(256, (0.0, 256.0)).unwrap();
+        for win in [
+            Window::from(WindowShape::Single(1)),
+            Window::from(WindowShape::Double(5, 7)),
+            Window::from(WindowShape::Triple(3, 4, 2)),
+            Window::from(WindowShape::Double(4, 3)).with_stride((2, 3, 1)),
+            Window::from(WindowShape::Double(2, 2)).with_stride((1, 5, 1)),
+            Window::from(WindowShape::Double(3, 3)).with_dilation((2, 2, 1)),
+        ] {
+            let sliding = thread_entropy(img.view(), win, bins).unwrap();
+            let sorted = thread_apply_over_window(img.view(), win, Entropy { bins }).unwrap();
+            assert_eq!(sorted.shape(), sliding.shape());
+            for (a, b) in sorted.iter().zip(&sliding) {
+                assert!((a - b).abs() < 1e-9, "{} expected {} found {}", win, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn entropy_values() {
+        // 4 equally likely values are 2 bits, a constant window is 0
+        let img = Array3::from_shape_fn((4, 8, 1), |(a, b, _)| match b {
+            0..=3 => (a + b % 2 * 4) as u16,
+            _ => 9,
+        });
+        let bins = Bins::new(16, (0.0, 16.0)).unwrap();
+        let found = thread_entropy(img.view(), WindowShape::Double(2, 2), bins).unwrap();
+        assert!((found[[0, 0, 0]] - 2.0).abs() < 1e-12);
+        assert_eq!(0.0, found[[1, 5, 0]]);
+        // coarse bins merge values, 0..=3 and 4..=7 fall in two bins
+        let coarse = Bins::new(2, (0.0, 8.0)).unwrap();
+        let found = thread_entropy(img.view(), WindowShape::Double(2, 2), coarse).unwrap();
+        assert!((found[[0, 0, 0]] - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn invalid_bins() {
+        assert!(matches!(
+            Bins::new(0, (0.0, 1.0)),
+            Err(PyLensError::InvalidArgument { name: "bins", .. })
+        ));
+        assert!(matches!(
+            Bins::new(8, (2.0, 1.0)),
+            Err(PyLensError::InvalidArgument { name: "value_range", .. })
+        ));
+        assert!(matches!(
+            Bins::new(8, (0.0, f64::INFINITY)),
+            Err(PyLensError::NonFinite { name: "value_range", .. })
+        ));
+    }
+}
@@ -0,0 +1,208 @@
+//! non-rectangular windows, a [`Footprint`] marks which elements of the box window are used
+//!
+//! [`Masked`] gathers the marked elements of each window before handing them to a statistic, so
+//! every [`Reducer`] honours a footprint without knowing about it
+
+use ndarray::{s, Array3, ArrayView3};
+
+use super::window_methods::Reducer;
+use super::WindowShape;
+use crate::error::PyLensError;
+
+/// boolean mask over a window, only elements where it is `true` contribute to a statistic
+#[derive(Clone, Debug, PartialEq)]
+pub struct Footprint {
+    mask: Array3<bool>,
+    /// index of every `true` element, in the order of the window
+    offsets: Vec<(usize, usize, usize)>,
+}
+
+impl Footprint {
+    /// # Errors
+    /// [`PyLensError::EmptyWindow`] if any axis of `mask` is empty,
+    /// [`PyLensError::InvalidArgument`] if no element of `mask` is `true`
+    pub fn new(mask: Array3<bool>) -> Result<Self, PyLensError> {
+        if let Some(axis) = mask.shape().iter().position(|&n| n == 0) {
+            return Err(PyLensError::EmptyWindow { axis });
+        }
+        let offsets: Vec<_> = mask.indexed_iter().filter(|(_, &m)| m).map(|(i, _)| i).collect();
+        if offsets.is_empty() {
+            return Err(PyLensError::InvalidArgument {
+                name: "footprint",
+                reason: "at least one element must be true".to_string(),
+            });
+        }
+        Ok(Footprint { mask, offsets })
+    }
+
+    /// `(2r + 1, 2r + 1, 1)` footprint from the distance `(dy, dx)` of each element to the centre
+    fn from_distance(radius: usize, keep: impl Fn(isize, isize) -> bool) -> Self {
+        let r = radius as isize;
+        let n = 2 * radius + 1;
+        let mask = Array3::from_shape_fn((n, n, 1), |(i, j, _)| keep(i as isize - r, j as isize - r));
+        Footprint::new(mask).expect("the centre row and column are never empty")
+    }
+
+    /// every element within `radius` of the centre
+    pub fn disk(radius: usize) -> Self {
+        let r2 = (radius * radius) as isize;
+        Footprint::from_distance(radius, |y, x| y * y + x * x <= r2)
+    }
+
+    /// ring of the elements of a [`Footprint::disk`] of `outer` that are not in a disk of
+    /// `inner - 1`, so both radii are included and `annulus(0, r)` is `disk(r)`
+    ///
+    /// # Errors
+    /// [`PyLensError::InvalidArgument`] if `inner` is larger than `outer`
+    pub fn annulus(inner: usize, outer: usize) -> Result<Self, PyLensError> {
+        if inner > outer {
+            return Err(PyLensError::InvalidArgument {
+                name: "inner",
+                reason: format!("the inner radius {} is larger than the outer {}", inner, outer),
+            });
+        }
+        let hole = match inner {
+            0 => -1,
+            _ => ((inner - 1) * (inner - 1)) as isize,
+        };
+        let r2 = (outer * outer) as isize;
+        Ok(Footprint::from_distance(outer, |y, x| (hole + 1..=r2).contains(&(y * y + x * x))))
+    }
+
+    /// every element within `radius` steps of the centre, moving along rows and columns
+    pub fn diamond(radius: usize) -> Self {
+        let r = radius as isize;
+        Footprint::from_distance(radius, |y, x| y.abs() + x.abs() <= r)
+    }
+
+    /// the centre row and column, `radius` elements either side of the centre
+    pub fn cross(radius: usize) -> Self {
+        Footprint::from_distance(radius, |y, x| y == 0 || x == 0)
+    }
+
+    /// box window the footprint covers
+    pub fn shape(&self) -> WindowShape {
+        let sh = self.mask.shape();
+        WindowShape::Triple(sh[0], sh[1], sh[2])
+    }
+
+    pub fn mask(&self) -> ArrayView3<'_, bool> {
+        self.mask.view()
+    }
+
+    /// the footprint reversed on every axis
+    pub fn flipped(&self) -> Footprint {
+        Footprint::new(self.mask.slice(s![..;-1, ..;-1, ..;-1]).to_owned())
+            .expect("flipping keeps every element")
+    }
+
+    /// number of elements used from each window
+    pub fn count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// the elements of `w` under the footprint as a `(1, n, 1)` window, stored in `buf`,
+    /// `w` must have the shape of the footprint
+    pub fn masked<'b, T: Clone>(&self, w: ArrayView3<T>, buf: &'b mut Vec<T>) -> ArrayView3<'b, T> {
+        buf.clear();
+        buf.extend(self.offsets.iter().map(|&i| w[i].clone()));
+        ArrayView3::from_shape((1, buf.len(), 1), buf).expect("one value per offset")
+    }
+}
+
+/// `reducer` applied to the elements of each window under `footprint`
+#[derive(Copy, Clone, Debug)]
+pub struct Masked<'a, R> {
+    pub footprint: &'a Footprint,
+    pub reducer: R,
+}
+
+impl<T, O, R> Reducer<T, O> for Masked<'_, R>
+where
+    T: Clone,
+    R: Reducer<T, O>,
+{
+    fn reduce(&self, w: ArrayView3<T>) -> O {
+        let mut buf = Vec::with_capacity(self.footprint.offsets.len());
+        self.reducer.reduce(self.footprint.masked(w, &mut buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::window::footprint::*;
+    use crate::window::window_methods::{max, mean, median};
+    use crate::window::{thread_apply_over_window, Window};
+    use ndarray::Array3;
+
+    fn rows(f: &Footprint) -> Vec<String> {
+        f.mask()
+            .index_axis(ndarray::Axis(2), 0)
+            .rows()
+            .into_iter()
+            .map(|r| r.iter().map(|&m| if m { '#' } else { '.' }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn footprint_shapes() {
+        assert_eq!(vec![".#.", "###", ".#."], rows(&Footprint::disk(1)));
+        assert_eq!(
+            vec!["..#..", ".###.", "#####", ".###.", "..#.."],
+            rows(&Footprint::diamond(2))
+        );
+        assert_eq!(vec!["..#..", "..#..", "#####", "..#..", "..#.."], rows(&Footprint::cross(2)));
+        assert_eq!(
+            vec!["..#..", ".#.#.", "#...#", ".#.#.", "..#.."],
+            rows(&Footprint::annulus(2, 2).unwrap())
+        );
+        assert_eq!(13, Footprint::disk(2).count());
+        assert_eq!(WindowShape::Triple(7, 7, 1), Footprint::disk(3).shape());
+        assert_eq!(vec!["#"], rows(&Footprint::annulus(0, 0).unwrap()));
+        assert_eq!(Footprint::disk(3), Footprint::annulus(0, 3).unwrap());
+    }
+
+    #[test]
+    fn only_masked_elements_count() {
+        let img = Array3::from_shape_fn((20, 16, 2), |(a, b, c)| ((a * 5) ^ (b * 11) ^ c) as u8);
+        let footprint = Footprint::cross(1);
+        let win = Window::from(footprint.shape());
+        let reducer = Masked { footprint: &footprint, reducer: max };
+        let masked = thread_apply_over_window(img.view(), win, reducer).unwrap();
+        let expected = Array3::from_shape_fn((18, 14, 2), |(i, j, k)| {
+            [(i, j + 1), (i + 1, j), (i + 1, j + 1), (i + 1, j + 2), (i + 2, j + 1)]
+                .iter()
+                .map(|&(a, b)| img[[a, b, k]])
+                .max()
+                .unwrap()
+        });
+        assert_eq!(expected, masked);
+
+        // a full mask is the box window
+        let full = Footprint::new(Array3::from_elem((3, 4, 2), true)).unwrap();
+        let win = Window::from(full.shape());
+        for stat in [mean, median] {
+            let boxed = thread_apply_over_window(img.view(), win, stat).unwrap();
+            let reducer = Masked { footprint: &full, reducer: stat };
+            let masked = thread_apply_over_window(img.view(), win, reducer).unwrap();
+            assert_eq!(boxed, masked);
+        }
+    }
+
+    #[test]
+    fn invalid_footprints() {
+        assert!(matches!(
+            Footprint::new(Array3::from_elem((3, 3, 1), false)),
+            Err(PyLensError::InvalidArgument { name: "footprint", .. })
+        ));
+        assert!(matches!(
+            Footprint::new(Array3::from_elem((3, 0, 1), true)),
+            Err(PyLensError::EmptyWindow { axis: 1 })
+        ));
+        assert!(matches!(
+            Footprint::annulus(3, 2),
+            Err(PyLensError::InvalidArgument { name: "inner", .. })
+        ));
+    }
+}
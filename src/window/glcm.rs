@@ -0,0 +1,357 @@
+//! grey level co-occurrence matrices (GLCM) and the haralick texture features of every window
+//!
+//! the image is first quantised to a few grey levels with [`quantise`], the matrix of a window
+//! counts how often level `a` has level `b` at an offset from it. the matrix itself is never
+//! built, every feature but the energy and entropy is a mean over the pairs of the window, those
+//! two count equal pairs after sorting them, so large level counts cost nothing extra
+
+use ndarray::{Array3, Array4, ArrayView3, Axis};
+use std::str::FromStr;
+
+use super::window_methods::NumConv;
+use super::{thread_apply_stacked, Window};
+use crate::error::{check_finite, PyLensError};
+
+/// a haralick feature of a normalised co-occurrence matrix `p`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Haralick {
+    /// `sum p(a, b) * (a - b)^2`
+    Contrast,
+    /// `sum p(a, b) / (1 + (a - b)^2)`
+    Homogeneity,
+    /// `sqrt(sum p(a, b)^2)`
+    Energy,
+    /// `sum p(a, b) * (a - mean_a) * (b - mean_b) / (std_a * std_b)`, 1 when either is constant
+    Correlation,
+    /// `-sum p(a, b) * log2(p(a, b))`
+    Entropy,
+}
+
+impl Haralick {
+    pub const ALL: [Haralick; 5] = [
+        Haralick::Contrast,
+        Haralick::Homogeneity,
+        Haralick::Energy,
+        Haralick::Correlation,
+        Haralick::Entropy,
+    ];
+
+    /// the name [`Haralick::from_str`] parses
+    pub fn name(self) -> &'static str {
+        match self {
+            Haralick::Contrast => "contrast",
+            Haralick::Homogeneity => "homogeneity",
+            Haralick::Energy => "energy",
+            Haralick::Correlation => "correlation",
+            Haralick::Entropy => "entropy",
+        }
+    }
+}
+
+impl FromStr for Haralick {
+    type Err = PyLensError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Haralick::ALL.into_iter().find(|f| f.name() == s) {
+            Some(f) => Ok(f),
+            None => Err(PyLensError::InvalidArgument {
+                name: "features",
+                reason: format!(
+                    "expected 'contrast', 'homogeneity', 'energy', 'correlation' or 'entropy', \
+                     got {:?}",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+/// which pairs make the co-occurrence matrices of a window and which features are taken from them
+#[derive(Clone, Debug, PartialEq)]
+pub struct Glcm {
+    /// `(rows, columns)` from the first element of a pair to the second
+    offsets: Vec<(isize, isize)>,
+    features: Vec<Haralick>,
+    /// count every pair both ways, the matrix is then its own transpose
+    symmetric: bool,
+    /// one value per feature, the mean over the offsets, otherwise one per offset and feature
+    average: bool,
+}
+
+impl Glcm {
+    /// # Errors
+    /// [`PyLensError::InvalidArgument`] if `offsets` or `features` is empty
+    pub fn new(
+        offsets: Vec<(isize, isize)>,
+        features: Vec<Haralick>,
+        symmetric: bool,
+        average: bool,
+    ) -> Result<Self, PyLensError> {
+        if offsets.is_empty() {
+            return Err(PyLensError::InvalidArgument {
+                name: "offsets",
+                reason: "at least one offset is needed".to_string(),
+            });
+        }
+        if features.is_empty() {
+            return Err(PyLensError::InvalidArgument {
+                name: "features",
+                reason: "at least one feature is needed".to_string(),
+            });
+        }
+        Ok(Glcm { offsets, features, symmetric, average })
+    }
+
+    /// number of values for every window, the length of the `out` slice of
+    /// [`Glcm::features_into`], offset major when the offsets are not averaged
+    pub fn count(&self) -> usize {
+        match self.average {
+            true => self.features.len(),
+            false => self.features.len() * self.offsets.len(),
+        }
+    }
+
+    /// write the features of the window `w` of grey levels into `out`, pairs never cross
+    /// between layers of the window
+    pub fn features_into(&self, w: ArrayView3<u8>, out: &mut [f64]) {
+        out.iter_mut().for_each(|o| *o = 0.0);
+        let n_features = self.features.len();
+        let needs_counts = self
+            .features
+            .iter()
+            .any(|f| matches!(f, Haralick::Energy | Haralick::Entropy));
+        let mut pairs: Vec<(u8, u8)> = Vec::new();
+        for (o, &(dy, dx)) in self.offsets.iter().enumerate() {
+            pairs.clear();
+            for layer in w.axis_iter(Axis(2)) {
+                let (h, wd) = (layer.shape()[0] as isize, layer.shape()[1] as isize);
+                for i in 0.max(-dy)..h.min(h - dy) {
+                    for j in 0.max(-dx)..wd.min(wd - dx) {
+                        let a = layer[[i as usize, j as usize]];
+                        let b = layer[[(i + dy) as usize, (j + dx) as usize]];
+                        pairs.push((a, b));
+                        if self.symmetric {
+                            pairs.push((b, a));
+                        }
+                    }
+                }
+            }
+            let values = match self.average {
+                true => &mut out[..],
+                false => &mut out[o * n_features..(o + 1) * n_features],
+            };
+            let weight = if self.average { 1.0 / self.offsets.len() as f64 } else { 1.0 };
+            let features = pair_features(&mut pairs, &self.features, needs_counts);
+            for (v, f) in values.iter_mut().zip(features) {
+                *v += weight * f;
+            }
+        }
+    }
+}
+
+/// the `features` of the co-occurrence matrix of `pairs`, sorts `pairs` when `needs_counts`
+fn pair_features<'a>(
+    pairs: &mut [(u8, u8)],
+    features: &'a [Haralick],
+    needs_counts: bool,
+) -> impl Iterator<Item = f64> + 'a {
+    let n = pairs.len() as f64;
+    let (mut sa, mut sb, mut saa, mut sbb, mut sab) = (0f64, 0f64, 0f64, 0f64, 0f64);
+    let (mut contrast, mut homogeneity) = (0f64, 0f64);
+    for &(a, b) in pairs.iter() {
+        let (a, b) = (a as f64, b as f64);
+        let d2 = (a - b) * (a - b);
+        contrast += d2;
+        homogeneity += 1.0 / (1.0 + d2);
+        sa += a;
+        sb += b;
+        saa += a * a;
+        sbb += b * b;
+        sab += a * b;
+    }
+    let (mut asm, mut entropy) = (0f64, 0f64);
+    if needs_counts {
+        pairs.sort_unstable();
+        for run in pairs.chunk_by(|x, y| x == y) {
+            let p = run.len() as f64 / n;
+            asm += p * p;
+            entropy -= p * p.log2();
+        }
+    }
+    let (ma, mb) = (sa / n, sb / n);
+    let (va, vb) = (saa / n - ma * ma, sbb / n - mb * mb);
+    let correlation = match va * vb {
+        // rounding can leave a constant window fractionally above zero
+        v if v <= 1e-12 => 1.0,
+        v => (sab / n - ma * mb) / v.sqrt(),
+    };
+    features.iter().map(move |f| match f {
+        Haralick::Contrast => contrast / n,
+        Haralick::Homogeneity => homogeneity / n,
+        Haralick::Energy => asm.sqrt(),
+        Haralick::Correlation => correlation,
+        Haralick::Entropy => entropy,
+    })
+}
+
+/// grey level `0..levels` of every element, `range` `(lo, hi)` is split into `levels` equal bins,
+/// values outside it go to the first or last level and NaN to the first
+///
+/// # Errors
+/// [`PyLensError::InvalidArgument`] if `levels` is not in `2..=256` or `hi` is not above `lo`,
+/// [`PyLensError::NonFinite`] for a NaN or infinite `lo` or `hi`
+pub fn quantise<T: NumConv>(
+    arr: ArrayView3<T>,
+    levels: usize,
+    (lo, hi): (f64, f64),
+) -> Result<Array3<u8>, PyLensError> {
+    if !(2..=256).contains(&levels) {
+        return Err(PyLensError::InvalidArgument {
+            name: "levels",
+            reason: format!("expected 2 to 256 grey levels, got {}", levels),
+        });
+    }
+    if check_finite("value_range", hi)? <= check_finite("value_range", lo)? {
+        return Err(PyLensError::InvalidArgument {
+            name: "value_range",
+            reason: format!("the upper value {} must be above the lower {}", hi, lo),
+        });
+    }
+    let step = (hi - lo) / levels as f64;
+    let top = (levels - 1) as f64;
+    Ok(arr.map(|x| ((x.as_f64() - lo) / step).floor().clamp(0.0, top) as u8))
+}
+
+/// the `glcm` features of every window of `win_size` in the quantised image `levels`,
+/// stacked on a new last axis, see [`thread_apply_stacked`]
+///
+/// # Errors
+/// [`PyLensError::InvalidArgument`] if an offset leaves no pair inside the window,
+/// see [`Window::validate`]
+pub fn thread_glcm(
+    levels: ArrayView3<u8>,
+    win_size: impl Into<Window>,
+    glcm: &Glcm,
+) -> Result<Array4<f64>, PyLensError> {
+    let win = win_size.into();
+    let (w0, w1, _) = win.dims();
+    for &(dy, dx) in &glcm.offsets {
+        if dy.unsigned_abs() >= w0 || dx.unsigned_abs() >= w1 {
+            return Err(PyLensError::InvalidArgument {
+                name: "offsets",
+                reason: format!(
+                    "offset ({}, {}) leaves no pair inside a window of {} by {}",
+                    dy, dx, w0, w1
+                ),
+            });
+        }
+    }
+    thread_apply_stacked(levels, win, glcm.count(), |w: ArrayView3<u8>, out: &mut [f64]| {
+        glcm.features_into(w, out)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::window::glcm::*;
+    use crate::window::WindowShape;
+    use ndarray::{s, Array2, Array3};
+
+    /// features from an explicit normalised matrix, the textbook definitions
+    fn reference(levels: &Array2<u8>, (dy, dx): (isize, isize), symmetric: bool) -> Vec<f64> {
+        let mut p = Array2::<f64>::zeros((256, 256));
+        let (h, w) = (levels.nrows() as isize, levels.ncols() as isize);
+        for i in 0..h {
+            for j in 0..w {
+                let (y, x) = (i + dy, j + dx);
+                if (0..h).contains(&y) && (0..w).contains(&x) {
+                    let (a, b) = (levels[[i as usize, j as usize]], levels[[y as usize, x as usize]]);
+                    p[[a as usize, b as usize]] += 1.0;
+                    if symmetric {
+                        p[[b as usize, a as usize]] += 1.0;
+                    }
+                }
+            }
+        }
+        p /= p.sum();
+        let idx = |f: &dyn Fn(f64, f64, f64) -> f64| {
+            p.indexed_iter().map(|((a, b), &v)| f(a as f64, b as f64, v)).sum::<f64>()
+        };
+        let (ma, mb) = (idx(&|a, _, v| a * v), idx(&|_, b, v| b * v));
+        let sa = idx(&|a, _, v| (a - ma).powi(2) * v).sqrt();
+        let sb = idx(&|_, b, v| (b - mb).powi(2) * v).sqrt();
+        vec![
+            idx(&|a, b, v| v * (a - b).powi(2)),
+            idx(&|a, b, v| v / (1.0 + (a - b).powi(2))),
+            idx(&|_, _, v| v * v).sqrt(),
+            idx(&|a, b, v| v * (a - ma) * (b - mb)) / (sa * sb),
+            -idx(&|_, _, v| if v > 0.0 { v * v.log2() } else { 0.0 }),
+        ]
+    }
+
+    #[test]
+    fn features_match_matrix() {
+        let img = Array3::from_shape_fn((12, 10, 2), |(a, b, c)| ((a * 3 + b * b + c * 5) % 23) as u16);
+        let levels = quantise(img.view(), 6, (0.0, 23.0)).unwrap();
+        assert!(levels.iter().all(|&l| l < 6));
+        let offsets = vec![(0, 1), (1, -1), (2, 0)];
+        for symmetric in [false, true] {
+            let glcm = Glcm::new(offsets.clone(), Haralick::ALL.to_vec(), symmetric, false).unwrap();
+            let found = thread_glcm(levels.view(), WindowShape::Double(5, 4), &glcm).unwrap();
+            assert_eq!([8, 7, 2, 15], found.shape());
+            for (i, j, k) in [(0, 0, 0), (3, 6, 1), (7, 2, 0)] {
+                let window = levels.slice(s![i..i + 5, j..j + 4, k]).to_owned();
+                for (o, &offset) in offsets.iter().enumerate() {
+                    let expected = reference(&window, offset, symmetric);
+                    for (f, e) in expected.iter().enumerate() {
+                        let v = found[[i, j, k, o * 5 + f]];
+                        assert!((v - e).abs() < 1e-9, "{:?} {} expected {} found {}", offset, f, e, v);
+                    }
+                }
+            }
+            // the average is the mean over the offsets
+            let mean = Glcm::new(offsets.clone(), Haralick::ALL.to_vec(), symmetric, true).unwrap();
+            let averaged = thread_glcm(levels.view(), WindowShape::Double(5, 4), &mean).unwrap();
+            for ((i, j, k, f), v) in averaged.indexed_iter() {
+                let e = (0..3).map(|o| found[[i, j, k, o * 5 + f]]).sum::<f64>() / 3.0;
+                assert!((v - e).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn constant_window() {
+        let levels = Array3::<u8>::from_elem((6, 6, 1), 3);
+        let glcm = Glcm::new(vec![(1, 1)], Haralick::ALL.to_vec(), true, true).unwrap();
+        let found = thread_glcm(levels.view(), WindowShape::Single(3), &glcm).unwrap();
+        assert_eq!(vec![0.0, 1.0, 1.0, 1.0, 0.0], found.slice(s![1, 2, 0, ..]).to_vec());
+    }
+
+    #[test]
+    fn invalid_glcm() {
+        let img = Array3::<f32>::zeros((6, 6, 1));
+        assert!(matches!(
+            quantise(img.view(), 300, (0.0, 1.0)),
+            Err(PyLensError::InvalidArgument { name: "levels", .. })
+        ));
+        assert!(matches!(
+            quantise(img.view(), 8, (1.0, 1.0)),
+            Err(PyLensError::InvalidArgument { name: "value_range", .. })
+        ));
+        assert_eq!(
+            Err(PyLensError::InvalidArgument {
+                name: "features",
+                reason: "at least one feature is needed".to_string()
+            }),
+            Glcm::new(vec![(0, 1)], vec![], false, true)
+        );
+        let glcm = Glcm::new(vec![(0, 3)], vec![Haralick::Contrast], false, true).unwrap();
+        let levels = quantise(img.view(), 8, (0.0, 1.0)).unwrap();
+        assert!(matches!(
+            thread_glcm(levels.view(), WindowShape::Single(3), &glcm),
+            Err(PyLensError::InvalidArgument { name: "offsets", .. })
+        ));
+        assert_eq!(Ok(Haralick::Entropy), "entropy".parse());
+    }
+}
@@ -0,0 +1,58 @@
+//! histograms slid along the rows of an image of bin indices, shared by [`super::entropy`] and
+//! [`super::rank`]
+//!
+//! every output row starts from an empty histogram, each step along the row only removes the
+//! columns that leave the window and adds the ones that enter it, so a step costs `w0 * w2`
+//! updates whatever the window width
+
+use ndarray::{s, ArrayView3, ArrayViewMut3, Axis};
+
+use super::Window;
+
+/// counts of the values in one window, updated one value at a time
+pub(crate) trait SlidingHistogram {
+    fn add(&mut self, bin: usize);
+    fn remove(&mut self, bin: usize);
+    /// called at the end of every row, once every value has been removed
+    fn reset(&mut self) {}
+}
+
+/// fill `out` with `value(hist)` for every window of `win` in the bin indices `bins`,
+/// `win` has no dilation and `hist` starts empty
+pub(crate) fn slide_histogram<H, O>(
+    bins: ArrayView3<u16>,
+    win: Window,
+    hist: &mut H,
+    mut out: ArrayViewMut3<O>,
+    value: impl Fn(&H) -> O,
+) where
+    H: SlidingHistogram,
+{
+    let (w0, w1, w2) = win.dims();
+    let st = win.stride;
+    let (out_rows, out_cols, out_layers) = out.dim();
+    for i in 0..out_rows {
+        for k in 0..out_layers {
+            let rows = bins.slice(s![i * st.0..i * st.0 + w0, .., k * st.2..k * st.2 + w2]);
+            let column = |c| rows.index_axis(Axis(1), c);
+            // columns `a..b` of the input are in the histogram
+            let (mut a, mut b) = (0, 0);
+            for j in 0..out_cols {
+                let (start, end) = (j * st.1, j * st.1 + w1);
+                for c in a..start.min(b) {
+                    column(c).iter().for_each(|&bin| hist.remove(bin as usize));
+                }
+                for c in b.max(start)..end {
+                    column(c).iter().for_each(|&bin| hist.add(bin as usize));
+                }
+                (a, b) = (start, end);
+                out[[i, j, k]] = value(hist);
+            }
+            // empty the histogram for the next row without touching every bin
+            for c in a..b {
+                column(c).iter().for_each(|&bin| hist.remove(bin as usize));
+            }
+            hist.reset();
+        }
+    }
+}
@@ -0,0 +1,483 @@
+//! summed-area table (integral image) versions of the windowed statistics,
+//! every window is answered from the corners of two tables so the cost per output
+//! pixel no longer depends on the window size
+//!
+//! the per window functions in [`super::window_methods`] are kept as the reference
+
+use ndarray::{Array3, ArrayView3, ArrayViewMut3, Axis, Dim, Ix3};
+use num_traits::identities::Zero;
+use std::ops::{Add, Neg, Sub};
+
+use super::window_methods::NumConv;
+use super::{map_tiles, Window};
+use crate::error::PyLensError;
+
+/// a double-double number, `hi + lo` with `lo` at most half an ulp of `hi`, about 106 bits
+///
+/// integers up to 2^106 add exactly, so the tables of integer inputs hold exact sums and a flat
+/// window next to a wide spread still has a variance of exactly 0
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+struct Compensated {
+    hi: f64,
+    lo: f64,
+}
+
+impl Compensated {
+    fn new(x: f64) -> Self {
+        Compensated { hi: x, lo: 0.0 }
+    }
+
+    /// `a + b` for `|a| >= |b|`, exact
+    fn quick_two_sum(a: f64, b: f64) -> Self {
+        let hi = a + b;
+        Compensated { hi, lo: b - (hi - a) }
+    }
+
+    /// `self * x`, the product of the high part is exact
+    fn mul(self, x: f64) -> Self {
+        let p = self.hi * x;
+        Compensated::quick_two_sum(p, self.hi.mul_add(x, -p) + self.lo * x)
+    }
+
+    fn square(self) -> Self {
+        let p = self.hi * self.hi;
+        let e = self.hi.mul_add(self.hi, -p) + 2.0 * self.hi * self.lo;
+        Compensated::quick_two_sum(p, e)
+    }
+
+    fn value(self) -> f64 {
+        self.hi + self.lo
+    }
+}
+
+impl Add for Compensated {
+    type Output = Self;
+
+    /// two-sum of the high parts, then the low parts folded into the error
+    fn add(self, other: Self) -> Self {
+        let hi = self.hi + other.hi;
+        let b = hi - self.hi;
+        let err = (self.hi - (hi - b)) + (other.hi - b);
+        Compensated::quick_two_sum(hi, err + self.lo + other.lo)
+    }
+}
+
+impl Neg for Compensated {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Compensated { hi: -self.hi, lo: -self.lo }
+    }
+}
+
+impl Sub for Compensated {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+/// summed volume tables for the values and the squared values of a block of the input
+///
+/// both tables are larger than the block by `lag` on every axis, `sum[[i, j, k]]` holds the sum
+/// of every value `lag` apart before `[i, j, k] - lag` so the first `lag` rows, columns and layers
+/// are zero. a lag of 1 is the usual summed area table, a larger lag sums the elements of a
+/// dilated window, see [`Window`]
+///
+/// values are shifted by the block mean before they are accumulated, the tables then hold
+/// deviations rather than raw values, which keeps the sum of squares small enough that taking
+/// the difference of two corners doesn't lose the precision of the window, this matters for
+/// large values with a small spread, e.g. u32 data near `u32::MAX`. the sums are [`Compensated`],
+/// a single f64 would lose the variance of a flat window when the rest of the block spreads widely
+struct IntegralImage {
+    sum: Array3<Compensated>,
+    sum_sq: Array3<Compensated>,
+    /// count of the NaN and infinite values left out of the sums, when the block has any
+    non_finite: Option<Array3<i64>>,
+    lag: (usize, usize, usize),
+    /// value subtracted from every element before it was summed
+    shift: f64,
+}
+
+impl IntegralImage {
+    fn new<T: NumConv>(arr: ArrayView3<T>, lag: (usize, usize, usize)) -> Self {
+        let sh = arr.shape();
+        let (total, count) = arr
+            .iter()
+            .map(|x| x.as_f64())
+            .filter(|x| x.is_finite())
+            .fold((0.0, 0usize), |(total, count), x| (total + x, count + 1));
+        // rounded so integer inputs stay integers, they are then summed exactly
+        let shift = (total / count.max(1) as f64).round();
+
+        let table_shape = (sh[0] + lag.0, sh[1] + lag.1, sh[2] + lag.2);
+        let mut sum = Array3::<Compensated>::default(table_shape);
+        let mut sum_sq = Array3::<Compensated>::default(table_shape);
+        let mut non_finite = (count < arr.len()).then(|| Array3::<i64>::zeros(table_shape));
+        for ((i, j, k), x) in arr.indexed_iter() {
+            let at = [i + lag.0, j + lag.1, k + lag.2];
+            match x.as_f64() {
+                x if x.is_finite() => {
+                    let v = Compensated::new(x - shift);
+                    sum[at] = v;
+                    sum_sq[at] = v.square();
+                }
+                _ => non_finite.as_mut().expect("counted as non finite")[at] = 1,
+            }
+        }
+        running_sums(&mut sum, lag);
+        running_sums(&mut sum_sq, lag);
+        if let Some(table) = non_finite.as_mut() {
+            running_sums(table, lag);
+        }
+        IntegralImage { sum, sum_sq, non_finite, lag, shift }
+    }
+
+    /// sum and sum of squares of the window of `d` elements, `lag` apart, starting at `[i, j, k]`,
+    /// `None` if any of them is NaN or infinite
+    fn window(
+        &self,
+        (i, j, k): (usize, usize, usize),
+        d: Ix3,
+    ) -> Option<(Compensated, Compensated)> {
+        let far = (
+            i + d[0] * self.lag.0,
+            j + d[1] * self.lag.1,
+            k + d[2] * self.lag.2,
+        );
+        if let Some(table) = &self.non_finite {
+            if corners(table, (i, j, k), far) > 0 {
+                return None;
+            }
+        }
+        Some((corners(&self.sum, (i, j, k), far), corners(&self.sum_sq, (i, j, k), far)))
+    }
+}
+
+/// summed area table of `table` in place, one axis at a time, only ever adds two values `lag`
+/// apart
+fn running_sums<V: Copy + Add<Output = V>>(table: &mut Array3<V>, lag: (usize, usize, usize)) {
+    for (axis, step) in [lag.0, lag.1, lag.2].into_iter().enumerate() {
+        for mut lane in table.lanes_mut(Axis(axis)) {
+            for q in step..lane.len() {
+                lane[q] = lane[q] + lane[q - step];
+            }
+        }
+    }
+}
+
+/// sum of the block of a summed area table from `[i, j, k]` up to `[a, b, c]`
+fn corners<V>(
+    t: &Array3<V>,
+    (i, j, k): (usize, usize, usize),
+    (a, b, c): (usize, usize, usize),
+) -> V
+where
+    V: Copy + Add<Output = V> + Sub<Output = V>,
+{
+    t[[a, b, c]] - t[[i, b, c]] - t[[a, j, c]] - t[[a, b, k]]
+        + t[[i, j, c]]
+        + t[[i, b, k]]
+        + t[[a, j, k]]
+        - t[[i, j, k]]
+}
+
+/// fill `out` with `stat(n, mean, m2)` for every window of `win` in `arr`
+///
+/// `n` is the number of values in the window, `mean` their mean and `m2` the sum of squared
+/// deviations from the window mean, `m2 / n` is the population variance. both are NaN for the
+/// windows with a NaN or infinite value, like the per window reducers, other windows are unaffected
+fn moments_into<T, O, F>(arr: ArrayView3<T>, win: Window, mut out: ArrayViewMut3<O>, stat: &F)
+where
+    T: NumConv,
+    F: Fn(f64, f64, f64) -> O,
+{
+    let table = IntegralImage::new(arr, win.dilation);
+    let w = win.dims();
+    let d = Dim([w.0, w.1, w.2]);
+    let n = (w.0 * w.1 * w.2) as f64;
+    let st = win.stride;
+    for ((i, j, k), o) in out.indexed_iter_mut() {
+        let Some((s, q)) = table.window((i * st.0, j * st.1, k * st.2), d) else {
+            *o = stat(n, f64::NAN, f64::NAN);
+            continue;
+        };
+        // n * m2 = n * q - s^2, exact for integer inputs, rounding can take a flat window of
+        // floats fractionally below zero
+        let m2 = match (q.mul(n) - s.square()).value() / n {
+            m2 if m2 < 0.0 => 0.0,
+            m2 => m2,
+        };
+        *o = stat(n, s.value() / n + table.shift, m2);
+    }
+}
+
+/// apply `stat(n, mean, m2)` to every window using summed area tables, see [`moments_into`]
+///
+/// # Errors
+/// see [`Window::validate`]
+pub(crate) fn thread_integral_moments<T, O, F>(
+    input_array: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    stat: F,
+) -> Result<Array3<O>, PyLensError>
+where
+    T: NumConv + Sync,
+    O: Zero + Clone + Send + Sync,
+    F: Fn(f64, f64, f64) -> O + Send + Sync,
+{
+    let win = win_size.into();
+    map_tiles(input_array, win, |src, out| moments_into(src, win, out, &stat))
+}
+
+/// windowed standard deviation, O(1) per output pixel, multiplied by `scale` and converted to `O`
+/// with [`NumConv::from_f64`], same result as [`super::window_methods::stdev_ddof_0`] /
+/// [`super::window_methods::stdev_ddof_1`] up to floating point rounding for a scale of 1
+///
+/// # Errors
+/// see [`Window::validate`]
+pub fn thread_integral_stdev<T, O>(
+    input_array: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    ddof: f64,
+    scale: f64,
+) -> Result<Array3<O>, PyLensError>
+where
+    T: NumConv + Sync,
+    O: Zero + NumConv + Clone + Send + Sync,
+{
+    thread_integral_moments(input_array, win_size, |n, _, m2| {
+        O::from_f64(scale * (m2 / (n - ddof)).sqrt())
+    })
+}
+
+/// windowed rms of the deviations from the window mean, O(1) per output pixel, multiplied by
+/// `scale` and converted to `O` with [`NumConv::from_f64`], same result as
+/// [`super::window_methods::faster_rms_u64_adding`] up to floating point rounding for a scale of 1
+///
+/// # Errors
+/// see [`Window::validate`]
+pub fn thread_integral_rms<T, O>(
+    input_array: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    scale: f64,
+) -> Result<Array3<O>, PyLensError>
+where
+    T: NumConv + Sync,
+    O: Zero + NumConv + Clone + Send + Sync,
+{
+    thread_integral_moments(input_array, win_size, |n, _, m2| {
+        O::from_f64(scale * (m2 / n).sqrt())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::window::integral::*;
+    use crate::window::WindowShape;
+    use ndarray::{s, Array3, ArrayView3};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// reference stdev for every window, straight from ndarray
+    fn reference_stdev<T: NumConv + Clone>(
+        arr: ArrayView3<T>,
+        d: (usize, usize, usize),
+        ddof: f64,
+    ) -> Array3<f64> {
+        let w = arr.mapv(|x| x.as_f64());
+        let mut out = Array3::zeros((
+            arr.shape()[0] - d.0 + 1,
+            arr.shape()[1] - d.1 + 1,
+            arr.shape()[2] - d.2 + 1,
+        ));
+        for (o, win) in out.iter_mut().zip(w.windows(d)) {
+            *o = win.std(ddof);
+        }
+        out
+    }
+
+    fn assert_close(expected: &Array3<f64>, found: &Array3<f64>, tol: f64) {
+        assert_eq!(expected.shape(), found.shape());
+        for (e, f) in expected.iter().zip(found) {
+            assert!(
+                (e - f).abs() <= tol * e.abs().max(1.0),
+                "expected {} found {}",
+                e,
+                f
+            );
+        }
+    }
+
+    #[test]
+    fn integral_stdev_matches_reference() {
+        let mut rng = StdRng::seed_from_u64(26);
+        let arr: Array3<u8> = Array3::from_shape_fn((37, 41, 3), |_| rng.gen());
+        for win in [
+            WindowShape::Single(1),
+            WindowShape::Single(5),
+            WindowShape::Double(3, 8),
+            WindowShape::Triple(4, 2, 3),
+            WindowShape::Triple(37, 41, 3),
+        ] {
+            for ddof in [0.0, 1.0] {
+                let found =
+                    thread_integral_moments(arr.view(), win, |n, _, m2| (m2 / (n - ddof)).sqrt())
+                        .unwrap();
+                if win.dims() == (1, 1, 1) && ddof == 1.0 {
+                    assert!(found.iter().all(|f| f.is_nan()));
+                    continue;
+                }
+                let expected = reference_stdev(arr.view(), win.dims(), ddof);
+                assert_close(&expected, &found, 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn integral_stdev_strided_dilated() {
+        let mut rng = StdRng::seed_from_u64(31);
+        let arr: Array3<u16> = Array3::from_shape_fn((40, 33, 2), |_| rng.gen_range(0..1000));
+        let win = Window::from(WindowShape::Triple(3, 4, 2))
+            .with_stride((2, 3, 1))
+            .with_dilation((3, 2, 1));
+        let found =
+            thread_integral_moments(arr.view(), win, |n, _, m2| (m2 / (n - 1.0)).sqrt()).unwrap();
+        assert_eq!([17, 9, 1], found.shape());
+        let w = arr.mapv(|x| x as f64);
+        for ((i, j, k), f) in found.indexed_iter() {
+            let (i, j) = (i * 2, j * 3);
+            let expected = w.slice(s![i..i + 7;3, j..j + 7;2, k..k + 2]).std(1.0);
+            assert!((expected - f).abs() < 1e-9, "expected {} found {}", expected, f);
+        }
+    }
+
+    #[test]
+    fn integral_stdev_large_offset() {
+        // small spread on top of a huge value, naive sum of squares would lose every digit
+        let mut rng = StdRng::seed_from_u64(27);
+        let arr: Array3<u32> =
+            Array3::from_shape_fn((60, 20, 1), |_| u32::MAX - rng.gen_range(0..16));
+        let found =
+            thread_integral_moments(arr.view(), WindowShape::Single(4), |n, _, m2| (m2 / n).sqrt())
+                .unwrap();
+        // stdev doesn't change when the values are mirrored and shifted, this keeps the
+        // reference exact
+        let deviations = arr.mapv(|x| (u32::MAX - x) as u8);
+        let expected = reference_stdev(deviations.view(), (4, 4, 1), 0.0);
+        assert_close(&expected, &found, 1e-12);
+    }
+
+    #[test]
+    fn integral_stdev_flat_next_to_noise() {
+        // noise over the whole u32 range beside a flat region in the same tile, the squares of
+        // the noise would swamp a plain f64 running sum
+        let mut rng = StdRng::seed_from_u64(29);
+        let arr: Array3<u32> =
+            Array3::from_shape_fn((60, 40, 1), |(_, b, _)| if b < 20 { rng.gen() } else { 7 });
+        let float = arr.mapv(|x| x as f32);
+        let win = WindowShape::Single(3);
+        let expected = reference_stdev(arr.view(), (3, 3, 1), 0.0);
+        let found = thread_integral_moments(arr.view(), win, |n, _, m2| (m2 / n).sqrt()).unwrap();
+        assert!(found.slice(s![.., 20.., ..]).iter().all(|&f| f == 0.0));
+        assert_close(&expected, &found, 1e-9);
+        let found = thread_integral_moments(float.view(), win, |n, _, m2| (m2 / n).sqrt()).unwrap();
+        assert!(found.slice(s![.., 20.., ..]).iter().all(|&f| f == 0.0));
+        assert_close(&reference_stdev(float.view(), (3, 3, 1), 0.0), &found, 1e-9);
+    }
+
+    #[test]
+    fn integral_non_finite_windows() {
+        let mut rng = StdRng::seed_from_u64(34);
+        let mut arr: Array3<f32> = Array3::from_shape_fn((30, 20, 1), |_| rng.gen_range(0.0..9.0));
+        arr[[4, 5, 0]] = f32::NAN;
+        arr[[20, 12, 0]] = f32::INFINITY;
+        let found =
+            thread_integral_moments(arr.view(), WindowShape::Single(3), |n, _, m2| (m2 / n).sqrt())
+                .unwrap();
+        let expected = reference_stdev(arr.view(), (3, 3, 1), 0.0);
+        // only the windows over a NaN or infinite value are NaN, not their whole tile
+        assert_eq!(18, found.iter().filter(|f| f.is_nan()).count());
+        for (e, f) in expected.iter().zip(&found) {
+            assert_eq!(e.is_nan(), f.is_nan());
+            assert!(e.is_nan() || (e - f).abs() < 1e-9, "expected {} found {}", e, f);
+        }
+    }
+
+    #[test]
+    fn integral_matches_window_methods() {
+        use crate::window::thread_apply_over_window;
+        use crate::window::window_methods::{faster_rms_u64_adding, stdev_ddof_0};
+        let arr: Array3<u16> =
+            Array3::from_shape_fn((50, 50, 3), |(a, b, c)| (a * b + c * 7) as u16);
+        let win = WindowShape::Triple(5, 5, 1);
+
+        let integral: Array3<u16> = thread_integral_rms(arr.view(), win, 1.0).unwrap();
+        let reference = thread_apply_over_window(arr.view(), win, faster_rms_u64_adding).unwrap();
+        // both round, floating point error can only move a result across a half by one
+        assert!(integral
+            .iter()
+            .zip(&reference)
+            .all(|(a, b)| a.abs_diff(*b) <= 1));
+
+        let integral: Array3<u16> = thread_integral_stdev(arr.view(), win, 0.0, 1.0).unwrap();
+        let reference = thread_apply_over_window(arr.view(), win, stdev_ddof_0).unwrap();
+        assert!(integral
+            .iter()
+            .zip(&reference)
+            .all(|(a, b)| a.abs_diff(*b) <= 1));
+    }
+
+    #[test]
+    fn integral_signed_and_float_inputs() {
+        let mut rng = StdRng::seed_from_u64(34);
+        let arr: Array3<i16> = Array3::from_shape_fn((21, 17, 2), |_| rng.gen_range(-3000..3000));
+        let found: Array3<f64> = thread_integral_rms(arr.view(), WindowShape::Single(4), 1.0).unwrap();
+        assert_close(&reference_stdev(arr.view(), (4, 4, 1), 0.0), &found, 1e-9);
+
+        let arr: Array3<f32> = Array3::from_shape_fn((21, 17, 2), |_| rng.gen_range(-1.0..1.0));
+        let found: Array3<f64> =
+            thread_integral_stdev(arr.view(), WindowShape::Double(3, 5), 1.0, 1.0).unwrap();
+        assert_close(&reference_stdev(arr.view(), (3, 5, 1), 1.0), &found, 1e-9);
+    }
+
+    #[test]
+    fn integral_window_mean() {
+        let mut rng = StdRng::seed_from_u64(41);
+        let arr: Array3<u32> =
+            Array3::from_shape_fn((30, 24, 2), |_| u32::MAX - rng.gen_range(0..500));
+        let found = thread_integral_moments(arr.view(), WindowShape::Double(5, 3), |_, m, _| m)
+            .unwrap();
+        let w = arr.mapv(|x| x as f64);
+        let expected = Array3::from_shape_fn((26, 22, 2), |(i, j, k)| {
+            w.slice(s![i..i + 5, j..j + 3, k]).mean().unwrap()
+        });
+        assert_close(&expected, &found, 1e-12);
+    }
+
+    #[test]
+    fn integral_output_types() {
+        let arr: Array3<u8> = Array3::from_shape_fn((9, 9, 1), |(a, b, _)| (a * 31 + b * b) as u8);
+        let win = WindowShape::Single(3);
+        let exact: Array3<f64> = thread_integral_stdev(arr.view(), win, 1.0, 1.0).unwrap();
+        let single: Array3<f32> = thread_integral_stdev(arr.view(), win, 1.0, 1.0).unwrap();
+        let rounded: Array3<u8> = thread_integral_stdev(arr.view(), win, 1.0, 1.0).unwrap();
+        let scaled: Array3<f64> = thread_integral_stdev(arr.view(), win, 1.0, 0.5).unwrap();
+        let expected = reference_stdev(arr.view(), (3, 3, 1), 1.0);
+        assert_close(&expected, &exact, 1e-9);
+        assert_close(&expected, &single.mapv(|x| x as f64), 1e-6);
+        assert_eq!(expected.mapv(|x| x.round().min(255.0) as u8), rounded);
+        assert_close(&expected.mapv(|x| x * 0.5), &scaled, 1e-9);
+    }
+
+    #[test]
+    fn integral_invalid_window() {
+        let arr: Array3<u8> = Array3::zeros((10, 10, 3));
+        let out = thread_integral_stdev::<_, f64>(arr.view(), WindowShape::Single(11), 0.0, 1.0);
+        assert!(matches!(
+            out,
+            Err(PyLensError::WindowTooLarge { axis: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,431 @@
+//! weighted windows, a [`Kernel`] gives every element of the window its own weight
+//!
+//! the weighted statistics and the correlation are [`Reducer`]s so they run on the same row tiles
+//! and thread pool as the unweighted ones, see [`super::thread_apply_over_window`]. kernels that
+//! are the product of one weight per axis, such as boxes, gaussians and the sobel kernels, can
+//! also be applied one axis at a time with [`thread_separable`]
+
+use ndarray::{s, Array3, ArrayView3, Axis};
+use std::str::FromStr;
+
+use super::window_methods::{NumConv, Reducer};
+use super::{map_tiles, Window, WindowShape};
+use crate::error::{check_finite, PyLensError};
+
+/// weights of a window, one per element, the window has the shape of the weights
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kernel {
+    weights: Array3<f64>,
+    /// one weight per element of each axis whose product is `weights`, for separable kernels
+    factors: Option<Factors>,
+}
+
+/// the weights along each axis of a separable kernel, see [`Kernel::factors`]
+pub type Factors = [Vec<f64>; 3];
+
+impl Kernel {
+    /// # Errors
+    /// [`PyLensError::EmptyWindow`] if any axis of `weights` is empty,
+    /// [`PyLensError::NonFinite`] if a weight is NaN or infinite
+    pub fn new(weights: Array3<f64>) -> Result<Self, PyLensError> {
+        if let Some(axis) = weights.shape().iter().position(|&n| n == 0) {
+            return Err(PyLensError::EmptyWindow { axis });
+        }
+        for &w in weights.iter() {
+            check_finite("kernel", w)?;
+        }
+        let factors = rank_one_factors(weights.view());
+        Ok(Kernel { weights, factors })
+    }
+
+    /// the kernel `a[i] * b[j] * c[k]`
+    ///
+    /// # Errors
+    /// see [`Kernel::new`]
+    pub fn from_factors(factors: Factors) -> Result<Self, PyLensError> {
+        let [a, b, c] = &factors;
+        let shape = (a.len(), b.len(), c.len());
+        let weights = Array3::from_shape_fn(shape, |(i, j, k)| a[i] * b[j] * c[k]);
+        let kernel = Kernel::new(weights)?;
+        Ok(Kernel {
+            factors: Some(factors),
+            ..kernel
+        })
+    }
+
+    /// gaussian weights summing to 1, like `scipy.ndimage.gaussian_filter` the kernel reaches
+    /// `truncate` standard deviations either side of the centre, a sigma of 0 gives that axis
+    /// a size of 1
+    ///
+    /// # Errors
+    /// [`PyLensError::NonFinite`] or [`PyLensError::InvalidArgument`] if a sigma is negative or
+    /// `truncate` is not above 0
+    pub fn gaussian(sigma: (f64, f64, f64), truncate: f64) -> Result<Self, PyLensError> {
+        if check_finite("truncate", truncate)? <= 0.0 {
+            return Err(PyLensError::InvalidArgument {
+                name: "truncate",
+                reason: format!("must be above 0, got {}", truncate),
+            });
+        }
+        let axes = [sigma.0, sigma.1, sigma.2].map(|sd| -> Result<Vec<f64>, PyLensError> {
+            if check_finite("sigma", sd)? < 0.0 {
+                return Err(PyLensError::InvalidArgument {
+                    name: "sigma",
+                    reason: format!("must be at least 0, got {}", sd),
+                });
+            }
+            let radius = (truncate * sd + 0.5) as isize;
+            Ok((-radius..=radius)
+                .map(|x| match sd == 0.0 {
+                    true => 1.0,
+                    false => (-0.5 * (x as f64 / sd).powi(2)).exp(),
+                })
+                .collect())
+        });
+        let [a, b, c] = axes;
+        // each axis sums to 1 so their product does too
+        let normalised = |v: Vec<f64>| {
+            let total: f64 = v.iter().sum();
+            v.into_iter().map(|x| x / total).collect()
+        };
+        Kernel::from_factors([normalised(a?), normalised(b?), normalised(c?)])
+    }
+
+    /// window covered by the kernel
+    pub fn shape(&self) -> WindowShape {
+        let sh = self.weights.shape();
+        WindowShape::Triple(sh[0], sh[1], sh[2])
+    }
+
+    pub fn weights(&self) -> ArrayView3<'_, f64> {
+        self.weights.view()
+    }
+
+    /// weights along each axis for kernels that are the product of one weight per axis, found
+    /// for any kernel of [`Kernel::new`] that is one to within rounding
+    pub fn factors(&self) -> Option<&Factors> {
+        self.factors.as_ref()
+    }
+
+    /// the kernel reversed on every axis, correlating with it is a convolution with `self`
+    pub fn flipped(&self) -> Kernel {
+        let reversed = |v: &Vec<f64>| v.iter().rev().cloned().collect();
+        Kernel {
+            weights: self.weights.slice(s![..;-1, ..;-1, ..;-1]).to_owned(),
+            factors: self.factors.as_ref().map(|[a, b, c]| [reversed(a), reversed(b), reversed(c)]),
+        }
+    }
+}
+
+/// `weights` as the product of one vector per axis if it is one, each weight to within `1e-12` of
+/// the largest
+///
+/// a rank one tensor is fixed by the lines through its largest element, dividing them by that
+/// element once gives the factors
+fn rank_one_factors(weights: ArrayView3<f64>) -> Option<Factors> {
+    let (pivot, &largest) = weights
+        .indexed_iter()
+        .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+        .expect("kernels are never empty");
+    if largest == 0.0 {
+        return None;
+    }
+    let (i, j, k) = pivot;
+    let a: Vec<f64> = weights.slice(s![.., j, k]).to_vec();
+    let b: Vec<f64> = weights.slice(s![i, .., k]).iter().map(|x| x / largest).collect();
+    let c: Vec<f64> = weights.slice(s![i, j, ..]).iter().map(|x| x / largest).collect();
+    let tolerance = 1e-12 * largest.abs();
+    let separable = weights
+        .indexed_iter()
+        .all(|((x, y, z), w)| (w - a[x] * b[y] * c[z]).abs() <= tolerance);
+    separable.then_some([a, b, c])
+}
+
+/// correlation with the separable kernel `factors`, see [`Kernel::factors`], one axis at a time
+///
+/// every row tile is summed along the first axis, then the second, then the third, each pass
+/// into a buffer shared by the tile, which costs `w0 + w1 + w2` instead of `w0 * w1 * w2` per
+/// element. the window of `win_size` must have the shape of the factors, its stride and dilation
+/// are honoured like in [`super::thread_apply_over_window`]
+///
+/// # Errors
+/// see [`Window::validate`] and [`crate::pool::set_num_threads`]
+pub fn thread_separable<T>(
+    input_array: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    factors: &Factors,
+) -> Result<Array3<f64>, PyLensError>
+where
+    T: NumConv + Sync,
+{
+    let win = win_size.into();
+    let (stride, dilation) = (win.stride, win.dilation);
+    map_tiles(input_array, win, |src, mut out| {
+        let (rows, cols, depth) = out.dim();
+        let sh = src.shape();
+        let mut along_0 = Array3::<f64>::zeros((rows, sh[1], sh[2]));
+        for (r, mut row) in along_0.outer_iter_mut().enumerate() {
+            for (i, &f) in factors[0].iter().enumerate() {
+                let src_row = src.index_axis(Axis(0), r * stride.0 + i * dilation.0);
+                row.zip_mut_with(&src_row, |acc, x| *acc += f * x.as_f64());
+            }
+        }
+        let mut along_1 = Array3::<f64>::zeros((rows, cols, sh[2]));
+        for c in 0..cols {
+            let mut dst = along_1.index_axis_mut(Axis(1), c);
+            for (j, &f) in factors[1].iter().enumerate() {
+                dst.scaled_add(f, &along_0.index_axis(Axis(1), c * stride.1 + j * dilation.1));
+            }
+        }
+        for l in 0..depth {
+            let mut dst = out.index_axis_mut(Axis(2), l);
+            for (k, &f) in factors[2].iter().enumerate() {
+                dst.scaled_add(f, &along_1.index_axis(Axis(2), l * stride.2 + k * dilation.2));
+            }
+        }
+    })
+}
+
+/// statistics with a weighted version, see [`Weighted`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WeightedStat {
+    Mean,
+    Variance,
+    Stdev,
+    /// the weighted standard deviation with a ddof of 0, like `windowed_rms`
+    Rms,
+}
+
+impl FromStr for WeightedStat {
+    type Err = PyLensError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mean" => Ok(WeightedStat::Mean),
+            "variance" => Ok(WeightedStat::Variance),
+            "stdev" => Ok(WeightedStat::Stdev),
+            "rms" => Ok(WeightedStat::Rms),
+            _ => Err(PyLensError::InvalidArgument {
+                name: "stat",
+                reason: format!(
+                    "expected one of 'mean', 'variance', 'stdev' or 'rms', got {:?}",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+/// weighted mean, variance, stdev or rms of each window, the window must have the shape of the
+/// kernel
+///
+/// the variance with `ddof` is `sum(w * d^2) / (V1 - ddof * V2 / V1)` with `V1 = sum(w)` and
+/// `V2 = sum(w^2)`, the usual correction for reliability weights, which is `n - ddof` for equal
+/// weights
+#[derive(Copy, Clone, Debug)]
+pub struct Weighted<'a> {
+    kernel: &'a Kernel,
+    stat: WeightedStat,
+    ddof: f64,
+}
+
+impl<'a> Weighted<'a> {
+    /// # Errors
+    /// [`PyLensError::InvalidArgument`] if a weight is negative or the weights sum to 0
+    pub fn new(kernel: &'a Kernel, stat: WeightedStat, ddof: f64) -> Result<Self, PyLensError> {
+        if kernel.weights.iter().any(|&w| w < 0.0) || kernel.weights.sum() <= 0.0 {
+            return Err(PyLensError::InvalidArgument {
+                name: "kernel",
+                reason: "weights for a statistic must be at least 0 with a sum above 0".to_string(),
+            });
+        }
+        Ok(Weighted { kernel, stat, ddof })
+    }
+}
+
+impl<T: NumConv> Reducer<T, f64> for Weighted<'_> {
+    fn reduce(&self, w: ArrayView3<T>) -> f64 {
+        let weights = &self.kernel.weights;
+        let (mut v1, mut v2, mut sum) = (0f64, 0f64, 0f64);
+        for (k, x) in weights.iter().zip(w.iter()) {
+            v1 += k;
+            v2 += k * k;
+            sum += k * x.as_f64();
+        }
+        let mean = sum / v1;
+        if self.stat == WeightedStat::Mean {
+            return mean;
+        }
+        let m2: f64 = weights
+            .iter()
+            .zip(w.iter())
+            .map(|(k, x)| k * (x.as_f64() - mean).powi(2))
+            .sum();
+        let variance = |ddof: f64| m2 / (v1 - ddof * v2 / v1);
+        match self.stat {
+            WeightedStat::Mean => mean,
+            WeightedStat::Variance => variance(self.ddof),
+            WeightedStat::Stdev => variance(self.ddof).sqrt(),
+            WeightedStat::Rms => variance(0.0).sqrt(),
+        }
+    }
+}
+
+/// sum of every element of the window times its weight, `scipy.ndimage.correlate`, use a
+/// [`Kernel::flipped`] kernel for a convolution
+#[derive(Copy, Clone, Debug)]
+pub struct Correlate<'a> {
+    pub kernel: &'a Kernel,
+}
+
+impl<T: NumConv> Reducer<T, f64> for Correlate<'_> {
+    fn reduce(&self, w: ArrayView3<T>) -> f64 {
+        self.kernel
+            .weights
+            .iter()
+            .zip(w.iter())
+            .map(|(k, x)| k * x.as_f64())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::window::kernel::*;
+    use crate::window::window_methods::{mean, variance};
+    use crate::window::{thread_apply_over_window, Window};
+    use ndarray::{s, Array3};
+
+    fn sobel() -> Array3<f64> {
+        let weights = vec![1.0, 0.0, -1.0, 2.0, 0.0, -2.0, 1.0, 0.0, -1.0];
+        Array3::from_shape_vec((3, 3, 1), weights).unwrap()
+    }
+
+    fn test_image() -> Array3<u16> {
+        Array3::from_shape_fn((40, 30, 2), |(a, b, c)| ((a * 7) ^ (b * 3) ^ c) as u16)
+    }
+
+    #[test]
+    fn equal_weights_match_unweighted() {
+        let img = test_image();
+        let kernel = Kernel::new(Array3::from_elem((3, 5, 1), 2.0)).unwrap();
+        let win = Window::from(kernel.shape());
+        for (stat, ddof) in [
+            (WeightedStat::Mean, 0.0),
+            (WeightedStat::Variance, 1.0),
+            (WeightedStat::Stdev, 0.0),
+            (WeightedStat::Rms, 0.0),
+        ] {
+            let weighted = Weighted::new(&kernel, stat, ddof).unwrap();
+            let found = thread_apply_over_window(img.view(), win, weighted).unwrap();
+            let expected = thread_apply_over_window(img.view(), win, |w: ArrayView3<u16>| match stat {
+                WeightedStat::Mean => mean(w),
+                WeightedStat::Variance => variance(w, ddof),
+                _ => variance(w, ddof).sqrt(),
+            })
+            .unwrap();
+            for (a, b) in expected.iter().zip(found.iter()) {
+                assert!((a - b).abs() < 1e-9, "{:?}: {} != {}", stat, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn gaussian_kernel() {
+        let kernel = Kernel::gaussian((1.0, 0.5, 0.0), 4.0).unwrap();
+        // radius int(4 * sigma + 0.5)
+        assert_eq!(WindowShape::Triple(9, 5, 1), kernel.shape());
+        let w = kernel.weights();
+        assert!((w.sum() - 1.0).abs() < 1e-12);
+        assert_eq!(w, kernel.flipped().weights());
+        let centre = w[[4, 2, 0]];
+        assert!(w.iter().all(|&x| x <= centre));
+        // one sigma from the centre along the first axis
+        assert!((w[[5, 2, 0]] / centre - (-0.5f64).exp()).abs() < 1e-12);
+
+        assert!(matches!(
+            Kernel::gaussian((-1.0, 1.0, 0.0), 4.0),
+            Err(PyLensError::InvalidArgument { name: "sigma", .. })
+        ));
+        assert!(matches!(
+            Kernel::gaussian((1.0, 1.0, 0.0), 0.0),
+            Err(PyLensError::InvalidArgument { name: "truncate", .. })
+        ));
+    }
+
+    #[test]
+    fn correlate_and_convolve() {
+        let img = test_image();
+        // a single 1 off centre picks the element it sits on
+        let mut weights = Array3::zeros((3, 3, 1));
+        weights[[0, 2, 0]] = 1.0;
+        let kernel = Kernel::new(weights).unwrap();
+        let win = Window::from(kernel.shape());
+        let correlated = thread_apply_over_window(img.view(), win, Correlate { kernel: &kernel }).unwrap();
+        assert_eq!(img.slice(s![..38, 2.., ..]).mapv(f64::from), correlated);
+        let flipped = kernel.flipped();
+        let convolved = thread_apply_over_window(img.view(), win, Correlate { kernel: &flipped }).unwrap();
+        assert_eq!(img.slice(s![2.., ..28, ..]).mapv(f64::from), convolved);
+    }
+
+    #[test]
+    fn invalid_kernels() {
+        assert!(matches!(
+            Kernel::new(Array3::zeros((3, 0, 1))),
+            Err(PyLensError::EmptyWindow { axis: 1 })
+        ));
+        assert!(matches!(
+            Kernel::new(Array3::from_elem((1, 1, 1), f64::NAN)),
+            Err(PyLensError::NonFinite { name: "kernel", .. })
+        ));
+        let kernel = Kernel::new(Array3::from_shape_vec((1, 2, 1), vec![1.0, -1.0]).unwrap()).unwrap();
+        assert!(matches!(
+            Weighted::new(&kernel, WeightedStat::Mean, 0.0),
+            Err(PyLensError::InvalidArgument { name: "kernel", .. })
+        ));
+        assert!(matches!(
+            "median".parse::<WeightedStat>(),
+            Err(PyLensError::InvalidArgument { name: "stat", .. })
+        ));
+    }
+
+    #[test]
+    fn separable_matches_direct() {
+        let img = test_image();
+        let kernels = [
+            Kernel::gaussian((2.0, 1.0, 0.0), 3.0).unwrap(),
+            Kernel::new(sobel()).unwrap(),
+            Kernel::new(Array3::from_elem((4, 5, 2), 1.0)).unwrap(),
+            Kernel::from_factors([vec![1.0, -2.0], vec![0.5, 3.0, 1.0], vec![2.0]]).unwrap(),
+        ];
+        for kernel in kernels {
+            for kernel in [kernel.flipped(), kernel] {
+                let factors = kernel.factors().expect("separable");
+                for win in [
+                    Window::from(kernel.shape()),
+                    Window::from(kernel.shape()).with_stride((2, 3, 1)).with_dilation((2, 1, 1)),
+                ] {
+                    let reducer = Correlate { kernel: &kernel };
+                    let direct = thread_apply_over_window(img.view(), win, reducer).unwrap();
+                    let separable = thread_separable(img.view(), win, factors).unwrap();
+                    assert_eq!(direct.shape(), separable.shape());
+                    for (a, b) in direct.iter().zip(separable.iter()) {
+                        assert!((a - b).abs() < 1e-9, "{:?}: {} != {}", win, a, b);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn non_separable_kernels() {
+        let laplace = vec![0.0, 1.0, 0.0, 1.0, -4.0, 1.0, 0.0, 1.0, 0.0];
+        let laplace = Array3::from_shape_vec((3, 3, 1), laplace).unwrap();
+        assert_eq!(None, Kernel::new(laplace).unwrap().factors());
+        assert_eq!(None, Kernel::new(Array3::zeros((2, 2, 1))).unwrap().factors());
+        let sobel = Kernel::new(sobel()).unwrap();
+        let [a, b, c] = sobel.factors().unwrap();
+        assert_eq!((&vec![-1.0, -2.0, -1.0], &vec![-1.0, 0.0, 1.0], &vec![1.0]), (a, b, c));
+    }
+}
@@ -0,0 +1,323 @@
+//! grayscale morphology, erosion and dilation are the minimum and maximum of the window centred
+//! on every pixel and the other operations combine them
+//!
+//! box structuring elements run one axis at a time with the van herk / gil-werman algorithm,
+//! about 3 comparisons per element whatever the size of the box, other elements take the minimum
+//! or maximum of the values of every window under a [`Footprint`]
+
+use ndarray::{Array3, ArrayView1, ArrayView3, ArrayViewMut1, ArrayViewMut3, Axis, Zip};
+use num_traits::Zero;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use super::footprint::{Footprint, Masked};
+use super::padding::{pad_for_mode, OutputMode, PadMode};
+use super::window_methods::{cmp, max, min, NumConv};
+use super::{map_tiles, thread_apply_over_window, Window, WindowShape};
+use crate::error::PyLensError;
+
+/// the morphological operations, see [`thread_morphology`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Morphology {
+    /// minimum of the structuring element
+    Erosion,
+    /// maximum of the reflected structuring element
+    Dilation,
+    /// dilation of the erosion, removes bright details smaller than the element
+    Opening,
+    /// erosion of the dilation, fills dark details smaller than the element
+    Closing,
+    /// the image minus its opening, the bright details the opening removes
+    TopHat,
+    /// the closing minus the image, the dark details the closing fills
+    BlackHat,
+    /// dilation minus erosion, the local contrast around edges
+    Gradient,
+}
+
+impl FromStr for Morphology {
+    type Err = PyLensError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "erosion" => Morphology::Erosion,
+            "dilation" => Morphology::Dilation,
+            "opening" => Morphology::Opening,
+            "closing" => Morphology::Closing,
+            "tophat" => Morphology::TopHat,
+            "blackhat" => Morphology::BlackHat,
+            "gradient" => Morphology::Gradient,
+            _ => {
+                return Err(PyLensError::InvalidArgument {
+                    name: "operation",
+                    reason: format!(
+                        "expected 'erosion', 'dilation', 'opening', 'closing', 'tophat', \
+                         'blackhat' or 'gradient', got {:?}",
+                        s
+                    ),
+                })
+            }
+        })
+    }
+}
+
+/// the structuring element, centred on every pixel so every size must be odd
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Structure<'a> {
+    /// every element of a window of this shape
+    Box(WindowShape),
+    /// the elements of a footprint, such as [`Footprint::disk`] or [`Footprint::cross`]
+    Footprint(&'a Footprint),
+}
+
+impl Structure<'_> {
+    /// # Errors
+    /// [`PyLensError::InvalidArgument`] if any size is even
+    fn window(self) -> Result<Window, PyLensError> {
+        let (shape, name) = match self {
+            Structure::Box(shape) => (shape, "size"),
+            Structure::Footprint(footprint) => (footprint.shape(), "footprint"),
+        };
+        let (a, b, c) = shape.dims();
+        if [a, b, c].iter().any(|n| n % 2 == 0) {
+            return Err(PyLensError::InvalidArgument {
+                name,
+                reason: format!("every size must be odd to have a centre, got {}", shape),
+            });
+        }
+        Ok(shape.into())
+    }
+}
+
+/// which end of the window order, see [`cmp`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Extremum {
+    Min,
+    Max,
+}
+
+impl Extremum {
+    fn pick<T: NumConv + Clone>(self, a: &T, b: &T) -> T {
+        match (self, cmp(b, a)) {
+            (Extremum::Min, Ordering::Less) | (Extremum::Max, Ordering::Greater) => b.clone(),
+            _ => a.clone(),
+        }
+    }
+}
+
+/// extremum of every `w` consecutive values of `src` into `out`
+///
+/// `src` is cut into blocks of `w`, a window covers the end of one block and the start of the
+/// next, so it is the extremum of a running suffix of the first and a running prefix of the second
+fn van_herk<T: NumConv + Clone>(
+    src: ArrayView1<T>,
+    w: usize,
+    ext: Extremum,
+    (prefix, suffix): (&mut Vec<T>, &mut Vec<T>),
+    mut out: ArrayViewMut1<T>,
+) {
+    let n = out.len() + w - 1;
+    prefix.clear();
+    for (i, x) in src.iter().take(n).enumerate() {
+        let p = match i % w {
+            0 => x.clone(),
+            _ => ext.pick(&prefix[i - 1], x),
+        };
+        prefix.push(p);
+    }
+    suffix.clear();
+    suffix.extend(src.iter().take(n).cloned());
+    for i in (0..n - 1).rev() {
+        if (i + 1) % w != 0 {
+            suffix[i] = ext.pick(&suffix[i], &suffix[i + 1]);
+        }
+    }
+    for (i, o) in out.iter_mut().enumerate() {
+        *o = ext.pick(&suffix[i], &prefix[i + w - 1]);
+    }
+}
+
+/// [`van_herk`] along every lane of `axis`
+fn van_herk_axis<T: NumConv + Clone>(
+    src: ArrayView3<T>,
+    axis: Axis,
+    w: usize,
+    ext: Extremum,
+    mut out: ArrayViewMut3<T>,
+) {
+    let (mut prefix, mut suffix) = (Vec::new(), Vec::new());
+    Zip::from(src.lanes(axis))
+        .and(out.lanes_mut(axis))
+        .for_each(|lane, out| van_herk(lane, w, ext, (&mut prefix, &mut suffix), out));
+}
+
+/// extremum of every dense box window of `win`, one axis at a time
+fn thread_box_extremum<T>(
+    input_array: ArrayView3<T>,
+    win: Window,
+    ext: Extremum,
+) -> Result<Array3<T>, PyLensError>
+where
+    T: NumConv + Zero + Clone + Send + Sync,
+{
+    let (w0, w1, w2) = win.dims();
+    map_tiles(input_array, win, |src, out| {
+        let (rows, cols, _) = out.dim();
+        let sh = src.shape();
+        let mut along_0 = Array3::<T>::zeros((rows, sh[1], sh[2]));
+        van_herk_axis(src, Axis(0), w0, ext, along_0.view_mut());
+        let mut along_1 = Array3::<T>::zeros((rows, cols, sh[2]));
+        van_herk_axis(along_0.view(), Axis(1), w1, ext, along_1.view_mut());
+        van_herk_axis(along_1.view(), Axis(2), w2, ext, out);
+    })
+}
+
+/// erosion or dilation of `input_array` by `structure`, with the shape of the input
+fn extremum<T>(
+    input_array: ArrayView3<T>,
+    structure: Structure,
+    pad_mode: PadMode,
+    ext: Extremum,
+) -> Result<Array3<T>, PyLensError>
+where
+    T: NumConv + Zero + Clone + Send + Sync,
+{
+    let win = structure.window()?;
+    let padded = pad_for_mode(input_array, win, OutputMode::Same, pad_mode)?;
+    let padded = padded.view();
+    match (structure, ext) {
+        (Structure::Box(_), _) => thread_box_extremum(padded, win, ext),
+        (Structure::Footprint(footprint), Extremum::Min) => {
+            thread_apply_over_window(padded, win, Masked { footprint, reducer: min })
+        }
+        (Structure::Footprint(footprint), Extremum::Max) => {
+            let footprint = &footprint.flipped();
+            thread_apply_over_window(padded, win, Masked { footprint, reducer: max })
+        }
+    }
+}
+
+/// `operation` applied to `input_array` with `structure`, the output has the shape of the input,
+/// padded with `pad_mode` for every erosion and dilation
+///
+/// erosion and dilation are exact in the input type, the differences of the top-hats and the
+/// gradient are taken in `f64` so they never wrap, NaN sorts above every number
+///
+/// # Errors
+/// see [`Structure`], [`pad_for_mode`] and [`Window::validate`]
+pub fn thread_morphology<T>(
+    input_array: ArrayView3<T>,
+    structure: Structure,
+    pad_mode: PadMode,
+    operation: Morphology,
+) -> Result<Array3<f64>, PyLensError>
+where
+    T: NumConv + Zero + Clone + Send + Sync,
+{
+    let erode = |a: ArrayView3<T>| extremum(a, structure, pad_mode, Extremum::Min);
+    let dilate = |a: ArrayView3<T>| extremum(a, structure, pad_mode, Extremum::Max);
+    let as_f64 = |a: Array3<T>| a.map(|x| x.as_f64());
+    let run = |operation| thread_morphology(input_array, structure, pad_mode, operation);
+    Ok(match operation {
+        Morphology::Erosion => as_f64(erode(input_array)?),
+        Morphology::Dilation => as_f64(dilate(input_array)?),
+        Morphology::Opening => as_f64(dilate(erode(input_array)?.view())?),
+        Morphology::Closing => as_f64(erode(dilate(input_array)?.view())?),
+        Morphology::TopHat => input_array.map(|x| x.as_f64()) - run(Morphology::Opening)?,
+        Morphology::BlackHat => run(Morphology::Closing)? - input_array.map(|x| x.as_f64()),
+        Morphology::Gradient => run(Morphology::Dilation)? - run(Morphology::Erosion)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::window::footprint::Footprint;
+    use crate::window::morphology::*;
+    use crate::window::window_methods::{max, min};
+    use ndarray::{s, Array3};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn van_herk_matches_direct() {
+        let mut rng = StdRng::seed_from_u64(47);
+        let img: Array3<i32> = Array3::from_shape_fn((29, 37, 3), |_| rng.gen_range(-500..500));
+        for shape in [
+            WindowShape::Single(1),
+            WindowShape::Single(7),
+            WindowShape::Double(3, 11),
+            WindowShape::Triple(5, 1, 3),
+            WindowShape::Double(29, 5),
+        ] {
+            let win = Window::from(shape);
+            let found = thread_box_extremum(img.view(), win, Extremum::Min).unwrap();
+            assert_eq!(thread_apply_over_window(img.view(), win, min).unwrap(), found, "{}", win);
+            let found = thread_box_extremum(img.view(), win, Extremum::Max).unwrap();
+            assert_eq!(thread_apply_over_window(img.view(), win, max).unwrap(), found, "{}", win);
+        }
+    }
+
+    #[test]
+    fn operations() {
+        // a bright 2 x 2 square and a dark single pixel on a flat background
+        let mut img = Array3::<u8>::from_elem((10, 10, 1), 50);
+        img.slice_mut(s![2..4, 2..4, ..]).fill(200);
+        img[[7, 7, 0]] = 0;
+        let run = |structure, operation| {
+            thread_morphology(img.view(), structure, PadMode::Edge, operation).unwrap()
+        };
+        let square = Structure::Box(WindowShape::Single(3));
+        let disk = Footprint::disk(1);
+        let plus = Structure::Footprint(&disk);
+        // a 3 x 3 box is wider than both details, opening removes the bright one and closing
+        // fills the dark one
+        let (opened, closed) = (run(square, Morphology::Opening), run(square, Morphology::Closing));
+        assert_eq!((50.0, 0.0), (opened[[2, 2, 0]], opened[[7, 7, 0]]));
+        assert_eq!((200.0, 50.0), (closed[[2, 2, 0]], closed[[7, 7, 0]]));
+        let top_hat = run(square, Morphology::TopHat);
+        assert_eq!(150.0, top_hat[[2, 2, 0]]);
+        assert_eq!(0.0, top_hat[[7, 7, 0]]);
+        assert_eq!(50.0, run(square, Morphology::BlackHat)[[7, 7, 0]]);
+        // erosion spreads the dark pixel over the element, a disk of 1 is a cross
+        let eroded = run(plus, Morphology::Erosion);
+        let dark: Vec<_> =
+            eroded.indexed_iter().filter(|(_, &x)| x == 0.0).map(|(i, _)| i).collect();
+        assert_eq!(vec![(6, 7, 0), (7, 6, 0), (7, 7, 0), (7, 8, 0), (8, 7, 0)], dark);
+        let gradient = run(square, Morphology::Gradient);
+        assert_eq!(150.0, gradient[[1, 1, 0]]);
+        assert_eq!(0.0, gradient[[0, 9, 0]]);
+    }
+
+    #[test]
+    fn dilation_reflects_the_footprint() {
+        // the element only holds the pixel to the right, erosion moves a point left and dilation
+        // with the reflected element moves it right
+        let mut img = Array3::<f32>::zeros((5, 5, 1));
+        img[[2, 2, 0]] = 1.0;
+        let mut mask = Array3::from_elem((1, 3, 1), false);
+        mask[[0, 2, 0]] = true;
+        let footprint = Footprint::new(mask).unwrap();
+        let structure = Structure::Footprint(&footprint);
+        let run = |operation| {
+            thread_morphology(img.view(), structure, PadMode::Constant(0.0), operation).unwrap()
+        };
+        assert_eq!(1.0, run(Morphology::Dilation)[[2, 3, 0]]);
+        assert_eq!(1.0, run(Morphology::Erosion)[[2, 1, 0]]);
+        assert_eq!(img.mapv(f64::from), run(Morphology::Opening));
+    }
+
+    #[test]
+    fn invalid_structures() {
+        let img = Array3::<u16>::zeros((6, 6, 1));
+        let even = Structure::Box(WindowShape::Double(3, 4));
+        assert!(matches!(
+            thread_morphology(img.view(), even, PadMode::Reflect, Morphology::Erosion),
+            Err(PyLensError::InvalidArgument { name: "size", .. })
+        ));
+        assert!(matches!(
+            "open".parse::<Morphology>(),
+            Err(PyLensError::InvalidArgument { name: "operation", .. })
+        ));
+    }
+}
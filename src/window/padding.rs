@@ -0,0 +1,316 @@
+//! border handling for the window functions
+//!
+//! every window function works on "valid" windows, windows that fit entirely inside the array.
+//! [`OutputMode::Same`] and [`OutputMode::Full`] are made by padding the input first, see
+//! [`pad_for_mode`], so the window functions themselves never need to know about borders
+
+use ndarray::{s, Array3, ArrayView3, CowArray, Ix3};
+use std::str::FromStr;
+
+use super::window_methods::NumConv;
+use super::Window;
+use crate::error::PyLensError;
+
+/// how the output of a window function lines up with its input
+///
+/// # Valid
+/// only windows that fit inside the input, the output is smaller by `window - 1`
+///
+/// # Same
+/// the window is centred on each input element, the output has the shape of the input,
+/// for even windows the extra element is after the centre. with a stride the output has
+/// `ceil(input / stride)` elements, the first window is centred on the first element
+///
+/// # Full
+/// every window that overlaps the input by at least one element,
+/// the output is larger by `window - 1`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OutputMode {
+    Valid,
+    Same,
+    Full,
+}
+
+/// values used for the part of a window that falls outside the input, names follow `numpy.pad`
+///
+/// # Reflect
+/// mirrored about the edge element, without repeating it: `c b | a b c d | c b`
+///
+/// # Edge
+/// the edge element repeated: `a a | a b c d | d d`
+///
+/// # Constant(v)
+/// a fixed value: `v v | a b c d | v v`
+///
+/// # Wrap
+/// the input repeated: `c d | a b c d | a b`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PadMode {
+    Reflect,
+    Edge,
+    Constant(f64),
+    Wrap,
+}
+
+impl FromStr for OutputMode {
+    type Err = PyLensError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "valid" => Ok(OutputMode::Valid),
+            "same" => Ok(OutputMode::Same),
+            "full" => Ok(OutputMode::Full),
+            _ => Err(PyLensError::InvalidArgument {
+                name: "mode",
+                reason: format!("expected one of 'valid', 'same' or 'full', got {:?}", s),
+            }),
+        }
+    }
+}
+
+impl PadMode {
+    /// parse the python `padding` argument, `cval` is only used by `"constant"`
+    ///
+    /// # Errors
+    /// [`PyLensError::InvalidArgument`] for an unknown name,
+    /// [`PyLensError::NonFinite`] for a constant that is NaN or infinite
+    pub fn parse(s: &str, cval: f64) -> Result<Self, PyLensError> {
+        match s {
+            "reflect" => Ok(PadMode::Reflect),
+            "edge" => Ok(PadMode::Edge),
+            "constant" => Ok(PadMode::Constant(crate::error::check_finite("cval", cval)?)),
+            "wrap" => Ok(PadMode::Wrap),
+            _ => Err(PyLensError::InvalidArgument {
+                name: "padding",
+                reason: format!(
+                    "expected one of 'reflect', 'edge', 'constant' or 'wrap', got {:?}",
+                    s
+                ),
+            }),
+        }
+    }
+
+    /// index of the input element used at position `i` of an axis of length `n`,
+    /// `i` counts from the start of the input so the padding before it is negative,
+    /// `None` for [`PadMode::Constant`] outside the input
+    fn source_index(self, i: isize, n: usize) -> Option<usize> {
+        let n_i = n as isize;
+        if (0..n_i).contains(&i) {
+            return Some(i as usize);
+        }
+        match self {
+            PadMode::Constant(_) => None,
+            PadMode::Edge => Some(i.clamp(0, n_i - 1) as usize),
+            PadMode::Wrap => Some(i.rem_euclid(n_i) as usize),
+            PadMode::Reflect => {
+                if n == 1 {
+                    return Some(0);
+                }
+                // the mirrored sequence repeats every 2 * (n - 1) elements
+                let period = 2 * (n_i - 1);
+                let m = i.rem_euclid(period);
+                Some(if m < n_i { m } else { period - m } as usize)
+            }
+        }
+    }
+}
+
+impl OutputMode {
+    /// padding `(before, after)` on every axis needed to produce this mode with `win_size`,
+    /// based on the extent of the window so dilated windows are padded by the area they cover
+    pub fn pad_widths(self, win_size: impl Into<Window>) -> [(usize, usize); 3] {
+        let e = win_size.into().extent();
+        [e.0, e.1, e.2].map(|e| {
+            let total = e.saturating_sub(1);
+            match self {
+                OutputMode::Valid => (0, 0),
+                OutputMode::Same => (total / 2, total - total / 2),
+                OutputMode::Full => (total, total),
+            }
+        })
+    }
+}
+
+/// pad `arr` by `widths` `(before, after)` on every axis
+///
+/// # Errors
+/// [`PyLensError::EmptyImage`] if an axis that needs padding is empty and `mode` takes its
+/// values from the input
+pub fn pad<T>(
+    arr: ArrayView3<T>,
+    widths: [(usize, usize); 3],
+    mode: PadMode,
+) -> Result<Array3<T>, PyLensError>
+where
+    T: NumConv + Clone,
+{
+    let sh = arr.shape();
+    let needs_values = !matches!(mode, PadMode::Constant(_));
+    if needs_values && (0..3).any(|ax| sh[ax] == 0 && widths[ax] != (0, 0)) {
+        return Err(PyLensError::EmptyImage { shape: sh.to_vec() });
+    }
+    let fill = match mode {
+        PadMode::Constant(v) => Some(T::from_f64(v)),
+        _ => None,
+    };
+    // source index along each axis for every output position, computed once per axis
+    let index_maps: Vec<Vec<Option<usize>>> = (0..3)
+        .map(|ax| {
+            let (before, after) = widths[ax];
+            (0..sh[ax] + before + after)
+                .map(|i| mode.source_index(i as isize - before as isize, sh[ax]))
+                .collect()
+        })
+        .collect();
+    let out_shape = (index_maps[0].len(), index_maps[1].len(), index_maps[2].len());
+    let out = Array3::from_shape_fn(out_shape, |(i, j, k)| {
+        match (index_maps[0][i], index_maps[1][j], index_maps[2][k]) {
+            (Some(a), Some(b), Some(c)) => arr[[a, b, c]].clone(),
+            _ => fill.clone().unwrap(),
+        }
+    });
+    Ok(out)
+}
+
+/// input to hand to a window function so its "valid" output has the shape `mode` asks for,
+/// borrowed as is for [`OutputMode::Valid`], otherwise padded with `pad_mode`
+///
+/// # Errors
+/// see [`pad`]
+pub fn pad_for_mode<T>(
+    arr: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    mode: OutputMode,
+    pad_mode: PadMode,
+) -> Result<CowArray<T, Ix3>, PyLensError>
+where
+    T: NumConv + Clone,
+{
+    match mode {
+        OutputMode::Valid => Ok(CowArray::from(arr)),
+        _ => Ok(CowArray::from(pad(arr, mode.pad_widths(win_size), pad_mode)?)),
+    }
+}
+
+/// the element at the centre of every window of `win_size` in `arr`, for even windows the one
+/// before the middle, in the shape of the window function output, so a window statistic of
+/// `arr` lines up with the element it was computed for
+///
+/// # Errors
+/// see [`Window::validate`]
+pub fn window_centres<'a, T>(
+    arr: ArrayView3<'a, T>,
+    win_size: impl Into<Window>,
+) -> Result<ArrayView3<'a, T>, PyLensError> {
+    let win = win_size.into();
+    let shape = win.output_shape(arr.shape())?;
+    let (e, st) = (win.extent(), win.stride);
+    let first = [(e.0 - 1) / 2, (e.1 - 1) / 2, (e.2 - 1) / 2];
+    let last = [
+        first[0] + (shape[0] - 1) * st.0,
+        first[1] + (shape[1] - 1) * st.1,
+        first[2] + (shape[2] - 1) * st.2,
+    ];
+    Ok(arr.slice_move(s![
+        first[0]..=last[0];st.0,
+        first[1]..=last[1];st.1,
+        first[2]..=last[2];st.2
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::window::integral::thread_integral_stdev;
+    use crate::window::padding::*;
+    use crate::window::WindowShape;
+    use ndarray::{s, Array3};
+
+    fn padded_line(mode: PadMode, before: usize, after: usize) -> Vec<u8> {
+        let arr = Array3::from_shape_vec((1, 4, 1), vec![1u8, 2, 3, 4]).unwrap();
+        let out = pad(arr.view(), [(0, 0), (before, after), (0, 0)], mode).unwrap();
+        out.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_pad_modes() {
+        assert_eq!(vec![3, 2, 1, 2, 3, 4, 3, 2], padded_line(PadMode::Reflect, 2, 2));
+        assert_eq!(vec![1, 1, 1, 2, 3, 4, 4, 4], padded_line(PadMode::Edge, 2, 2));
+        assert_eq!(vec![9, 9, 1, 2, 3, 4, 9, 9], padded_line(PadMode::Constant(9.0), 2, 2));
+        assert_eq!(vec![3, 4, 1, 2, 3, 4, 1, 2], padded_line(PadMode::Wrap, 2, 2));
+    }
+
+    #[test]
+    fn test_pad_wider_than_array() {
+        // same as numpy.pad([1, 2, 3, 4], 7, mode)
+        assert_eq!(
+            vec![2, 1, 2, 3, 4, 3, 2, 1, 2, 3, 4, 3, 2, 1, 2, 3, 4, 3],
+            padded_line(PadMode::Reflect, 7, 7)
+        );
+        assert_eq!(
+            vec![2, 3, 4, 1, 2, 3, 4, 1, 2, 3, 4, 1, 2, 3, 4, 1, 2, 3],
+            padded_line(PadMode::Wrap, 7, 7)
+        );
+    }
+
+    #[test]
+    fn test_pad_empty_axis() {
+        let arr: Array3<u8> = Array3::zeros((0, 4, 1));
+        let out = pad(arr.view(), [(1, 1), (0, 0), (0, 0)], PadMode::Reflect);
+        assert!(matches!(out, Err(PyLensError::EmptyImage { .. })));
+        let out = pad(arr.view(), [(1, 1), (0, 0), (0, 0)], PadMode::Constant(0.0)).unwrap();
+        assert_eq!([2, 4, 1], out.shape());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ok(OutputMode::Same), "same".parse());
+        assert!(matches!(
+            "middle".parse::<OutputMode>(),
+            Err(PyLensError::InvalidArgument { name: "mode", .. })
+        ));
+        assert_eq!(Ok(PadMode::Constant(2.0)), PadMode::parse("constant", 2.0));
+        assert!(matches!(
+            PadMode::parse("mirror", 0.0),
+            Err(PyLensError::InvalidArgument { name: "padding", .. })
+        ));
+        assert!(matches!(
+            PadMode::parse("constant", f64::NAN),
+            Err(PyLensError::NonFinite { name: "cval", .. })
+        ));
+    }
+
+    #[test]
+    fn test_output_mode_shapes() {
+        let arr: Array3<u8> = Array3::from_shape_fn((20, 15, 3), |(a, b, c)| (a * b + c) as u8);
+        for win in [
+            WindowShape::Single(5),
+            WindowShape::Double(4, 7),
+            WindowShape::Triple(3, 3, 2),
+            WindowShape::Single(30), // larger than the array, fine once padded
+        ] {
+            let w = win.dims();
+            for (mode, shape) in [
+                (OutputMode::Same, [20, 15, 3]),
+                (OutputMode::Full, [20 + w.0 - 1, 15 + w.1 - 1, 3 + w.2 - 1]),
+            ] {
+                let padded = pad_for_mode(arr.view(), win, mode, PadMode::Edge).unwrap();
+                let out: Array3<u8> = thread_integral_stdev(padded.view(), win, 0.0, 1.0).unwrap();
+                assert_eq!(shape, out.shape(), "{} {:?}", win, mode);
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_is_centred() {
+        let arr: Array3<u16> = Array3::from_shape_fn((20, 15, 1), |(a, b, _)| (a * a + 3 * b) as u16);
+        let win = WindowShape::Double(5, 4);
+        let valid: Array3<u16> = thread_integral_stdev(arr.view(), win, 0.0, 1.0).unwrap();
+        for pad_mode in [PadMode::Reflect, PadMode::Edge, PadMode::Constant(0.0), PadMode::Wrap] {
+            let padded = pad_for_mode(arr.view(), win, OutputMode::Same, pad_mode).unwrap();
+            let same: Array3<u16> = thread_integral_stdev(padded.view(), win, 0.0, 1.0).unwrap();
+            // the interior doesn't touch the padding, before padding is (5-1)/2 = 2 and (4-1)/2 = 1
+            assert_eq!(valid, same.slice(s![2..18, 1..13, ..]));
+        }
+    }
+}
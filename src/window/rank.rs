@@ -0,0 +1,267 @@
+//! median, percentile and rank filters
+//!
+//! integer images whose values span at most 65536 numbers slide one histogram of the window along
+//! each row, see [`super::histogram`] (huang's algorithm). the histogram has a coarse level with
+//! one count per group of bins, so finding a rank reads about `2 * sqrt(span)` counts instead of
+//! sorting the window, and large windows stay fast. floats, wider integer ranges and dilated
+//! windows select from every window instead
+
+use ndarray::{Array3, ArrayView3, ArrayViewMut3};
+
+use super::histogram::{slide_histogram, SlidingHistogram};
+use super::window_methods::{nth_smallest, NumConv, Percentile, Reducer};
+use super::{map_tiles, thread_apply_over_window, Window};
+use crate::error::PyLensError;
+
+/// the integer a value is binned by, `None` for types ranked by sorting
+pub trait RankKey {
+    fn rank_key(&self) -> Option<i64>;
+}
+
+macro_rules! rank_key {
+    ($($t:ty),*) => {
+        $(impl RankKey for $t {
+            fn rank_key(&self) -> Option<i64> {
+                i64::try_from(*self).ok()
+            }
+        })*
+    };
+}
+
+rank_key!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl RankKey for f32 {
+    fn rank_key(&self) -> Option<i64> {
+        None
+    }
+}
+
+impl RankKey for f64 {
+    fn rank_key(&self) -> Option<i64> {
+        None
+    }
+}
+
+/// which value of the sorted window a rank filter returns
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Rank {
+    /// the `k`th smallest, counting from 0
+    Nth(usize),
+    /// interpolated like [`Percentile`]
+    Percentile(Percentile),
+}
+
+impl Rank {
+    /// the median, the mean of the two middle values for windows with an even size
+    pub fn median() -> Self {
+        Rank::Percentile(Percentile::new(50.0).expect("50 is a percentile"))
+    }
+
+    /// # Errors
+    /// [`PyLensError::InvalidArgument`] if the rank isn't below the `n` values of a window
+    pub fn check(self, n: usize) -> Result<Self, PyLensError> {
+        match self {
+            Rank::Nth(k) if k >= n => Err(PyLensError::InvalidArgument {
+                name: "rank",
+                reason: format!("a window has {} values, the rank must be below it, got {}", n, k),
+            }),
+            _ => Ok(self),
+        }
+    }
+
+    /// the value from the `k`th smallest value `nth` of a window of `n` values
+    fn value(self, n: usize, nth: impl Fn(usize) -> f64) -> f64 {
+        match self {
+            Rank::Nth(k) => nth(k),
+            Rank::Percentile(p) => {
+                let pos = p.q() / 100.0 * (n - 1) as f64;
+                let lo = pos.floor() as usize;
+                let frac = pos - lo as f64;
+                let a = nth(lo);
+                if frac == 0.0 || lo + 1 == n {
+                    return a;
+                }
+                a + (nth(lo + 1) - a) * frac
+            }
+        }
+    }
+}
+
+impl<T: NumConv + Clone> Reducer<T, f64> for Rank {
+    fn reduce(&self, w: ArrayView3<T>) -> f64 {
+        match self {
+            Rank::Nth(k) => nth_smallest(w, *k).as_f64(),
+            Rank::Percentile(p) => p.reduce(w),
+        }
+    }
+}
+
+/// counts of every bin and of every group of `1 << shift` bins
+struct RankHistogram {
+    fine: Vec<u32>,
+    coarse: Vec<u32>,
+    shift: u32,
+}
+
+impl RankHistogram {
+    fn new(span: usize) -> Self {
+        let shift = span.next_power_of_two().trailing_zeros().div_ceil(2);
+        RankHistogram {
+            fine: vec![0; span],
+            coarse: vec![0; span.div_ceil(1 << shift)],
+            shift,
+        }
+    }
+
+    /// bin of the `k`th smallest value, `k` must be below the number of values
+    fn select(&self, mut k: u32) -> usize {
+        let mut group = 0;
+        while k >= self.coarse[group] {
+            k -= self.coarse[group];
+            group += 1;
+        }
+        let mut bin = group << self.shift;
+        while k >= self.fine[bin] {
+            k -= self.fine[bin];
+            bin += 1;
+        }
+        bin
+    }
+}
+
+impl SlidingHistogram for RankHistogram {
+    fn add(&mut self, bin: usize) {
+        self.fine[bin] += 1;
+        self.coarse[bin >> self.shift] += 1;
+    }
+
+    fn remove(&mut self, bin: usize) {
+        self.fine[bin] -= 1;
+        self.coarse[bin >> self.shift] -= 1;
+    }
+}
+
+/// smallest key of `arr` and the number of keys up to its largest, when every value has a key
+/// and they span at most 65536
+fn key_span<T: RankKey>(arr: ArrayView3<T>) -> Option<(i64, usize)> {
+    let mut keys = arr.iter().map(|x| x.rank_key());
+    let first = keys.next()??;
+    let (lo, hi) = keys.try_fold((first, first), |(lo, hi), k| k.map(|k| (lo.min(k), hi.max(k))))?;
+    match hi.checked_sub(lo)? {
+        d @ 0..=0xffff => Some((lo, d as usize + 1)),
+        _ => None,
+    }
+}
+
+/// `rank` of every window of `win` in `arr`, `win` has no dilation and the keys of `arr` start
+/// at `lo` and span `span`
+fn sliding_rank_into<T: RankKey>(
+    arr: ArrayView3<T>,
+    win: Window,
+    (lo, span): (i64, usize),
+    rank: Rank,
+    out: ArrayViewMut3<f64>,
+) {
+    let bins = arr.map(|x| (x.rank_key().expect("every value has a key") - lo) as u16);
+    let (w0, w1, w2) = win.dims();
+    let n = w0 * w1 * w2;
+    let mut hist = RankHistogram::new(span);
+    let value = |h: &RankHistogram| rank.value(n, |k| (lo + h.select(k as u32) as i64) as f64);
+    slide_histogram(bins.view(), win, &mut hist, out, value);
+}
+
+/// `rank` of the values of every window, as `f64` like [`Percentile`]
+///
+/// integer images without a dilation use a sliding histogram, other images select the rank from
+/// every window, NaN sorts above every number
+///
+/// # Errors
+/// see [`Rank::check`] and [`Window::validate`]
+pub fn thread_rank_filter<T>(
+    input_array: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    rank: Rank,
+) -> Result<Array3<f64>, PyLensError>
+where
+    T: RankKey + NumConv + Clone + Sync,
+{
+    let win = win_size.into();
+    let (w0, w1, w2) = win.dims();
+    let rank = rank.check(w0 * w1 * w2)?;
+    match (win.dilation, key_span(input_array)) {
+        ((1, 1, 1), Some(keys)) => map_tiles(input_array, win, |src, out| {
+            sliding_rank_into(src, win, keys, rank, out)
+        }),
+        _ => thread_apply_over_window(input_array, win, rank),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::window::rank::*;
+    use crate::window::WindowShape;
+    use ndarray::Array3;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn sliding_matches_sorted() {
+        let mut rng = StdRng::seed_from_u64(46);
+        let img: Array3<i16> = Array3::from_shape_fn((33, 41, 2), |_| rng.gen_range(-900..900));
+        let wide = img.mapv(|x| x as i32 * 1000);
+        let ranks = [
+            Rank::median(),
+            Rank::Percentile(Percentile::new(12.5).unwrap()),
+            Rank::Percentile(Percentile::new(100.0).unwrap()),
+            Rank::Nth(0),
+            Rank::Nth(5),
+        ];
+        for win in [
+            Window::from(WindowShape::Single(3)),
+            Window::from(WindowShape::Double(31, 4)),
+            Window::from(WindowShape::Triple(3, 5, 2)),
+            Window::from(WindowShape::Double(5, 6)).with_stride((2, 4, 1)),
+            Window::from(WindowShape::Double(3, 3)).with_dilation((2, 3, 1)),
+        ] {
+            for rank in ranks {
+                let sliding = thread_rank_filter(img.view(), win, rank).unwrap();
+                let sorted = thread_apply_over_window(img.view(), win, rank).unwrap();
+                assert_eq!(sorted, sliding, "{} {:?}", win, rank);
+                // a span too wide for the histogram sorts every window
+                let wide = thread_rank_filter(wide.view(), win, rank).unwrap();
+                for (a, b) in sorted.iter().zip(&wide) {
+                    assert!((a * 1000.0 - b).abs() < 1e-6, "{} {:?}", win, rank);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn large_median() {
+        // the median of a 31 x 31 window of a ramp is the value of its centre
+        let img = Array3::from_shape_fn((64, 64, 1), |(a, b, _)| (a * 64 + b) as u16);
+        let win = WindowShape::Single(31);
+        let found = thread_rank_filter(img.view(), win, Rank::median()).unwrap();
+        assert_eq!([34, 34, 1], found.shape());
+        for ((i, j, _), &m) in found.indexed_iter() {
+            assert_eq!(img[[i + 15, j + 15, 0]] as f64, m);
+        }
+    }
+
+    #[test]
+    fn floats_and_invalid_rank() {
+        let img = Array3::from_shape_fn((5, 5, 1), |(a, b, _)| match (a, b) {
+            (2, 2) => f32::NAN,
+            _ => (a * 5 + b) as f32,
+        });
+        let max = thread_rank_filter(img.view(), WindowShape::Single(3), Rank::Nth(8)).unwrap();
+        assert!(max[[1, 1, 0]].is_nan());
+        let min = thread_rank_filter(img.view(), WindowShape::Single(3), Rank::Nth(0)).unwrap();
+        assert_eq!(6.0, min[[1, 1, 0]]);
+        assert!(matches!(
+            thread_rank_filter(img.view(), WindowShape::Single(3), Rank::Nth(9)),
+            Err(PyLensError::InvalidArgument { name: "rank", .. })
+        ));
+    }
+}
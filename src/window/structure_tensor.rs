@@ -0,0 +1,196 @@
+//! the structure tensor, gaussian weighted products of the gradients around every pixel, and the
+//! maps derived from its eigenvalues, coherence, orientation and the harris and shi-tomasi corner
+//! responses
+//!
+//! the gradients come from [`super::edges`] and the weighting runs one axis at a time like
+//! [`super::kernel::thread_separable`], so every map has the shape of the input
+
+use ndarray::{Array3, ArrayView3, Zip};
+
+use super::edges::{thread_gradients, Operator};
+use super::kernel::{thread_separable, Kernel};
+use super::morphology::{thread_morphology, Morphology, Structure};
+use super::padding::{pad_for_mode, OutputMode, PadMode};
+use super::window_methods::NumConv;
+use super::{Window, WindowShape};
+use crate::error::PyLensError;
+
+/// the symmetric 2x2 tensor `[[jyy, jxy], [jxy, jxx]]` of every pixel
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructureTensor {
+    pub jyy: Array3<f64>,
+    pub jxy: Array3<f64>,
+    pub jxx: Array3<f64>,
+}
+
+impl StructureTensor {
+    /// `f(jyy, jxy, jxx)` of every pixel
+    fn map(&self, f: impl Fn(f64, f64, f64) -> f64) -> Array3<f64> {
+        Zip::from(&self.jyy)
+            .and(&self.jxy)
+            .and(&self.jxx)
+            .map_collect(|&yy, &xy, &xx| f(yy, xy, xx))
+    }
+
+    /// `((l1 - l2) / (l1 + l2))^2` of the eigenvalues, 1 for a single gradient direction and 0
+    /// for none or every direction alike
+    pub fn coherence(&self) -> Array3<f64> {
+        self.map(|yy, xy, xx| {
+            let (l1, l2) = eigenvalues(yy, xy, xx);
+            match l1 + l2 {
+                sum if sum > 0.0 => ((l1 - l2) / sum).powi(2),
+                _ => 0.0,
+            }
+        })
+    }
+
+    /// direction of the dominant gradient in radians, `0.5 * atan2(2 jxy, jxx - jyy)`, 0 points
+    /// right and `pi / 2` down like [`super::edges::orientation`]
+    pub fn orientation(&self) -> Array3<f64> {
+        self.map(|yy, xy, xx| 0.5 * (2.0 * xy).atan2(xx - yy))
+    }
+
+    /// `det - k * trace^2`, positive at corners, negative along edges
+    pub fn harris(&self, k: f64) -> Array3<f64> {
+        self.map(|yy, xy, xx| yy * xx - xy * xy - k * (yy + xx).powi(2))
+    }
+
+    /// the smaller eigenvalue, large only where the gradients vary in every direction
+    pub fn shi_tomasi(&self) -> Array3<f64> {
+        self.map(|yy, xy, xx| eigenvalues(yy, xy, xx).1)
+    }
+}
+
+/// eigenvalues of `[[yy, xy], [xy, xx]]`, the larger first
+fn eigenvalues(yy: f64, xy: f64, xx: f64) -> (f64, f64) {
+    let mid = (yy + xx) / 2.0;
+    let spread = ((xx - yy) / 2.0).hypot(xy);
+    (mid + spread, mid - spread)
+}
+
+/// structure tensor of every pixel, the `operator` gradients multiplied and weighted by a gaussian
+/// of `sigma` on the first two axes, each channel on its own
+///
+/// # Errors
+/// see [`Kernel::gaussian`] and [`pad_for_mode`]
+pub fn thread_structure_tensor<T>(
+    input_array: ArrayView3<T>,
+    operator: Operator,
+    sigma: f64,
+    pad_mode: PadMode,
+) -> Result<StructureTensor, PyLensError>
+where
+    T: NumConv + Clone + Sync,
+{
+    let kernel = Kernel::gaussian((sigma, sigma, 0.0), 4.0)?;
+    let factors = kernel.factors().expect("gaussians are separable");
+    let win = Window::from(kernel.shape());
+    let (dy, dx) = thread_gradients(input_array, operator, pad_mode)?;
+    let weighted = |product: Array3<f64>| {
+        let padded = pad_for_mode(product.view(), win, OutputMode::Same, pad_mode)?;
+        thread_separable(padded.view(), win, factors)
+    };
+    Ok(StructureTensor {
+        jyy: weighted(&dy * &dy)?,
+        jxy: weighted(&dy * &dx)?,
+        jxx: weighted(&dx * &dx)?,
+    })
+}
+
+/// `(row, column, channel)` of every local maximum of `response` above `threshold`, the largest
+/// value within `min_distance` rows and columns of it, sorted from the strongest
+///
+/// pixels of a plateau are all maxima, NaN is never one
+///
+/// # Errors
+/// [`PyLensError::InvalidArgument`] if `min_distance` is 0, [`PyLensError::NonFinite`] for a NaN
+/// `threshold`
+pub fn corner_peaks(
+    response: ArrayView3<f64>,
+    min_distance: usize,
+    threshold: f64,
+) -> Result<Vec<(usize, usize, usize)>, PyLensError> {
+    if min_distance == 0 {
+        return Err(PyLensError::InvalidArgument {
+            name: "min_distance",
+            reason: "must be at least 1".to_string(),
+        });
+    }
+    if threshold.is_nan() {
+        return Err(PyLensError::NonFinite { name: "threshold", value: threshold });
+    }
+    let structure = Structure::Box(WindowShape::Single(2 * min_distance + 1));
+    let outside = PadMode::Constant(f64::NEG_INFINITY);
+    let largest = thread_morphology(response, structure, outside, Morphology::Dilation)?;
+    let mut peaks: Vec<_> = Zip::indexed(&response)
+        .and(&largest)
+        .fold(Vec::new(), |mut peaks, index, &r, &m| {
+            if r > threshold && r == m {
+                peaks.push(index);
+            }
+            peaks
+        });
+    peaks.sort_by(|&a, &b| response[b].total_cmp(&response[a]).then(a.cmp(&b)));
+    Ok(peaks)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::window::structure_tensor::*;
+    use ndarray::{s, Array3};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn stripes_are_coherent() {
+        // stripes along the columns, every gradient points down the rows
+        let img = Array3::from_shape_fn((24, 24, 1), |(a, _, _)| ((a as f64) * 0.7).sin() * 100.0);
+        let tensor = thread_structure_tensor(img.view(), Operator::Sobel, 2.0, PadMode::Reflect)
+            .unwrap();
+        let inside = s![4..20, 4..20, ..];
+        assert!(tensor.jxx.slice(inside).iter().all(|&x| x.abs() < 1e-9));
+        assert!(tensor.coherence().slice(inside).iter().all(|&c| (c - 1.0).abs() < 1e-9));
+        let orientation = tensor.orientation();
+        assert!(orientation.slice(inside).iter().all(|&o| (o.abs() - PI / 2.0).abs() < 1e-9));
+        assert!(tensor.shi_tomasi().slice(inside).iter().all(|&l| l.abs() < 1e-6));
+        // a flat image has no structure
+        let flat = Array3::<u8>::from_elem((9, 9, 1), 4);
+        let tensor =
+            thread_structure_tensor(flat.view(), Operator::Sobel, 1.0, PadMode::Reflect).unwrap();
+        assert!(tensor.coherence().iter().all(|&c| c == 0.0));
+    }
+
+    #[test]
+    fn corners_of_a_square() {
+        let mut img = Array3::<u8>::zeros((30, 30, 1));
+        img.slice_mut(s![10..20, 10..20, ..]).fill(255);
+        let tensor =
+            thread_structure_tensor(img.view(), Operator::Sobel, 1.5, PadMode::Reflect).unwrap();
+        for response in [tensor.harris(0.05), tensor.shi_tomasi()] {
+            let largest = response.fold(0.0, |a: f64, &b| a.max(b));
+            let peaks = corner_peaks(response.view(), 3, 0.1 * largest).unwrap();
+            assert_eq!(4, peaks.len(), "{:?}", peaks);
+            for (i, j, _) in peaks {
+                // every peak is next to a corner of the square
+                let near = |x: usize| x.abs_diff(10) <= 1 || x.abs_diff(19) <= 1;
+                assert!(near(i) && near(j), "{} {}", i, j);
+            }
+        }
+        // along the middle of an edge harris is negative
+        assert!(tensor.harris(0.05)[[15, 10, 0]] < 0.0);
+    }
+
+    #[test]
+    fn invalid_peaks() {
+        let response = Array3::<f64>::zeros((5, 5, 1));
+        assert!(matches!(
+            corner_peaks(response.view(), 0, 0.0),
+            Err(PyLensError::InvalidArgument { name: "min_distance", .. })
+        ));
+        assert!(matches!(
+            corner_peaks(response.view(), 1, f64::NAN),
+            Err(PyLensError::NonFinite { name: "threshold", .. })
+        ));
+        assert!(corner_peaks(response.view(), 1, 0.0).unwrap().is_empty());
+    }
+}
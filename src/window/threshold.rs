@@ -0,0 +1,264 @@
+//! local adaptive thresholding, every pixel is compared with a threshold computed from the
+//! window centred on it, the result is a mask that is `true` where the pixel is above it
+//!
+//! niblack, sauvola and wolf take the window mean and standard deviation from
+//! [`super::integral`] so their cost doesn't depend on the window size, bernsen needs the window
+//! minimum and maximum
+
+use ndarray::{Array3, ArrayView3, Zip};
+
+use super::integral::thread_integral_moments;
+use super::padding::{pad_for_mode, window_centres, OutputMode, PadMode};
+use super::window_methods::NumConv;
+use super::{thread_apply_stacked, Window};
+use crate::error::{check_finite, PyLensError};
+
+/// how the threshold of a window is computed from its mean `m`, population standard deviation
+/// `s`, minimum and maximum
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Threshold {
+    /// `m + k * s`
+    Niblack { k: f64 },
+    /// `m * (1 + k * (s / r - 1))`, `r` defaults to half the value range of the image
+    Sauvola { k: f64, r: Option<f64> },
+    /// `(1 - k) * m + k * lo + k * s / r * (m - lo)`, `lo` is the image minimum and `r` the
+    /// largest window standard deviation
+    Wolf { k: f64 },
+    /// `(min + max) / 2` of the window, a window whose `max - min` is below `contrast` is all one
+    /// class, above when its mid value is above the mid value of the image
+    Bernsen { contrast: f64 },
+}
+
+impl Threshold {
+    /// # Errors
+    /// [`PyLensError::NonFinite`] for a NaN or infinite parameter,
+    /// [`PyLensError::InvalidArgument`] if the sauvola `r` is not positive
+    fn validate(self) -> Result<Self, PyLensError> {
+        match self {
+            Threshold::Niblack { k } | Threshold::Wolf { k } => {
+                check_finite("k", k)?;
+            }
+            Threshold::Sauvola { k, r } => {
+                check_finite("k", k)?;
+                if let Some(r) = r {
+                    if check_finite("r", r)? <= 0.0 {
+                        return Err(PyLensError::InvalidArgument {
+                            name: "r",
+                            reason: format!("the dynamic range must be positive, got {}", r),
+                        });
+                    }
+                }
+            }
+            Threshold::Bernsen { contrast } => {
+                check_finite("contrast_threshold", contrast)?;
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// mask of the pixels of `input_array` above the `method` threshold of the window of `win_size`
+/// centred on them, the mask has the shape `mode` gives, padded with `pad_mode`
+///
+/// the standard deviation is the population one (ddof 0), NaN pixels are never above and are
+/// left out of the image minimum and maximum. a window over a NaN or infinite value has a NaN
+/// Niblack, Sauvola or Wolf threshold, so its centre is never above, other windows are unaffected
+///
+/// # Errors
+/// see [`Threshold`], [`pad_for_mode`] and [`Window::validate`]
+pub fn thread_threshold<T>(
+    input_array: ArrayView3<T>,
+    win_size: impl Into<Window>,
+    mode: OutputMode,
+    pad_mode: PadMode,
+    method: Threshold,
+) -> Result<Array3<bool>, PyLensError>
+where
+    T: NumConv + Clone + Sync,
+{
+    let win = win_size.into();
+    let method = method.validate()?;
+    let padded = pad_for_mode(input_array, win, mode, pad_mode)?;
+    let padded = padded.view();
+    let centres = window_centres(padded, win)?;
+    let (lo, hi) = input_array.iter().map(|x| x.as_f64()).fold(
+        (f64::INFINITY, f64::NEG_INFINITY),
+        |(lo, hi), x| (lo.min(x), hi.max(x)),
+    );
+    // a flat image has no spread, the tiny range keeps `s / r` at zero instead of NaN
+    let positive = |r: f64| r.max(f64::MIN_POSITIVE);
+    let sd = |n: f64, m2: f64| (m2 / n).sqrt();
+
+    let thresholds = match method {
+        Threshold::Niblack { k } => {
+            thread_integral_moments(padded, win, |n, m, m2| m + k * sd(n, m2))?
+        }
+        Threshold::Sauvola { k, r } => {
+            let r = positive(r.unwrap_or((hi - lo) / 2.0));
+            thread_integral_moments(padded, win, |n, m, m2| m * (1.0 + k * (sd(n, m2) / r - 1.0)))?
+        }
+        Threshold::Wolf { k } => {
+            let spread = thread_integral_moments(padded, win, |n, _, m2| sd(n, m2))?;
+            let r = positive(spread.fold(0.0, |a: f64, &b| a.max(b)));
+            thread_integral_moments(padded, win, |n, m, m2| {
+                (1.0 - k) * m + k * lo + k * sd(n, m2) / r * (m - lo)
+            })?
+        }
+        Threshold::Bernsen { contrast } => {
+            let image_mid = (lo + hi) / 2.0;
+            let range = thread_apply_stacked(padded, win, 2, |w: ArrayView3<T>, out: &mut [f64]| {
+                let (a, b) = w.iter().map(|x| x.as_f64()).fold(
+                    (f64::INFINITY, f64::NEG_INFINITY),
+                    |(a, b), x| (a.min(x), b.max(x)),
+                );
+                out.copy_from_slice(&[a, b]);
+            })?;
+            let mut mask = Array3::from_elem(centres.raw_dim(), false);
+            Zip::from(&mut mask)
+                .and(range.lanes(ndarray::Axis(3)))
+                .and(&centres)
+                .for_each(|m, r, x| {
+                    let mid = (r[0] + r[1]) / 2.0;
+                    *m = if r[1] - r[0] < contrast {
+                        mid > image_mid
+                    } else {
+                        x.as_f64() > mid
+                    };
+                });
+            return Ok(mask);
+        }
+    };
+    Ok(Zip::from(&thresholds)
+        .and(&centres)
+        .map_collect(|t, x| x.as_f64() > *t))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PyLensError;
+    use crate::window::padding::{OutputMode, PadMode};
+    use crate::window::threshold::*;
+    use crate::window::WindowShape;
+    use ndarray::{s, Array3};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// mean and population stdev of the `d` window starting at `[i, j, k]`
+    fn window_moments(img: &Array3<f64>, (i, j, k): (usize, usize, usize), d: usize) -> (f64, f64) {
+        let w = img.slice(s![i..i + d, j..j + d, k..k + 1]);
+        (w.mean().unwrap(), w.std(0.0))
+    }
+
+    #[test]
+    fn thresholds_match_formulas() {
+        let mut rng = StdRng::seed_from_u64(41);
+        let img: Array3<u8> = Array3::from_shape_fn((24, 19, 2), |_| rng.gen_range(20..230));
+        let w = img.mapv(|x| x as f64);
+        let (lo, hi) = (w.fold(255.0, |a: f64, &b| a.min(b)), w.fold(0.0, |a: f64, &b| a.max(b)));
+        let win = WindowShape::Single(5);
+        let valid = |method| {
+            thread_threshold(img.view(), win, OutputMode::Valid, PadMode::Reflect, method).unwrap()
+        };
+        let r_wolf = ndarray::indices((20, 15, 2))
+            .into_iter()
+            .map(|p| window_moments(&w, p, 5).1)
+            .fold(0.0, f64::max);
+        let formula = |method, m: f64, s: f64| match method {
+            Threshold::Niblack { k } => m + k * s,
+            Threshold::Sauvola { k, .. } => m * (1.0 + k * (s / ((hi - lo) / 2.0) - 1.0)),
+            Threshold::Wolf { k } => (1.0 - k) * m + k * lo + k * s / r_wolf * (m - lo),
+            Threshold::Bernsen { .. } => unreachable!(),
+        };
+        let methods = [
+            Threshold::Niblack { k: -0.2 },
+            Threshold::Sauvola { k: 0.2, r: None },
+            Threshold::Wolf { k: 0.5 },
+        ];
+        for method in methods {
+            let mask = valid(method);
+            assert_eq!([20, 15, 2], mask.shape());
+            for ((i, j, k), &found) in mask.indexed_iter() {
+                let (m, s) = window_moments(&w, (i, j, k), 5);
+                assert_eq!(w[[i + 2, j + 2, k]] > formula(method, m, s), found, "{:?}", method);
+            }
+        }
+    }
+
+    #[test]
+    fn bernsen_contrast() {
+        // a dark left half with faint texture and a bright edge through the right half
+        let img = Array3::from_shape_fn((12, 12, 1), |(a, b, _)| match b {
+            0..=5 => 10 + (a % 2) as u8,
+            6..=8 => 100,
+            _ => 200,
+        });
+        let method = Threshold::Bernsen { contrast: 15.0 };
+        let win = WindowShape::Single(3);
+        let mask =
+            thread_threshold(img.view(), win, OutputMode::Same, PadMode::Edge, method).unwrap();
+        assert_eq!(img.shape(), mask.shape());
+        let row: Vec<bool> = mask.slice(s![4, .., 0]).to_vec();
+        // low contrast windows compare their mid with the image mid of 105, the rest split at
+        // their own mid, 55 next to the dark half and 150 next to the bright one
+        let mut expected = vec![false; 12];
+        expected[6] = true;
+        expected[9..].fill(true);
+        assert_eq!(expected, row);
+    }
+
+    #[test]
+    fn same_mode_is_centred() {
+        let mut img = Array3::<f32>::zeros((9, 9, 1));
+        img[[4, 4, 0]] = 1.0;
+        let method = Threshold::Niblack { k: 0.0 };
+        let mask = thread_threshold(
+            img.view(),
+            WindowShape::Single(3),
+            OutputMode::Same,
+            PadMode::Constant(0.0),
+            method,
+        )
+        .unwrap();
+        let above: Vec<_> = mask.indexed_iter().filter(|(_, &m)| m).map(|(i, _)| i).collect();
+        assert_eq!(vec![(4, 4, 0)], above);
+    }
+
+    #[test]
+    fn nan_only_masks_its_windows() {
+        let mut img = Array3::<f32>::zeros((16, 12, 1));
+        img[[4, 4, 0]] = f32::NAN;
+        // bright pixels next to the NaN, one inside its windows and two just outside them
+        for p in [[4, 5, 0], [4, 6, 0], [6, 4, 0], [12, 9, 0]] {
+            img[p] = 1.0;
+        }
+        let win = WindowShape::Single(3);
+        for method in [
+            Threshold::Niblack { k: 0.0 },
+            Threshold::Sauvola { k: 0.2, r: None },
+            Threshold::Wolf { k: 0.5 },
+        ] {
+            let mask =
+                thread_threshold(img.view(), win, OutputMode::Same, PadMode::Edge, method).unwrap();
+            let above: Vec<_> = mask.indexed_iter().filter(|(_, &m)| m).map(|(i, _)| i).collect();
+            assert_eq!(vec![(4, 6, 0), (6, 4, 0), (12, 9, 0)], above, "{:?}", method);
+        }
+    }
+
+    #[test]
+    fn invalid_threshold_parameters() {
+        let img = Array3::<u8>::zeros((8, 8, 1));
+        let win = WindowShape::Single(3);
+        let run =
+            |method| thread_threshold(img.view(), win, OutputMode::Same, PadMode::Reflect, method);
+        assert!(matches!(
+            run(Threshold::Sauvola { k: 0.2, r: Some(0.0) }),
+            Err(PyLensError::InvalidArgument { name: "r", .. })
+        ));
+        assert!(matches!(
+            run(Threshold::Niblack { k: f64::NAN }),
+            Err(PyLensError::NonFinite { name: "k", .. })
+        ));
+        // a flat image is never above its own threshold
+        assert!(!run(Threshold::Wolf { k: 0.5 }).unwrap().iter().any(|&m| m));
+    }
+}
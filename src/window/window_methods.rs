@@ -1,117 +1,683 @@
 use ndarray::{ArrayView, Ix3};
+use std::cmp::Ordering;
+use std::str::FromStr;
 
+use super::WindowShape;
+use crate::error::{check_finite, PyLensError};
+
+/// signature of the window functions in this module without parameters, see [`Reducer`] for the
+/// ones that have them
+#[allow(dead_code)]
 pub type WinFunc<T> = fn(ArrayView<T, Ix3>) -> T;
 
+/// a window statistic that can hold parameters, what [`super::thread_apply_over_window`] applies
+/// to every window
+///
+/// every `Fn(ArrayView<T, Ix3>) -> O` is a reducer, so the functions in this module and closures
+/// can be used as is, the structs below carry the parameters of the statistics that need them:
+/// [`Stdev`], [`Variance`], [`Percentile`], [`FractionAbove`] and [`Lbp`]
+pub trait Reducer<T, O>: Sync {
+    fn reduce(&self, w: ArrayView<T, Ix3>) -> O;
+}
+
+impl<T, O, F> Reducer<T, O> for F
+where
+    F: Fn(ArrayView<T, Ix3>) -> O + Sync,
+{
+    fn reduce(&self, w: ArrayView<T, Ix3>) -> O {
+        self(w)
+    }
+}
+
+/// standard deviation with `ddof` delta degrees of freedom, like `numpy.std`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stdev {
+    pub ddof: f64,
+}
+
+impl<T: NumConv> Reducer<T, f64> for Stdev {
+    fn reduce(&self, w: ArrayView<T, Ix3>) -> f64 {
+        variance(w, self.ddof).sqrt()
+    }
+}
+
+/// variance with `ddof` delta degrees of freedom, see [`variance`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Variance {
+    pub ddof: f64,
+}
+
+impl<T: NumConv> Reducer<T, f64> for Variance {
+    fn reduce(&self, w: ArrayView<T, Ix3>) -> f64 {
+        variance(w, self.ddof)
+    }
+}
+
+/// `q`th percentile, see [`percentile`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Percentile {
+    q: f64,
+}
+
+impl Percentile {
+    /// # Errors
+    /// [`PyLensError::NonFinite`] or [`PyLensError::InvalidArgument`] if `q` is not in `0..=100`
+    pub fn new(q: f64) -> Result<Self, PyLensError> {
+        if !(0.0..=100.0).contains(&check_finite("q", q)?) {
+            return Err(PyLensError::InvalidArgument {
+                name: "q",
+                reason: format!("percentiles are between 0 and 100, got {}", q),
+            });
+        }
+        Ok(Percentile { q })
+    }
+
+    pub fn q(self) -> f64 {
+        self.q
+    }
+}
+
+impl<T: NumConv + Clone> Reducer<T, f64> for Percentile {
+    fn reduce(&self, w: ArrayView<T, Ix3>) -> f64 {
+        percentile(w, self.q)
+    }
+}
+
+/// fraction of the window strictly above `threshold`, between 0 and 1, NaN is never above
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FractionAbove {
+    pub threshold: f64,
+}
+
+impl<T: NumConv> Reducer<T, f64> for FractionAbove {
+    fn reduce(&self, w: ArrayView<T, Ix3>) -> f64 {
+        let above = w.iter().filter(|x| x.as_f64() > self.threshold).count();
+        above as f64 / w.len() as f64
+    }
+}
+
+/// one statistic of [`Stats`], named like the `windowed_<stat>` python functions
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Stat {
+    Rms,
+    Stdev,
+    Mean,
+    Variance,
+    Min,
+    Max,
+    Range,
+    Median,
+    /// `"p<q>"` such as `"p90"` or `"p2.5"`
+    Percentile(Percentile),
+    Mode,
+    Skewness,
+    Kurtosis,
+    MeanAbsDev,
+}
+
+impl Stat {
+    /// needs the mean and central moments of the window
+    fn uses_moments(self) -> bool {
+        matches!(
+            self,
+            Stat::Rms
+                | Stat::Stdev
+                | Stat::Mean
+                | Stat::Variance
+                | Stat::Skewness
+                | Stat::Kurtosis
+                | Stat::MeanAbsDev
+        )
+    }
+}
+
+impl FromStr for Stat {
+    type Err = PyLensError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "rms" => Stat::Rms,
+            "stdev" => Stat::Stdev,
+            "mean" => Stat::Mean,
+            "variance" => Stat::Variance,
+            "min" => Stat::Min,
+            "max" => Stat::Max,
+            "range" => Stat::Range,
+            "median" => Stat::Median,
+            "mode" => Stat::Mode,
+            "skewness" => Stat::Skewness,
+            "kurtosis" => Stat::Kurtosis,
+            "mean_abs_dev" => Stat::MeanAbsDev,
+            _ => match s.strip_prefix('p').map(str::parse::<f64>) {
+                Some(Ok(q)) => Stat::Percentile(Percentile::new(q)?),
+                _ => {
+                    return Err(PyLensError::InvalidArgument {
+                        name: "stats",
+                        reason: format!(
+                            "expected 'rms', 'stdev', 'mean', 'variance', 'min', 'max', 'range', \
+                             'median', 'mode', 'skewness', 'kurtosis', 'mean_abs_dev' or a \
+                             percentile such as 'p90', got {:?}",
+                            s
+                        ),
+                    })
+                }
+            },
+        })
+    }
+}
+
+/// several statistics of the same window computed together, the window is read once, the
+/// moments are shared by every statistic that uses them and the values are sorted once for
+/// every order statistic
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    stats: Vec<Stat>,
+    ddof: f64,
+}
+
+impl Stats {
+    /// `ddof` is used by [`Stat::Stdev`] and [`Stat::Variance`], [`Stat::Rms`] is the
+    /// standard deviation with a ddof of 0
+    ///
+    /// # Errors
+    /// [`PyLensError::InvalidArgument`] if `stats` is empty
+    pub fn new(stats: Vec<Stat>, ddof: f64) -> Result<Self, PyLensError> {
+        if stats.is_empty() {
+            return Err(PyLensError::InvalidArgument {
+                name: "stats",
+                reason: "at least one statistic is needed".to_string(),
+            });
+        }
+        Ok(Stats { stats, ddof })
+    }
+
+    /// number of statistics, the length of the `out` slice of [`Stats::reduce_into`]
+    pub fn count(&self) -> usize {
+        self.stats.len()
+    }
+
+    /// write every statistic of `w` into `out`, in the order they were given, each value is
+    /// what the matching function of this module returns
+    pub fn reduce_into<T: NumConv>(&self, w: ArrayView<T, Ix3>, out: &mut [f64]) {
+        let mut values: Vec<f64> = w.iter().map(|x| x.as_f64()).collect();
+        let n = values.len() as f64;
+        let (mut mean, mut m2, mut m3, mut m4, mut mad) = (0f64, 0f64, 0f64, 0f64, 0f64);
+        if self.stats.iter().any(|s| s.uses_moments()) {
+            mean = values.iter().sum::<f64>() / n;
+            for x in &values {
+                let d = x - mean;
+                let d2 = d * d;
+                m2 += d2;
+                m3 += d2 * d;
+                m4 += d2 * d2;
+                mad += d.abs();
+            }
+            (m2, m3, m4, mad) = (m2 / n, m3 / n, m4 / n, mad / n);
+        }
+        if self.stats.iter().any(|s| !s.uses_moments()) {
+            values.sort_unstable_by(f64::total_cmp);
+        }
+        let sorted = &values;
+        for (o, stat) in out.iter_mut().zip(&self.stats) {
+            *o = match *stat {
+                Stat::Rms => m2.sqrt(),
+                Stat::Stdev => (m2 * n / (n - self.ddof)).sqrt(),
+                Stat::Mean => mean,
+                Stat::Variance => m2 * n / (n - self.ddof),
+                Stat::Min => sorted[0],
+                Stat::Max => sorted[sorted.len() - 1],
+                Stat::Range => sorted[sorted.len() - 1] - sorted[0],
+                Stat::Median => sorted_percentile(sorted, 50.0),
+                Stat::Percentile(p) => sorted_percentile(sorted, p.q),
+                Stat::Mode => sorted_mode(sorted),
+                Stat::Skewness => m3 / m2.powf(1.5),
+                Stat::Kurtosis => m4 / (m2 * m2) - 3.0,
+                Stat::MeanAbsDev => mad,
+            };
+        }
+    }
+}
+
+/// how the bits of a local binary pattern become its code, see [`Lbp`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LbpMapping {
+    /// bit `p` is set when neighbour `p` is at least the centre, `2^points` codes
+    Default,
+    /// the smallest code of every rotation of the bits, so it doesn't change when the image
+    /// is rotated by a multiple of the angle between two neighbours
+    RotationInvariant,
+    /// rotation invariant uniform patterns, the number of set bits for patterns with at most two
+    /// changes between 0 and 1 around the circle, `points + 1` for every other pattern
+    Uniform,
+}
+
+impl FromStr for LbpMapping {
+    type Err = PyLensError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(LbpMapping::Default),
+            "ror" => Ok(LbpMapping::RotationInvariant),
+            "uniform" => Ok(LbpMapping::Uniform),
+            _ => Err(PyLensError::InvalidArgument {
+                name: "method",
+                reason: format!("expected 'default', 'ror' or 'uniform', got {:?}", s),
+            }),
+        }
+    }
+}
+
+/// local binary pattern of the centre of a square window, each neighbour is bilinearly
+/// interpolated from the 4 elements around it
+///
+/// neighbour `p` is at angle `2 * pi * p / points`, counter clockwise from the element right of
+/// the centre, same as `skimage.feature.local_binary_pattern`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lbp {
+    mapping: LbpMapping,
+    /// width of the window, the centre is at `[size / 2, size / 2]`
+    size: usize,
+    /// top left element and the weights of the 4 elements around every neighbour
+    samples: Vec<((usize, usize), [f64; 4])>,
+}
+
+impl Lbp {
+    /// the original 3x3 pattern, the 8 neighbours of the centre without interpolation
+    pub fn basic(mapping: LbpMapping) -> Self {
+        let samples = (0..8)
+            .map(|p| {
+                let angle = std::f64::consts::FRAC_PI_4 * p as f64;
+                let (dy, dx) = (-angle.sin().round(), angle.cos().round());
+                (((1.0 + dy) as usize, (1.0 + dx) as usize), [1.0, 0.0, 0.0, 0.0])
+            })
+            .collect();
+        Lbp { mapping, size: 3, samples }
+    }
+
+    /// `points` neighbours evenly spaced on a circle of `radius` around the centre
+    ///
+    /// # Errors
+    /// [`PyLensError::InvalidArgument`] if `points` is not in `1..=32` or `radius` is not above
+    /// 0, [`PyLensError::NonFinite`] for a NaN or infinite radius
+    pub fn circular(points: usize, radius: f64, mapping: LbpMapping) -> Result<Self, PyLensError> {
+        if !(1..=32).contains(&points) {
+            return Err(PyLensError::InvalidArgument {
+                name: "points",
+                reason: format!("codes have 32 bits, expected 1 to 32 points, got {}", points),
+            });
+        }
+        if check_finite("radius", radius)? <= 0.0 {
+            return Err(PyLensError::InvalidArgument {
+                name: "radius",
+                reason: format!("must be above 0, got {}", radius),
+            });
+        }
+        let c = radius.ceil();
+        let size = 2 * c as usize + 1;
+        // positions that are meant to be whole, e.g. radius * sin(pi), snap to the element
+        let snap = |v: f64| if (v - v.round()).abs() < 1e-9 { v.round() } else { v };
+        let samples = (0..points)
+            .map(|p| {
+                let angle = 2.0 * std::f64::consts::PI * p as f64 / points as f64;
+                let (y, x) = (snap(c - radius * angle.sin()), snap(c + radius * angle.cos()));
+                let (i, j) = (y.floor().min(size as f64 - 2.0), x.floor().min(size as f64 - 2.0));
+                let (fy, fx) = (y - i, x - j);
+                let weights = [(1.0 - fy) * (1.0 - fx), (1.0 - fy) * fx, fy * (1.0 - fx), fy * fx];
+                ((i as usize, j as usize), weights)
+            })
+            .collect();
+        Ok(Lbp { mapping, size, samples })
+    }
+
+    /// square window around every element, with a depth of 1
+    pub fn shape(&self) -> WindowShape {
+        WindowShape::Double(self.size, self.size)
+    }
+
+    /// number of different codes, `code < bins()` for every code
+    pub fn bins(&self) -> usize {
+        match self.mapping {
+            LbpMapping::Default | LbpMapping::RotationInvariant => 1 << self.samples.len(),
+            LbpMapping::Uniform => self.samples.len() + 2,
+        }
+    }
+}
+
+impl<T: NumConv> Reducer<T, u32> for Lbp {
+    fn reduce(&self, w: ArrayView<T, Ix3>) -> u32 {
+        let c = self.size / 2;
+        let centre = w[[c, c, 0]].as_f64();
+        let p = self.samples.len() as u32;
+        let mut bits = 0u64;
+        for (n, &((i, j), [a, b, d, e])) in self.samples.iter().enumerate() {
+            // whole positions only read their own element, the basic pattern has no room for more
+            let neighbour = match (b, d, e) {
+                (0.0, 0.0, 0.0) => w[[i, j, 0]].as_f64(),
+                _ => {
+                    a * w[[i, j, 0]].as_f64()
+                        + b * w[[i, j + 1, 0]].as_f64()
+                        + d * w[[i + 1, j, 0]].as_f64()
+                        + e * w[[i + 1, j + 1, 0]].as_f64()
+                }
+            };
+            if neighbour >= centre {
+                bits |= 1 << n;
+            }
+        }
+        let mask = (1u64 << p) - 1;
+        let rotate = |r: u32| ((bits >> r) | (bits << (p - r))) & mask;
+        match self.mapping {
+            LbpMapping::Default => bits as u32,
+            LbpMapping::RotationInvariant => (0..p).map(rotate).min().unwrap_or(0) as u32,
+            LbpMapping::Uniform => match (bits ^ rotate(1)).count_ones() {
+                0..=2 => bits.count_ones(),
+                _ => p + 1,
+            },
+        }
+    }
+}
+
+/// fraction of the codes of `w` equal to every index of `out`, codes past the end of `out` are
+/// counted in the total but not in any bin
+pub fn code_histogram(w: ArrayView<u32, Ix3>, out: &mut [f64]) {
+    out.iter_mut().for_each(|o| *o = 0.0);
+    let step = 1.0 / w.len() as f64;
+    for &code in w.iter() {
+        if let Some(o) = out.get_mut(code as usize) {
+            *o += step;
+        }
+    }
+}
+
+/// conversion between the element types of the window engine and `f64`,
+/// statistics are computed in `f64` and converted back with [`NumConv::from_f64`]
 pub trait NumConv {
+    /// nearest value of `Self`, integer types round to the nearest integer and saturate at
+    /// their range, NaN becomes 0
     fn from_f64(f: f64) -> Self;
-    fn as_u64(&self) -> u64;
     fn as_f64(&self) -> f64;
-    fn clamp_rms_max(f: f64) -> Self;
 }
 
 impl NumConv for u8 {
     fn from_f64(f: f64) -> Self {
-        f as u8
-    }
-    fn as_u64(&self) -> u64 {
-        *self as u64
+        f.round() as u8
     }
     fn as_f64(&self) -> f64 {
         *self as f64
     }
-
-    fn clamp_rms_max(f: f64) -> Self {
-        Self::from_f64(f * 2.0)
-    }
 }
 
 impl NumConv for u16 {
     fn from_f64(f: f64) -> Self {
-        f as u16
-    }
-    fn as_u64(&self) -> u64 {
-        *self as u64
+        f.round() as u16
     }
     fn as_f64(&self) -> f64 {
         *self as f64
     }
+}
 
+impl NumConv for u32 {
+    fn from_f64(f: f64) -> Self {
+        f.round() as u32
+    }
+    fn as_f64(&self) -> f64 {
+        *self as f64
+    }
+}
 
-    fn clamp_rms_max(f: f64) -> Self {
-        Self::from_f64(f * 2.0)
+impl NumConv for u64 {
+    fn from_f64(f: f64) -> Self {
+        f.round() as u64
+    }
+    fn as_f64(&self) -> f64 {
+        *self as f64
     }
 }
 
-impl NumConv for u32 {
+impl NumConv for i8 {
     fn from_f64(f: f64) -> Self {
-        f as u32
+        f.round() as i8
+    }
+    fn as_f64(&self) -> f64 {
+        *self as f64
     }
-    fn as_u64(&self) -> u64 {
-        *self as u64
+}
+
+impl NumConv for i16 {
+    fn from_f64(f: f64) -> Self {
+        f.round() as i16
     }
     fn as_f64(&self) -> f64 {
         *self as f64
     }
+}
 
-    fn clamp_rms_max(f: f64) -> Self {
-        Self::from_f64(f * 2.0)
+impl NumConv for i32 {
+    fn from_f64(f: f64) -> Self {
+        f.round() as i32
+    }
+    fn as_f64(&self) -> f64 {
+        *self as f64
     }
 }
 
-impl NumConv for u64 {
+impl NumConv for i64 {
     fn from_f64(f: f64) -> Self {
-        f as u64
+        f.round() as i64
     }
-    fn as_u64(&self) -> u64 {
-        *self
+    fn as_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl NumConv for f32 {
+    fn from_f64(f: f64) -> Self {
+        f as f32
     }
     fn as_f64(&self) -> f64 {
         *self as f64
     }
+}
 
-    fn clamp_rms_max(f: f64) -> Self {
-        Self::from_f64(f * 2.0)
+impl NumConv for f64 {
+    fn from_f64(f: f64) -> Self {
+        f
+    }
+    fn as_f64(&self) -> f64 {
+        *self
     }
 }
 
-/// builtin rms with ndarray
-pub fn stdev_ddof_0<T>(w: ArrayView<T, Ix3>) -> T
+/// builtin standard deviation with ndarray, `ddof` is the delta degrees of freedom
+#[allow(dead_code)] // reference for window::integral::thread_integral_stdev
+pub fn stdev<T>(w: ArrayView<T, Ix3>, ddof: f64) -> T
     where
         T: NumConv,
         T: Clone,
 {
     let w = w.mapv(|elem: T| elem.as_f64());
-    let w = w.std(0f64);
+    let w = w.std(ddof);
     T::from_f64(w)
 }
 
-/// builtin rms with ndarray
+/// [`stdev`] with a ddof of 0
+#[allow(dead_code)] // reference for window::integral::thread_integral_stdev
+pub fn stdev_ddof_0<T>(w: ArrayView<T, Ix3>) -> T
+    where
+        T: NumConv,
+        T: Clone,
+{
+    stdev(w, 0.0)
+}
+
+/// [`stdev`] with a ddof of 1
+#[allow(dead_code)] // reference for window::integral::thread_integral_stdev
 pub fn stdev_ddof_1<T>(w: ArrayView<T, Ix3>) -> T
     where
         T: NumConv,
         T: Clone,
 {
-    let w = w.mapv(|elem: T| elem.as_f64());
-    let w = w.std(1f64);
-    T::from_f64(w)
+    stdev(w, 1.0)
 }
 
 /// slightly faster than the ndarray rms, need to test more, but I like this one more...
+#[allow(dead_code)] // reference for window::integral::thread_integral_rms
 pub fn faster_rms_u64_adding<T: NumConv>(w: ArrayView<T, Ix3>) -> T {
     let len_inv = (w.len() as f64).recip();
-    let mean: f64 = w
-        .iter()
-        .fold(0u64, |a: u64, x: &T| a.saturating_add(x.as_u64())) as f64
-        * len_inv;
+    // summed as f64 so negative values count, integers below 2^53 add up exactly
+    let mean: f64 = w.iter().fold(0f64, |a: f64, x: &T| a + x.as_f64()) * len_inv;
     let flt: f64 = w
         .iter()
         .fold(0f64, |a: f64, x: &T| a + (x.as_f64() - mean).abs().powi(2));
 
-    T::clamp_rms_max((flt * len_inv).sqrt())
+    T::from_f64((flt * len_inv).sqrt())
+}
+
+// the statistics below return the exact `f64` value, or the input type for the ones that pick
+// a value out of the window, the caller decides how to convert and scale it
+
+/// windowed mean
+pub fn mean<T: NumConv>(w: ArrayView<T, Ix3>) -> f64 {
+    mean_f64(&w)
+}
+
+/// windowed variance, `ddof` is the delta degrees of freedom like `numpy.var`
+pub fn variance<T: NumConv>(w: ArrayView<T, Ix3>, ddof: f64) -> f64 {
+    let (_, m2, _, _) = central_moments(&w);
+    m2 * w.len() as f64 / (w.len() as f64 - ddof)
+}
+
+/// smallest value in the window, see [`cmp`] for NaN
+pub fn min<T: NumConv + Clone>(w: ArrayView<T, Ix3>) -> T {
+    w.iter().min_by(|a, b| cmp(*a, *b)).expect("windows are never empty").clone()
+}
+
+/// largest value in the window, see [`cmp`] for NaN
+pub fn max<T: NumConv + Clone>(w: ArrayView<T, Ix3>) -> T {
+    w.iter().max_by(|a, b| cmp(*a, *b)).expect("windows are never empty").clone()
+}
+
+/// largest minus smallest value in the window, as `f64` so `i8` windows holding both -128 and
+/// 127 don't overflow
+pub fn range<T: NumConv + Clone>(w: ArrayView<T, Ix3>) -> f64 {
+    max(w.view()).as_f64() - min(w).as_f64()
+}
+
+/// windowed median, the mean of the two middle values for windows with an even size
+#[allow(dead_code)]
+pub fn median<T: NumConv + Clone>(w: ArrayView<T, Ix3>) -> f64 {
+    percentile(w, 50.0)
+}
+
+/// `q`th percentile of the window, `q` in `0..=100`, interpolated linearly between the two
+/// nearest values like the default method of `numpy.percentile`
+pub fn percentile<T: NumConv + Clone>(w: ArrayView<T, Ix3>, q: f64) -> f64 {
+    let mut values: Vec<T> = w.iter().cloned().collect();
+    let pos = q / 100.0 * (values.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let frac = pos - lo as f64;
+    // only the values either side of `pos` need to be in place, not the whole window
+    let (_, below, above) = values.select_nth_unstable_by(lo, cmp);
+    if frac == 0.0 || above.is_empty() {
+        return below.as_f64();
+    }
+    let next = above.iter().min_by(|a, b| cmp(*a, *b)).unwrap();
+    let (a, b) = (below.as_f64(), next.as_f64());
+    a + (b - a) * frac
+}
+
+/// `k`th smallest value of the window counting from 0, ordered by [`cmp`], `k` must be below
+/// the window size
+pub fn nth_smallest<T: NumConv + Clone>(w: ArrayView<T, Ix3>, k: usize) -> T {
+    let mut values: Vec<T> = w.iter().cloned().collect();
+    let (_, nth, _) = values.select_nth_unstable_by(k, cmp);
+    nth.clone()
+}
+
+/// most common value in the window, the smallest of them if there is a tie
+pub fn mode<T: NumConv + PartialEq + Clone>(w: ArrayView<T, Ix3>) -> T {
+    let mut values: Vec<T> = w.iter().cloned().collect();
+    values.sort_unstable_by(cmp);
+    let mut best = (0, 0);
+    let mut start = 0;
+    for i in 1..=values.len() {
+        if i == values.len() || values[i] != values[start] {
+            if i - start > best.1 {
+                best = (start, i - start);
+            }
+            start = i;
+        }
+    }
+    values.swap_remove(best.0)
+}
+
+/// windowed skewness, `m3 / m2^1.5` from the biased central moments like `scipy.stats.skew`,
+/// flat windows have no skewness and give NaN
+pub fn skewness<T: NumConv>(w: ArrayView<T, Ix3>) -> f64 {
+    let (_, m2, m3, _) = central_moments(&w);
+    m3 / m2.powf(1.5)
+}
+
+/// windowed excess kurtosis, `m4 / m2^2 - 3` from the biased central moments like
+/// `scipy.stats.kurtosis`, flat windows give NaN
+pub fn kurtosis<T: NumConv>(w: ArrayView<T, Ix3>) -> f64 {
+    let (_, m2, _, m4) = central_moments(&w);
+    m4 / (m2 * m2) - 3.0
+}
+
+/// mean absolute deviation from the window mean
+pub fn mean_abs_dev<T: NumConv>(w: ArrayView<T, Ix3>) -> f64 {
+    let mean = mean_f64(&w);
+    let total: f64 = w.iter().map(|x| (x.as_f64() - mean).abs()).sum();
+    total / w.len() as f64
+}
+
+fn mean_f64<T: NumConv>(w: &ArrayView<T, Ix3>) -> f64 {
+    w.iter().map(|x| x.as_f64()).sum::<f64>() / w.len() as f64
+}
+
+/// mean and the 2nd, 3rd and 4th central moments, each divided by the window size
+fn central_moments<T: NumConv>(w: &ArrayView<T, Ix3>) -> (f64, f64, f64, f64) {
+    let mean = mean_f64(w);
+    let (mut m2, mut m3, mut m4) = (0f64, 0f64, 0f64);
+    for x in w.iter() {
+        let d = x.as_f64() - mean;
+        let d2 = d * d;
+        m2 += d2;
+        m3 += d2 * d;
+        m4 += d2 * d2;
+    }
+    let n = w.len() as f64;
+    (mean, m2 / n, m3 / n, m4 / n)
+}
+
+/// [`percentile`] of values sorted by [`cmp`]
+fn sorted_percentile(sorted: &[f64], q: f64) -> f64 {
+    let pos = q / 100.0 * (sorted.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let frac = pos - lo as f64;
+    if frac == 0.0 || lo + 1 == sorted.len() {
+        return sorted[lo];
+    }
+    sorted[lo] + (sorted[lo + 1] - sorted[lo]) * frac
+}
+
+/// [`mode`] of values sorted by [`cmp`]
+fn sorted_mode(sorted: &[f64]) -> f64 {
+    sorted
+        .chunk_by(|a, b| a == b)
+        .fold(&sorted[..1], |best, run| if run.len() > best.len() { run } else { best })[0]
+}
+
+/// ordering for sorting window values, NaN sorts above every number like `numpy.sort`, so it is
+/// the max of a window holding one and only the min of a window of NaN
+pub(crate) fn cmp<T: NumConv>(a: &T, b: &T) -> Ordering {
+    a.as_f64().total_cmp(&b.as_f64())
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::error::PyLensError;
     use crate::window;
     use ndarray::Array3;
     use window::window_methods::*;
@@ -157,4 +723,196 @@ mod tests {
         b.hash(&mut hasher);
         assert_eq!(7541581120933061747, hasher.finish());
     }
+
+    fn window_of(values: &[u16]) -> Array3<u16> {
+        Array3::from_shape_vec((1, values.len(), 1), values.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_order_statistics() {
+        let a = window_of(&[7, 3, 9, 3, 1, 12, 3, 9]);
+        assert_eq!(1, min(a.view()));
+        assert_eq!(12, max(a.view()));
+        assert_eq!(11.0, range(a.view()));
+        assert_eq!(3, mode(a.view()));
+        // sorted: 1 3 3 3 7 9 9 12, median is (3 + 7) / 2
+        assert_eq!(5.0, median(a.view()));
+        assert_eq!(1.0, percentile(a.view(), 0.0));
+        assert_eq!(12.0, percentile(a.view(), 100.0));
+        // position 0.9 * 7 = 6.3, 9 + 0.3 * (12 - 9)
+        assert!((9.9 - percentile(a.view(), 90.0)).abs() < 1e-12);
+        let sorted: Vec<_> = (0..8).map(|k| nth_smallest(a.view(), k)).collect();
+        assert_eq!(vec![1, 3, 3, 3, 7, 9, 9, 12], sorted);
+    }
+
+    #[test]
+    fn test_mode_tie_is_smallest() {
+        assert_eq!(2, mode(window_of(&[5, 2, 5, 2, 8]).view()));
+        assert_eq!(4, mode(window_of(&[4]).view()));
+    }
+
+    #[test]
+    fn test_moments() {
+        let a = window_of(&[2, 4, 4, 4, 5, 5, 7, 9]);
+        assert_eq!(5.0, mean(a.view()));
+        assert_eq!(4.0, variance(a.view(), 0.0));
+        assert_eq!(32.0 / 7.0, variance(a.view(), 1.0));
+        assert_eq!(1.5, mean_abs_dev(a.view()));
+
+        // one value far above the rest: m2 = 7, m3 = 42, m4 = 301
+        let a = window_of(&[1, 1, 1, 1, 1, 1, 1, 9]);
+        assert!((42.0 / 7f64.powf(1.5) - skewness(a.view())).abs() < 1e-12);
+        assert!((301.0 / 49.0 - 3.0 - kurtosis(a.view())).abs() < 1e-12);
+        // flat windows have no shape
+        assert!(skewness(window_of(&[3, 3, 3]).view()).is_nan());
+        assert!(kurtosis(window_of(&[3, 3, 3]).view()).is_nan());
+    }
+
+    #[test]
+    fn test_signed_and_float() {
+        let a = Array3::from_shape_vec((1, 4, 1), vec![-128i8, 127, -3, 0]).unwrap();
+        assert_eq!(-128, min(a.view()));
+        assert_eq!(127, max(a.view()));
+        assert_eq!(255.0, range(a.view()));
+        assert_eq!(-1.5, median(a.view()));
+        // deviations from the mean of -1: -127 128 -2 1
+        let expected = ((127f64.powi(2) + 128f64.powi(2) + 4.0 + 1.0) / 4.0).sqrt();
+        assert_eq!(expected.round() as i8, faster_rms_u64_adding(a.view()));
+
+        let a = Array3::from_shape_vec((1, 4, 1), vec![-2.5f32, 0.5, -0.25, 1.0]).unwrap();
+        assert_eq!(-2.5, min(a.view()));
+        assert_eq!(0.125, median(a.view()));
+        let expected = stdev_ddof_0(a.view());
+        assert!((expected - faster_rms_u64_adding(a.view())).abs() < 1e-6);
+
+        let a = Array3::from_shape_vec((1, 3, 1), vec![2.0f64, f64::NAN, -1.0]).unwrap();
+        assert_eq!(-1.0, min(a.view()));
+        assert!(max(a.view()).is_nan());
+        assert_eq!(2.0, median(a.view()));
+    }
+
+    #[test]
+    fn test_reducers() {
+        let a = window_of(&[2, 4, 4, 4, 5, 5, 7, 9]);
+        assert_eq!(2.0, Stdev { ddof: 0.0 }.reduce(a.view()));
+        assert_eq!(32.0 / 7.0, Variance { ddof: 1.0 }.reduce(a.view()));
+        assert_eq!(stdev::<f64>(a.mapv(|x| x as f64).view(), 1.0), Stdev { ddof: 1.0 }.reduce(a.view()));
+        assert_eq!(4.5, Percentile::new(50.0).unwrap().reduce(a.view()));
+        assert_eq!(0.5, FractionAbove { threshold: 4.0 }.reduce(a.view()));
+        // plain functions are reducers too
+        assert_eq!(mean(a.view()), mean::<u16>.reduce(a.view()));
+        for bad in [-1.0, 100.5, f64::NAN] {
+            assert!(Percentile::new(bad).is_err());
+        }
+    }
+
+    #[test]
+    fn test_from_f64_rounds_and_saturates() {
+        assert_eq!(3u8, u8::from_f64(2.5));
+        assert_eq!(2u8, u8::from_f64(2.49));
+        assert_eq!(255u8, u8::from_f64(300.0));
+        assert_eq!(0u8, u8::from_f64(-4.0));
+        assert_eq!(0u16, u16::from_f64(f64::NAN));
+        assert_eq!(-3i8, i8::from_f64(-2.5));
+        assert_eq!(-128i8, i8::from_f64(-1000.0));
+        assert_eq!(0.1f32, f32::from_f64(0.1));
+        assert_eq!(0.1f64, f64::from_f64(0.1));
+    }
+
+    #[test]
+    fn test_stats_match_single_statistics() {
+        let stats: Vec<Stat> = [
+            "rms", "stdev", "mean", "variance", "min", "max", "range", "median", "p90", "p0",
+            "mode", "skewness", "kurtosis", "mean_abs_dev",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        let stats = Stats::new(stats, 1.0).unwrap();
+        for values in [&[7u16, 3, 9, 3, 1, 12, 3, 9][..], &[5, 2, 5, 2, 8], &[4], &[1, 1, 1, 1, 9]] {
+            let a = window_of(values);
+            let w = a.view();
+            let mut out = vec![0f64; stats.count()];
+            stats.reduce_into(w, &mut out);
+            let expected = [
+                variance(w, 0.0).sqrt(),
+                variance(w, 1.0).sqrt(),
+                mean(w),
+                variance(w, 1.0),
+                min(w) as f64,
+                max(w) as f64,
+                range(w),
+                median(w),
+                percentile(w, 90.0),
+                percentile(w, 0.0),
+                mode(w) as f64,
+                skewness(w),
+                kurtosis(w),
+                mean_abs_dev(w),
+            ];
+            for (i, (e, o)) in expected.iter().zip(&out).enumerate() {
+                assert!(
+                    (e - o).abs() < 1e-12 || (e.is_nan() && o.is_nan()),
+                    "{:?} stat {}: {} != {}",
+                    values,
+                    i,
+                    e,
+                    o
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_stat_names() {
+        assert_eq!(Ok(Stat::MeanAbsDev), "mean_abs_dev".parse());
+        assert_eq!(Ok(Stat::Percentile(Percentile::new(2.5).unwrap())), "p2.5".parse());
+        assert!(matches!("p101".parse::<Stat>(), Err(PyLensError::InvalidArgument { name: "q", .. })));
+        assert!(matches!("sum".parse::<Stat>(), Err(PyLensError::InvalidArgument { name: "stats", .. })));
+        assert!(matches!("p".parse::<Stat>(), Err(PyLensError::InvalidArgument { name: "stats", .. })));
+        assert!(matches!(Stats::new(vec![], 0.0), Err(PyLensError::InvalidArgument { name: "stats", .. })));
+    }
+
+    #[test]
+    fn test_local_binary_patterns() {
+        let w = Array3::from_shape_vec((3, 3, 1), vec![6u8, 5, 2, 7, 5, 1, 9, 8, 7]).unwrap();
+        let code = |lbp: Lbp| lbp.reduce(w.view());
+        assert_eq!(252, code(Lbp::basic(LbpMapping::Default)));
+        assert_eq!(63, code(Lbp::basic(LbpMapping::RotationInvariant)));
+        assert_eq!(6, code(Lbp::basic(LbpMapping::Uniform)));
+        assert_eq!(252, code(Lbp::circular(8, 1.0, LbpMapping::Default).unwrap()));
+
+        // a quarter turn moves every neighbour on by two of the 8 points
+        use crate::window::thread_apply_over_window;
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(43);
+        let img: Array3<f64> = Array3::from_shape_fn((17, 13, 1), |_| rng.gen());
+        let turned = Array3::from_shape_fn((13, 17, 1), |(i, j, k)| img[[j, 12 - i, k]]);
+        for mapping in [LbpMapping::RotationInvariant, LbpMapping::Uniform] {
+            let lbp = Lbp::circular(8, 2.0, mapping).unwrap();
+            let codes = thread_apply_over_window(img.view(), lbp.shape(), lbp.clone()).unwrap();
+            let found = thread_apply_over_window(turned.view(), lbp.shape(), lbp.clone()).unwrap();
+            assert_eq!([9, 13, 1], found.shape());
+            for ((i, j, k), &f) in found.indexed_iter() {
+                assert_eq!(codes[[j, 8 - i, k]], f);
+                assert!((f as usize) < lbp.bins());
+            }
+        }
+
+        let codes = Array3::from_shape_vec((2, 3, 1), vec![0u32, 1, 1, 3, 9, 1]).unwrap();
+        let mut out = [0.0; 4];
+        code_histogram(codes.view(), &mut out);
+        assert_eq!([1.0 / 6.0, 0.5, 0.0, 1.0 / 6.0], out);
+
+        assert_eq!(10, Lbp::circular(8, 1.5, LbpMapping::Uniform).unwrap().bins());
+        assert!(matches!(
+            Lbp::circular(33, 1.0, LbpMapping::Default),
+            Err(PyLensError::InvalidArgument { name: "points", .. })
+        ));
+        assert!(matches!(
+            Lbp::circular(8, 0.0, LbpMapping::Default),
+            Err(PyLensError::InvalidArgument { name: "radius", .. })
+        ));
+        assert!(matches!("riu2".parse::<LbpMapping>(), Err(PyLensError::InvalidArgument { .. })));
+    }
 }